@@ -5,8 +5,9 @@ use crate::etype::eobject::EObject;
 use crate::json_utils::{json_expected, json_kind, JsonValue};
 use crate::m_try;
 use crate::registry::{EObjectType, ETypesRegistry};
-use crate::value::id::{EListId, EMapId, ETypeId};
+use crate::value::id::{EListId, EMapId, ETupleId, ETypeId};
 use crate::value::EValue;
+use itertools::Itertools;
 use miette::{bail, miette, Context};
 use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
@@ -19,6 +20,8 @@ use strum::EnumIs;
 use ustr::Ustr;
 use utils::whatever_ref::{WhateverRef, WhateverRefMap};
 
+pub mod computed;
+pub mod condition;
 pub mod conversion;
 pub mod default;
 pub mod econst;
@@ -26,6 +29,8 @@ pub mod eenum;
 pub mod eitem;
 pub mod eobject;
 pub mod estruct;
+pub mod generic;
+pub mod migration;
 pub mod property;
 pub mod title;
 
@@ -51,6 +56,10 @@ pub enum EDataType {
     Map {
         id: EMapId,
     },
+    /// Fixed-size list of heterogeneous items, stored as a JSON array
+    Tuple {
+        id: ETupleId,
+    },
     /// Unknown type. Cannot be named by the kdl type system, but can be used
     /// in graphs
     Unknown,
@@ -74,6 +83,19 @@ impl EDataType {
                 id: *id,
                 values: Default::default(),
             },
+            EDataType::Tuple { id } => EValue::Tuple {
+                id: *id,
+                values: reg
+                    .get_tuple(id)
+                    .map(|tuple| {
+                        tuple
+                            .item_types
+                            .iter()
+                            .map(|ty| ty.default_value(reg).into_owned())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            },
             EDataType::Unknown => EValue::Null,
         }
         .into()
@@ -95,6 +117,7 @@ impl EDataType {
             EDataType::Const { value } => value.to_string().into(),
             EDataType::List { id: ty } => ty.to_string().into(),
             EDataType::Map { id: ty } => ty.to_string().into(),
+            EDataType::Tuple { id: ty } => ty.to_string().into(),
             EDataType::Unknown => "unknown".into(),
         }
     }
@@ -124,6 +147,18 @@ impl EDataType {
                     )
                 },
             ),
+            EDataType::Tuple { id } => registry.get_tuple(id).map_or_else(
+                || format!("Unknown tuple `{}`", id),
+                |data| {
+                    format!(
+                        "Tuple<{}>",
+                        data.item_types
+                            .iter()
+                            .map(|ty| ty.title(registry))
+                            .join(", ")
+                    )
+                },
+            ),
         }
     }
 
@@ -152,6 +187,7 @@ impl EDataType {
             | EDataType::Number
             | EDataType::String
             | EDataType::Const { .. }
+            | EDataType::Tuple { .. }
             | EDataType::Unknown => Names::Ref(&[]),
             EDataType::Object { ident } => {
                 let obj = registry.get_object(ident).expect("object should exist");
@@ -196,6 +232,7 @@ impl EDataType {
             | EDataType::Number
             | EDataType::String
             | EDataType::Const { .. }
+            | EDataType::Tuple { .. }
             | EDataType::Unknown => Names::Nil,
             EDataType::Object { ident } => {
                 let obj = registry.get_object(ident).expect("object should exist");
@@ -319,6 +356,42 @@ impl EDataType {
                     values: entries,
                 })
             }
+            EDataType::Tuple { id } => {
+                let tuple = registry.get_tuple(id).ok_or_else(|| {
+                    miette!(
+                        "!!INTERNAL ERROR!! tuple id was not present in registry: `{}`",
+                        id
+                    )
+                })?;
+
+                let JsonValue::Array(items) = data else {
+                    bail!(
+                        "invalid data type. Expected tuple but got {}",
+                        json_kind(data)
+                    )
+                };
+
+                if items.len() != tuple.item_types.len() {
+                    bail!(
+                        "invalid tuple length. Expected {} items but got {}",
+                        tuple.item_types.len(),
+                        items.len()
+                    )
+                }
+
+                let mut tuple_items = vec![];
+                for (i, (ty, x)) in tuple.item_types.iter().zip(items.iter_mut()).enumerate() {
+                    tuple_items.push(
+                        ty.parse_json(registry, x, false)
+                            .with_context(|| format!("at index {}", i))?,
+                    )
+                }
+
+                Ok(EValue::Tuple {
+                    id: *id,
+                    values: tuple_items,
+                })
+            }
             EDataType::Unknown => {
                 bail!("cannot parse unknown type")
             }
@@ -0,0 +1,303 @@
+//! Computed read-only fields
+//!
+//! A struct field with the `computed` property holds an arithmetic expression
+//! over the sibling number fields, e.g. `computed="Damage * FireRate"`. A field
+//! with the `computed_graph` property is produced by a node group, which
+//! receives the whole struct as its only input and must emit the field value
+//! as its only output.
+//!
+//! Computed fields are evaluated in the declaration order, so a computed field
+//! may depend on the computed fields declared before it. Values are
+//! recalculated on every edit and before the project is saved, and validation
+//! reports stored values that disagree with the recomputed ones.
+//!
+//! Parsed expressions are cached in the registry extra data by their text,
+//! so changed expressions are parsed again after the types are reloaded.
+
+use crate::etype::eitem::EItemInfo;
+use crate::etype::estruct::EStructField;
+use crate::etype::property::default_properties::{PROP_FIELD_COMPUTED, PROP_FIELD_COMPUTED_GRAPH};
+use crate::etype::EDataType;
+use crate::graph::execution::GraphExecutionContext;
+use crate::project::project_graph::ProjectGraphs;
+use crate::project::side_effects::SideEffectsContext;
+use crate::registry::ETypesRegistry;
+use crate::value::EValue;
+use camino::Utf8Path;
+use diagnostic::context::DiagnosticContextMut;
+use exmex::{Express, FlatEx};
+use miette::{bail, miette, IntoDiagnostic};
+use parking_lot::RwLock;
+use std::sync::Arc;
+use ustr::Ustr;
+use utils::map::HashMap;
+
+/// Parsed expressions of the computed fields, by the expression text
+///
+/// Keyed by the text rather than the field, since the registry extra data
+/// is carried over when the types are reloaded
+#[derive(Default)]
+struct ExpressionCache(RwLock<HashMap<Ustr, Arc<Result<FlatEx<f64>, String>>>>);
+
+impl ExpressionCache {
+    fn get(registry: &ETypesRegistry, expr: Ustr) -> Arc<Result<FlatEx<f64>, String>> {
+        let cache = registry.extra_data::<Self>();
+        if let Some(parsed) = cache.0.read().get(&expr) {
+            return parsed.clone();
+        }
+
+        let parsed = Arc::new(FlatEx::<f64>::parse(expr.as_str()).map_err(|err| err.to_string()));
+        cache.0.write().insert(expr, parsed.clone());
+        parsed
+    }
+}
+
+/// Checks whether the field value is computed rather than edited by the user
+pub fn is_computed(field: &EItemInfo) -> bool {
+    let props = field.extra_properties();
+    PROP_FIELD_COMPUTED.try_get(props).is_some()
+        || PROP_FIELD_COMPUTED_GRAPH.try_get(props).is_some()
+}
+
+/// Recalculates all computed fields inside the value, innermost values first
+///
+/// Fields that fail to compute keep their stored value, the error is
+/// reported by [check_computed]
+///
+/// Returns `true` if any field was changed
+pub fn update_computed(
+    registry: &ETypesRegistry,
+    graphs: Option<&ProjectGraphs>,
+    value: &mut EValue,
+) -> bool {
+    let mut changed = false;
+    match value {
+        EValue::Null | EValue::Boolean { .. } | EValue::Number { .. } | EValue::String { .. } => {}
+        EValue::Struct { fields, .. } => {
+            for field in fields.values_mut() {
+                changed |= update_computed(registry, graphs, field);
+            }
+            changed |= update_struct(registry, graphs, value, |_, _| {});
+        }
+        EValue::Enum { data, .. } => changed |= update_computed(registry, graphs, data),
+        EValue::List { values, .. } | EValue::Tuple { values, .. } => {
+            for item in values {
+                changed |= update_computed(registry, graphs, item);
+            }
+        }
+        EValue::Map { values, .. } => {
+            for item in values.values_mut() {
+                changed |= update_computed(registry, graphs, item);
+            }
+        }
+    }
+
+    changed
+}
+
+/// Reports computed fields that fail to compute, or whose stored value
+/// disagrees with the recomputed one
+pub fn check_computed(
+    registry: &ETypesRegistry,
+    graphs: Option<&ProjectGraphs>,
+    mut ctx: DiagnosticContextMut,
+    value: &EValue,
+) {
+    match value {
+        EValue::Null | EValue::Boolean { .. } | EValue::Number { .. } | EValue::String { .. } => {}
+        EValue::Struct { fields, .. } => {
+            for (name, field) in fields {
+                check_computed(registry, graphs, ctx.enter_field(name.as_str()), field);
+            }
+
+            let mut recomputed = value.clone();
+            update_struct(registry, graphs, &mut recomputed, |field, result| {
+                let mut ctx = ctx.enter_field(field.name.as_str());
+                match result {
+                    Err(err) => {
+                        ctx.emit_error(miette!(
+                            code = "computed/failed",
+                            "failed to compute field value: {}",
+                            err
+                        ));
+                    }
+                    Ok(computed) => {
+                        let stored = fields.get(&field.name);
+                        if stored != Some(computed) {
+                            ctx.emit_warning(miette!(
+                                code = "computed/outdated",
+                                "stored value {} differs from the computed value {}",
+                                stored.map_or_else(|| "<missing>".to_string(), |v| v.to_string()),
+                                computed
+                            ));
+                        }
+                    }
+                }
+            });
+        }
+        EValue::Enum { variant, data } => {
+            check_computed(
+                registry,
+                graphs,
+                ctx.enter_variant(variant.variant_name().as_str()),
+                data,
+            );
+        }
+        EValue::List { values, .. } | EValue::Tuple { values, .. } => {
+            for (idx, item) in values.iter().enumerate() {
+                check_computed(registry, graphs, ctx.enter_index(idx), item);
+            }
+        }
+        EValue::Map { values, .. } => {
+            for (idx, item) in values.values().enumerate() {
+                check_computed(registry, graphs, ctx.enter_index(idx), item);
+            }
+        }
+    }
+}
+
+/// Recalculates computed fields of the struct in the declaration order,
+/// calling `report` with the result of every computed field
+fn update_struct(
+    registry: &ETypesRegistry,
+    graphs: Option<&ProjectGraphs>,
+    value: &mut EValue,
+    mut report: impl FnMut(&EStructField, &miette::Result<EValue>),
+) -> bool {
+    let EValue::Struct { ident, .. } = value else {
+        return false;
+    };
+    let Some(data) = registry.get_struct(ident) else {
+        return false;
+    };
+
+    let mut changed = false;
+    for field in data.fields.iter().filter(|f| is_computed(&f.ty)) {
+        let result = compute_field(registry, graphs, field, value);
+        report(field, &result);
+
+        let Ok(computed) = result else {
+            continue;
+        };
+
+        let EValue::Struct { fields, .. } = value else {
+            unreachable!("value should be a struct");
+        };
+        if fields.get(&field.name) != Some(&computed) {
+            fields.insert(field.name, computed);
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+fn compute_field(
+    registry: &ETypesRegistry,
+    graphs: Option<&ProjectGraphs>,
+    field: &EStructField,
+    value: &EValue,
+) -> miette::Result<EValue> {
+    let props = field.ty.extra_properties();
+    if let Some(expr) = PROP_FIELD_COMPUTED.try_get(props) {
+        let expr = ExpressionCache::get(registry, Ustr::from(expr.as_str()));
+        compute_expression(field, &expr, value)
+    } else if let Some(path) = PROP_FIELD_COMPUTED_GRAPH.try_get(props) {
+        compute_graph(registry, graphs, field, Utf8Path::new(path.as_str()), value)
+    } else {
+        bail!("field `{}` is not computed", field.name)
+    }
+}
+
+fn compute_expression(
+    field: &EStructField,
+    expr: &Result<FlatEx<f64>, String>,
+    value: &EValue,
+) -> miette::Result<EValue> {
+    if field.ty.ty() != EDataType::Number {
+        bail!(
+            "computed expressions can only produce numbers, but field type is `{}`",
+            field.ty.ty().name()
+        );
+    }
+
+    let EValue::Struct { fields, .. } = value else {
+        bail!("expected a struct, got {}", value);
+    };
+
+    let expr = match expr {
+        Ok(expr) => expr,
+        Err(err) => bail!("{}", err),
+    };
+    let args = expr
+        .var_names()
+        .iter()
+        .map(|name| match fields.get(&Ustr::from(name.as_str())) {
+            None => bail!("unknown field `{}` in expression", name),
+            Some(EValue::Number { value }) => Ok(value.0),
+            Some(other) => bail!("field `{}` is not a number, got {}", name, other),
+        })
+        .collect::<miette::Result<Vec<_>>>()?;
+
+    let result = expr.eval(&args).into_diagnostic()?;
+    if !result.is_finite() {
+        bail!("expression evaluated to {}", result);
+    }
+    Ok(result.into())
+}
+
+fn compute_graph(
+    registry: &ETypesRegistry,
+    graphs: Option<&ProjectGraphs>,
+    field: &EStructField,
+    path: &Utf8Path,
+    value: &EValue,
+) -> miette::Result<EValue> {
+    let Some(graphs) = graphs else {
+        bail!("node groups are not available in this context");
+    };
+
+    let graph = graphs
+        .graph_by_path(path)
+        .ok_or_else(|| miette!("graph `{}` is not found", path))?;
+
+    if !graph.is_node_group {
+        bail!("graph `{}` is not a node group", path);
+    }
+
+    if graph.inputs().len() != 1 || graph.outputs().len() != 1 {
+        bail!(
+            "computed field node group must have exactly one input and one output, but got {} inputs and {} outputs",
+            graph.inputs().len(),
+            graph.outputs().len()
+        );
+    }
+
+    let input = [value.clone()];
+    let mut outputs = None;
+    let mut ctx = GraphExecutionContext::from_graph(
+        graph.graph(),
+        registry,
+        Some(graphs),
+        SideEffectsContext::unavailable(),
+        true,
+        &input,
+        &mut outputs,
+    );
+    ctx.full_eval(true)?;
+    drop(ctx);
+
+    let output = outputs
+        .and_then(|o| o.into_iter().next())
+        .ok_or_else(|| miette!("computed field node group did not emit any outputs"))?;
+
+    if output.ty() != field.ty.ty() {
+        bail!(
+            "computed field node group returned a value of type `{}`, but `{}` was expected",
+            output.ty().name(),
+            field.ty.ty().name()
+        );
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,341 @@
+//! Conditions of the `required_if` and `visible_if` field properties
+//!
+//! A condition compares sibling fields of the struct with constants, for
+//! example `kind == Beam` or `kind != Beam && enabled == true`. Enum fields are
+//! compared by the variant name, other fields by their value. Comparisons can
+//! be combined with `&&` and `||`, with `&&` taking precedence. Values may be
+//! quoted to contain operators.
+//!
+//! Conditions are parsed once when the struct type is built, and checked
+//! against the sibling fields and their enum variants once all types are
+//! built.
+
+use crate::etype::eitem::EItemInfo;
+use crate::etype::estruct::EStructField;
+use crate::etype::EDataType;
+use crate::registry::ETypesRegistry;
+use crate::value::EValue;
+use miette::{bail, miette, Context};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use ustr::Ustr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldCondition {
+    source: String,
+    /// Groups of comparisons joined by `||`, comparisons inside a group are
+    /// joined by `&&`
+    any: Vec<Vec<Comparison>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Comparison {
+    field: Ustr,
+    negated: bool,
+    value: String,
+}
+
+impl Display for FieldCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FieldCondition {
+    pub fn parse(condition: &str) -> miette::Result<Self> {
+        let any = split_outside_quotes(condition, "||")
+            .into_iter()
+            .map(|group| {
+                split_outside_quotes(group, "&&")
+                    .into_iter()
+                    .map(Comparison::parse)
+                    .collect()
+            })
+            .collect::<miette::Result<_>>()?;
+
+        Ok(Self {
+            source: condition.trim().to_string(),
+            any,
+        })
+    }
+
+    /// Checks that the condition only compares the given sibling fields
+    pub(crate) fn check_fields(&self, fields: &[Ustr]) -> miette::Result<()> {
+        for cmp in self.any.iter().flatten() {
+            if !fields.contains(&cmp.field) {
+                bail!("unknown field `{}` in condition", cmp.field);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the compared values match the types of the sibling fields
+    ///
+    /// Fields of generic types are only checked when the condition is
+    /// evaluated
+    pub(crate) fn check_values(
+        &self,
+        registry: &ETypesRegistry,
+        fields: &[EStructField],
+    ) -> miette::Result<()> {
+        for cmp in self.any.iter().flatten() {
+            let Some(field) = fields.iter().find(|f| f.name == cmp.field) else {
+                bail!("unknown field `{}` in condition", cmp.field);
+            };
+            let EItemInfo::Specific(_) = &field.ty else {
+                continue;
+            };
+
+            match field.ty.ty() {
+                EDataType::Boolean => {
+                    parse_value::<bool>(&cmp.value, cmp.field)?;
+                }
+                EDataType::Number => {
+                    parse_value::<f64>(&cmp.value, cmp.field)?;
+                }
+                EDataType::String | EDataType::Const { .. } => {}
+                EDataType::Object { ident } => {
+                    let data = registry
+                        .get_object(&ident)
+                        .ok_or_else(|| miette!("type `{}` is not found", ident))?;
+                    let Some(data) = data.as_enum() else {
+                        bail!(
+                            "field `{}` of type `{}` can't be used in a condition",
+                            cmp.field,
+                            ident
+                        );
+                    };
+                    if !data.variants().iter().any(|v| v.name == cmp.value) {
+                        bail!(
+                            "enum `{}` of field `{}` has no variant `{}`",
+                            ident,
+                            cmp.field,
+                            cmp.value
+                        );
+                    }
+                }
+                other => bail!(
+                    "field `{}` of type `{}` can't be used in a condition",
+                    cmp.field,
+                    other.name()
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates the condition against the fields of the struct
+    pub fn eval(&self, fields: &BTreeMap<Ustr, EValue>) -> miette::Result<bool> {
+        for group in &self.any {
+            let mut all = true;
+            for cmp in group {
+                if !cmp.eval(fields)? {
+                    all = false;
+                    break;
+                }
+            }
+            if all {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+impl Comparison {
+    fn parse(comparison: &str) -> miette::Result<Self> {
+        let split = |op: &str| {
+            let pos = find_outside_quotes(comparison, op)?;
+            Some((&comparison[..pos], &comparison[pos + op.len()..]))
+        };
+        let (field, value, negated) = if let Some((field, value)) = split("!=") {
+            (field, value, true)
+        } else if let Some((field, value)) = split("==") {
+            (field, value, false)
+        } else {
+            bail!(
+                "expected a comparison in form of `field == value` or `field != value`, got `{}`",
+                comparison.trim()
+            );
+        };
+
+        let field = field.trim();
+        let value = value.trim();
+        if field.is_empty() || value.is_empty() {
+            bail!("incomplete comparison `{}`", comparison.trim());
+        }
+
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+
+        Ok(Self {
+            field: field.into(),
+            negated,
+            value: value.to_string(),
+        })
+    }
+
+    fn eval(&self, fields: &BTreeMap<Ustr, EValue>) -> miette::Result<bool> {
+        let field = fields
+            .get(&self.field)
+            .ok_or_else(|| miette!("unknown field `{}` in condition", self.field))?;
+
+        let equals = match field {
+            EValue::Enum { variant, .. } => variant.variant_name().as_str() == self.value,
+            EValue::Boolean { value } => *value == parse_value::<bool>(&self.value, self.field)?,
+            EValue::Number { value } => value.0 == parse_value::<f64>(&self.value, self.field)?,
+            EValue::String { value } => value == &self.value,
+            EValue::Null => self.value == "null",
+            other => bail!(
+                "field `{}` of type `{}` can't be used in a condition",
+                self.field,
+                other.ty().name()
+            ),
+        };
+
+        Ok(equals != self.negated)
+    }
+}
+
+/// Returns the position of the first occurrence of the pattern that is not
+/// inside of a quoted value
+fn find_outside_quotes(text: &str, pat: &str) -> Option<usize> {
+    let mut quoted = false;
+    for (pos, c) in text.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if !quoted && text[pos..].starts_with(pat) {
+            return Some(pos);
+        }
+    }
+    None
+}
+
+/// Splits the text on the separator, ignoring separators inside of quoted
+/// values
+fn split_outside_quotes<'a>(mut text: &'a str, sep: &str) -> Vec<&'a str> {
+    let mut parts = vec![];
+    while let Some(pos) = find_outside_quotes(text, sep) {
+        parts.push(&text[..pos]);
+        text = &text[pos + sep.len()..];
+    }
+    parts.push(text);
+    parts
+}
+
+fn parse_value<T: std::str::FromStr>(value: &str, field: Ustr) -> miette::Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse::<T>()
+        .map_err(|err| miette!("{}", err))
+        .with_context(|| format!("bad value `{}` for field `{}`", value, field))
+}
+
+/// Checks whenever the field should be shown to the user
+///
+/// Fields without the `visible_if` property are always visible
+pub fn is_field_visible(
+    field: &EStructField,
+    fields: &BTreeMap<Ustr, EValue>,
+) -> miette::Result<bool> {
+    match &field.visible_if {
+        None => Ok(true),
+        Some(condition) => condition.eval(fields),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldCondition;
+    use crate::project::io::embedded_registry;
+    use crate::registry::ETypesRegistry;
+    use crate::value::id::ETypeId;
+    use crate::value::EValue;
+    use rstest::rstest;
+    use std::collections::BTreeMap;
+    use ustr::Ustr;
+
+    fn fields() -> BTreeMap<Ustr, EValue> {
+        let mut fields = BTreeMap::new();
+        fields.insert("enabled".into(), true.into());
+        fields.insert("speed".into(), 2.5f64.into());
+        fields.insert("name".into(), "laser".to_string().into());
+        fields
+    }
+
+    #[rstest]
+    #[case("enabled == true", true)]
+    #[case("enabled != true", false)]
+    #[case("speed == 2.5", true)]
+    #[case("name == \"laser\"", true)]
+    #[case("name == laser && speed == 1", false)]
+    #[case("name == rocket || speed == 2.5", true)]
+    #[case("name == laser && speed == 1 || enabled == true", true)]
+    #[case("name == \"a||b\" || speed == 2.5", true)]
+    #[case("name != \"x==y\" && enabled == true", true)]
+    fn should_eval_condition(#[case] condition: &str, #[case] expected: bool) {
+        let condition = FieldCondition::parse(condition).unwrap();
+        assert_eq!(condition.eval(&fields()).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("enabled")]
+    #[case("== true")]
+    #[case("enabled == ")]
+    fn should_fail_parse(#[case] condition: &str) {
+        assert!(FieldCondition::parse(condition).is_err())
+    }
+
+    #[rstest]
+    #[case("missing == true")]
+    #[case("enabled == maybe")]
+    #[case("speed == fast")]
+    fn should_fail_eval(#[case] condition: &str) {
+        let condition = FieldCondition::parse(condition).unwrap();
+        assert!(condition.eval(&fields()).is_err())
+    }
+
+    fn registry(condition: &str) -> miette::Result<ETypesRegistry> {
+        let kind = r#"
+            enum {
+                const "first" 0
+                const "second" 1
+            }
+        "#;
+        let ship = format!(
+            r#"
+            struct {{
+                boolean "Enabled"
+                number "Speed" visible_if="{}"
+                object "Kind" "test:kind"
+            }}
+        "#,
+            condition
+        );
+        embedded_registry([
+            (ETypeId::from_raw("test:kind".into()), kind.to_string()),
+            (ETypeId::from_raw("test:ship".into()), ship),
+        ])
+    }
+
+    #[rstest]
+    #[case("Enabled == true && Kind == second")]
+    #[case("Speed != 0 || Kind != first")]
+    fn should_check_condition_on_build(#[case] condition: &str) {
+        registry(condition).unwrap();
+    }
+
+    #[rstest]
+    #[case("Missing == true")]
+    #[case("Enabled == maybe")]
+    #[case("Speed == fast")]
+    #[case("Kind == third")]
+    fn should_fail_condition_on_build(#[case] condition: &str) {
+        assert!(registry(condition).is_err())
+    }
+}
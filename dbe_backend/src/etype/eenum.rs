@@ -3,15 +3,19 @@ use crate::etype::eenum::pattern::{EnumPattern, Tagged};
 use crate::etype::eenum::variant::{EEnumVariant, EEnumVariantId, EEnumVariantWithId};
 use crate::etype::eitem::EItemInfo;
 use crate::etype::eobject::EObject;
+use crate::etype::generic::GenericConstraint;
+use crate::etype::migration;
+use crate::etype::property::default_properties::PROP_FIELD_PRIORITY;
 use crate::etype::property::ObjectPropertyId;
 use crate::etype::title::ObjectTitle;
 use crate::json_utils::repr::{JsonRepr, Repr};
 use crate::json_utils::{json_kind, JsonMap, JsonValue};
 use crate::registry::{EObjectType, ETypesRegistry};
 use crate::value::id::ETypeId;
-use crate::value::EValue;
+use crate::value::{ENumber, EValue};
 use itertools::Itertools;
 use miette::{bail, miette, Context};
+use std::cmp::Reverse;
 use ustr::{Ustr, UstrMap};
 use utils::map::HashMap;
 use utils::whatever_ref::WhateverRef;
@@ -23,6 +27,7 @@ pub mod variant;
 pub struct EEnumData {
     pub generic_arguments: Vec<Ustr>,
     pub generic_arguments_values: Vec<EItemInfo>,
+    pub generic_constraints: Vec<(Ustr, GenericConstraint)>,
     pub generic_parent_id: Option<ETypeId>,
     pub ident: ETypeId,
     pub repr: Option<Repr>,
@@ -31,6 +36,8 @@ pub struct EEnumData {
     tagged_repr: Option<Tagged>,
     variants: Vec<EEnumVariant>,
     variant_ids: Vec<EEnumVariantId>,
+    /// Indices of variants in the order they are matched against JSON
+    match_order: Vec<usize>,
 }
 
 impl EEnumData {
@@ -44,6 +51,7 @@ impl EEnumData {
         Self {
             generic_arguments,
             generic_arguments_values: vec![],
+            generic_constraints: vec![],
             generic_parent_id: None,
             ident,
             repr,
@@ -52,6 +60,7 @@ impl EEnumData {
             tagged_repr,
             variants: Default::default(),
             variant_ids: Default::default(),
+            match_order: Default::default(),
         }
     }
 
@@ -114,6 +123,7 @@ impl EEnumData {
             variant: variant.name,
         });
         self.variants.push(variant);
+        self.recalculate_match_order();
     }
 
     fn recalculate_variants(&mut self) {
@@ -124,6 +134,37 @@ impl EEnumData {
                 variant: variant.name,
             }
         }
+        self.recalculate_match_order();
+    }
+
+    /// Sorts variants by their matching priority, keeping the declaration
+    /// order for variants of equal priority
+    fn recalculate_match_order(&mut self) {
+        self.match_order = (0..self.variants.len()).collect();
+        self.match_order
+            .sort_by_key(|i| Reverse(variant_priority(&self.variants[*i])));
+    }
+
+    /// Checks that no JSON value can be matched by two variants of the same
+    /// priority, since the declaration order would silently decide between
+    /// them
+    pub(crate) fn check_overlaps(&self) -> miette::Result<()> {
+        for (i, a) in self.variants.iter().enumerate() {
+            for b in &self.variants[i + 1..] {
+                if a.overlaps(b) && variant_priority(a) == variant_priority(b) {
+                    bail!(
+                        help = "set different `priority` properties on the variants to choose which one is matched first",
+                        "enum variants `{}` ({}) and `{}` ({}) have overlapping patterns",
+                        a.name,
+                        a.pat,
+                        b.name,
+                        b.pat,
+                    );
+                }
+            }
+        }
+
+        Ok(())
     }
 
     pub fn variants(&self) -> &[EEnumVariant] {
@@ -138,12 +179,40 @@ impl EEnumData {
         self.variants.iter().zip(self.variant_ids.iter())
     }
 
+    /// Returns the variant that the JSON data would be parsed as
+    pub(crate) fn matching_variant(&self, data: &JsonValue) -> Option<&EEnumVariant> {
+        self.match_order
+            .iter()
+            .map(|idx| &self.variants[*idx])
+            .find(|variant| variant.matches_json(data))
+    }
+
+    /// Whether the enum can store its data version next to the tag
+    pub(crate) fn is_versionable(&self) -> bool {
+        matches!(
+            self.tagged_repr,
+            Some(Tagged::External | Tagged::Adjacent { .. })
+        )
+    }
+
     pub(crate) fn parse_json(
         &self,
         registry: &ETypesRegistry,
         data: &mut JsonValue,
         inline: bool,
     ) -> miette::Result<EValue> {
+        // Inline and repr-backed enums don't carry their own version field
+        if !inline && self.repr.is_none() {
+            if let Some(version) = migration::type_version(&self.extra_properties)? {
+                let kind = json_kind(data);
+                let JsonValue::Object(data) = data else {
+                    bail!("versioned enum must be a JSON object, but got `{}`", kind);
+                };
+                // Enums don't support node group migrations, see [migration::check_migrations]
+                migration::migrate(registry, self.ident, version, data)?;
+            }
+        }
+
         if let Some(repr) = self.tagged_repr {
             let JsonValue::Object(data) = &data else {
                 bail!(
@@ -190,8 +259,9 @@ impl EEnumData {
                 }
             }
         }
-        for (variant, id) in self.variants_with_ids() {
-            if variant.pat.matches_json(data) {
+        for idx in &self.match_order {
+            let (variant, id) = (&self.variants[*idx], &self.variant_ids[*idx]);
+            if variant.matches_json(data) {
                 let mut data_holder: Option<JsonValue> = None;
                 let data = if let EnumPattern::Tagged { repr, tag } = &variant.pat {
                     let JsonValue::Object(fields) = data else {
@@ -250,7 +320,7 @@ impl EEnumData {
                 )
             })?;
         let mut json_content = value.write_json(registry).context("in enum value")?;
-        let json_value = if let Some(repr) = self.tagged_repr {
+        let mut json_value = if let Some(repr) = self.tagged_repr {
             let tag = variant.get_tag_value();
             match repr {
                 Tagged::External => {
@@ -287,7 +357,20 @@ impl EEnumData {
         };
 
         if let Some(repr) = &self.repr {
-            return repr.into_repr(registry, json_value);
+            return repr.into_repr_of(registry, self.ident, json_value);
+        }
+
+        if let Some(version) = migration::type_version(&self.extra_properties)? {
+            if version > 0 {
+                let kind = json_kind(&json_value);
+                let JsonValue::Object(obj) = &mut json_value else {
+                    bail!(
+                        "versioned enum must serialize into an object, but got `{}`",
+                        kind
+                    );
+                };
+                obj.insert(migration::VERSION_FIELD.to_string(), version.into());
+            }
         }
 
         Ok(json_value)
@@ -323,3 +406,67 @@ impl EObject for EEnumData {
         self.title.get(self, registry)
     }
 }
+
+fn variant_priority(variant: &EEnumVariant) -> ENumber {
+    PROP_FIELD_PRIORITY.get(variant.data.extra_properties(), 0.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::etype::EDataType;
+    use crate::project::io::embedded_registry;
+    use crate::registry::ETypesRegistry;
+    use crate::value::id::ETypeId;
+    use crate::value::EValue;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn id(name: &str) -> ETypeId {
+        ETypeId::from_raw(format!("test:{}", name).into())
+    }
+
+    fn registry(first: &str, second: &str) -> miette::Result<ETypesRegistry> {
+        let item = r#"
+            enum {
+                object "first" "test:first"
+                object "second" "test:second"
+            }
+        "#;
+        embedded_registry([
+            (id("first"), first.to_string()),
+            (id("second"), second.to_string()),
+            (id("item"), item.to_string()),
+        ])
+    }
+
+    #[rstest]
+    #[case(r#"struct { string "Name" }"#, r#"struct { number "Level" }"#)]
+    #[case(
+        r#"struct { const "Type" 1 }"#,
+        r#"struct { const "Type" 1
+                    string "Name" }"#
+    )]
+    fn should_fail_overlapping_objects(#[case] first: &str, #[case] second: &str) {
+        assert!(registry(first, second).is_err())
+    }
+
+    #[test]
+    fn should_match_object_by_consts() {
+        let registry = registry(
+            r#"struct { const "Type" 1
+                        string "Name" }"#,
+            r#"struct { const "Type" 2
+                        string "Name" }"#,
+        )
+        .unwrap();
+
+        let mut json = json!({"Type": 2, "Name": "item"});
+        let value = EDataType::Object { ident: id("item") }
+            .parse_json(&registry, &mut json, false)
+            .unwrap();
+        let EValue::Enum { variant, .. } = value else {
+            panic!("expected an enum value, got {:?}", value);
+        };
+        assert_eq!(variant.variant_name().as_str(), "second");
+    }
+}
@@ -71,10 +71,7 @@ impl EnumPattern {
                     let tag_field = m.get(tag_field.as_str());
                     tag_field.is_some_and(|val| tag.matches_json(val).by_value)
                         // When tag field is missing, match the first variant that has an empty tag
-                        || (tag_field.is_none()
-                            && (tag == &ETypeConst::Null
-                                || tag == &ETypeConst::Number(0.into())
-                                || tag == &ETypeConst::String("".into())))
+                        || (tag_field.is_none() && is_empty_tag(tag))
                 }
             }),
             EnumPattern::UntaggedObject => value.is_object(),
@@ -88,4 +85,56 @@ impl EnumPattern {
             EnumPattern::Never => false,
         }
     }
+
+    /// Checks whether some JSON value can be matched by both patterns
+    pub fn overlaps(&self, other: &EnumPattern) -> bool {
+        use EnumPattern as P;
+        match (self, other) {
+            (P::Never, _) | (_, P::Never) => false,
+            (
+                P::Tagged {
+                    repr: a,
+                    tag: tag_a,
+                },
+                P::Tagged {
+                    repr: b,
+                    tag: tag_b,
+                },
+            ) => {
+                if a != b {
+                    return false;
+                }
+                match a {
+                    Tagged::External => tag_a.as_json_key() == tag_b.as_json_key(),
+                    Tagged::Internal { .. } | Tagged::Adjacent { .. } => {
+                        tag_a == tag_b || (is_empty_tag(tag_a) && is_empty_tag(tag_b))
+                    }
+                }
+            }
+            (P::Tagged { .. }, other) | (other, P::Tagged { .. }) => {
+                matches!(other, P::UntaggedObject | P::Map)
+            }
+            (P::UntaggedObject | P::Map, P::UntaggedObject | P::Map) => true,
+            (P::Boolean, P::Boolean) | (P::Number, P::Number) | (P::String, P::String) => true,
+            (P::List, P::List) => true,
+            (P::Ref(_), P::Ref(_) | P::Number | P::String) | (P::Number | P::String, P::Ref(_)) => {
+                true
+            }
+            (P::Const(a), P::Const(b)) => a == b,
+            (P::Const(c), other) | (other, P::Const(c)) => matches!(
+                (c, other),
+                (ETypeConst::Boolean(_), P::Boolean)
+                    | (ETypeConst::Number(_), P::Number | P::Ref(_))
+                    | (ETypeConst::String(_), P::String | P::Ref(_))
+            ),
+            _ => false,
+        }
+    }
+}
+
+/// Tags that match objects with a missing tag field
+fn is_empty_tag(tag: &ETypeConst) -> bool {
+    tag == &ETypeConst::Null
+        || tag == &ETypeConst::Number(0.into())
+        || tag == &ETypeConst::String("".into())
 }
@@ -7,6 +7,7 @@ use crate::etype::eobject::EObject;
 use crate::etype::property::default_properties::PROP_FIELD_TAG;
 use crate::etype::EDataType;
 use crate::json_utils::repr::JsonRepr;
+use crate::json_utils::JsonValue;
 use crate::registry::{EObjectType, ETypesRegistry};
 use crate::value::id::ETypeId;
 use crate::value::EValue;
@@ -21,6 +22,9 @@ pub struct EEnumVariant {
     pub pat: EnumPattern,
     pub data: EItemInfo,
     pub name: Ustr,
+    /// Constant fields of the untagged object variant, which tell apart
+    /// untagged variants of different struct types
+    pub consts: Vec<(Ustr, ETypeConst)>,
 }
 
 impl EEnumVariant {
@@ -33,7 +37,43 @@ impl EEnumVariant {
     }
 
     pub(crate) fn new(name: Ustr, pat: EnumPattern, data: EItemInfo) -> Self {
-        Self { pat, data, name }
+        Self {
+            pat,
+            data,
+            name,
+            consts: vec![],
+        }
+    }
+
+    /// Checks whether the JSON value can belong to this variant
+    ///
+    /// Untagged object variants reject objects with a different value in
+    /// any of their constant fields
+    pub fn matches_json(&self, value: &JsonValue) -> bool {
+        if !self.pat.matches_json(value) {
+            return false;
+        }
+        let Some(obj) = value.as_object() else {
+            return true;
+        };
+        self.consts.iter().all(|(name, c)| {
+            obj.get(name.as_str())
+                .is_none_or(|value| c.matches_json(value).by_value)
+        })
+    }
+
+    /// Checks whether some JSON value can be matched by both variants
+    pub fn overlaps(&self, other: &EEnumVariant) -> bool {
+        if !self.pat.overlaps(&other.pat) {
+            return false;
+        }
+        // Objects can't hold two different values in the same constant field
+        !self.consts.iter().any(|(name, a)| {
+            other
+                .consts
+                .iter()
+                .any(|(other_name, b)| name == other_name && a != b)
+        })
     }
 
     pub(crate) fn get_tag_value(&self) -> ETypeConst {
@@ -50,6 +90,7 @@ impl EEnumVariant {
         if item.is_generic() {
             return Ok(EEnumVariant::new(name, EnumPattern::Never, item));
         }
+        let mut consts = vec![];
         let pat = if let Some(repr) = tagged_repr {
             let tag = PROP_FIELD_TAG.get(item.extra_properties(), ETypeConst::String(variant_name));
 
@@ -64,7 +105,7 @@ impl EEnumVariant {
                 EDataType::Number => EnumPattern::Number,
                 EDataType::String => EnumPattern::String,
                 EDataType::Const { value } => EnumPattern::Const(*value),
-                EDataType::List { .. } => EnumPattern::List,
+                EDataType::List { .. } | EDataType::Tuple { .. } => EnumPattern::List,
                 EDataType::Map { .. } => EnumPattern::Map,
                 EDataType::Object { ident } => {
                     let data = get_object(ident).context("This error might potentially be caused by the circular reference in types. Try specifying enum pattern manually")?;
@@ -73,6 +114,16 @@ impl EEnumVariant {
                     if let Some(pat) = data.repr().and_then(|repr| repr.enum_pat()) {
                         pat
                     } else {
+                        if let EObjectType::Struct(data) = &*data {
+                            consts = data
+                                .fields
+                                .iter()
+                                .filter_map(|field| match field.ty.ty() {
+                                    EDataType::Const { value } => Some((field.name, value)),
+                                    _ => None,
+                                })
+                                .collect();
+                        }
                         EnumPattern::UntaggedObject
                     }
                 }
@@ -80,7 +131,9 @@ impl EEnumVariant {
             }
         };
 
-        Ok(EEnumVariant::new(name, pat, item))
+        let mut variant = EEnumVariant::new(name, pat, item);
+        variant.consts = consts;
+        Ok(variant)
     }
 }
 
@@ -1,8 +1,12 @@
+use crate::etype::condition::FieldCondition;
 use crate::etype::econst::ETypeConst;
 use crate::etype::eitem::EItemInfo;
 use crate::etype::eobject::EObject;
+use crate::etype::generic::GenericConstraint;
+use crate::etype::migration;
 use crate::etype::property::default_properties::{
-    PROP_FIELD_DEFAULT, PROP_FIELD_INLINE, PROP_OBJECT_SAVE_DEFAULT_VALUES,
+    PROP_FIELD_DEFAULT, PROP_FIELD_INLINE, PROP_FIELD_REQUIRED_IF, PROP_FIELD_VISIBLE_IF,
+    PROP_OBJECT_SAVE_DEFAULT_VALUES,
 };
 use crate::etype::property::ObjectPropertyId;
 use crate::etype::title::ObjectTitle;
@@ -12,18 +16,66 @@ use crate::m_try;
 use crate::registry::ETypesRegistry;
 use crate::value::id::ETypeId;
 use crate::value::EValue;
+use camino::Utf8PathBuf;
 use itertools::Itertools;
 use miette::{bail, miette, Context};
+use parking_lot::RwLock;
+use std::cell::{Cell, RefCell};
 use std::collections::BTreeMap;
 use std::ops::Deref;
+use std::sync::Arc;
 use tracing::warn;
 use ustr::{Ustr, UstrMap};
 use utils::map::HashMap;
 
+thread_local! {
+    static FORCE_DEFAULT_VALUES: Cell<bool> = const { Cell::new(false) };
+    static UNKNOWN_FIELDS: RefCell<Option<Vec<UnknownFields>>> = const { RefCell::new(None) };
+}
+
+/// JSON fields of a struct that don't match any of its fields, and are
+/// dropped when the struct is parsed
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct UnknownFields {
+    pub ty: ETypeId,
+    pub fields: Vec<String>,
+}
+
+/// Unknown fields of the loaded files, by file path
+pub type UnknownFieldsData = RwLock<BTreeMap<Utf8PathBuf, Vec<UnknownFields>>>;
+
+impl UnknownFields {
+    /// Returns the unknown fields of the files loaded with the given registry
+    pub fn of(registry: &ETypesRegistry) -> Arc<UnknownFieldsData> {
+        registry.extra_data::<UnknownFieldsData>()
+    }
+}
+
+/// Runs the provided function, collecting the unknown fields of all structs
+/// parsed during it
+pub fn collect_unknown_fields<T>(func: impl FnOnce() -> T) -> (T, Vec<UnknownFields>) {
+    let previous = UNKNOWN_FIELDS.with_borrow_mut(|unknown| unknown.replace(vec![]));
+    let result = func();
+    let unknown = UNKNOWN_FIELDS
+        .with_borrow_mut(|unknown| std::mem::replace(unknown, previous))
+        .unwrap_or_default();
+    (result, unknown)
+}
+
+/// Runs the provided function, writing default values of all structs to JSON
+/// regardless of the `save_default_values` property
+pub fn with_default_values<T>(func: impl FnOnce() -> T) -> T {
+    let previous = FORCE_DEFAULT_VALUES.replace(true);
+    let result = func();
+    FORCE_DEFAULT_VALUES.set(previous);
+    result
+}
+
 #[derive(Debug, Clone)]
 pub struct EStructData {
     pub generic_arguments: Vec<Ustr>,
     pub generic_arguments_values: Vec<EItemInfo>,
+    pub generic_constraints: Vec<(Ustr, GenericConstraint)>,
     pub generic_parent_id: Option<ETypeId>,
     pub ident: ETypeId,
     pub fields: Vec<EStructField>,
@@ -37,6 +89,10 @@ pub struct EStructData {
 pub struct EStructField {
     pub name: Ustr,
     pub ty: EItemInfo,
+    /// Parsed `required_if` condition of the field
+    pub required_if: Option<FieldCondition>,
+    /// Parsed `visible_if` condition of the field
+    pub visible_if: Option<FieldCondition>,
 }
 
 impl EStructField {
@@ -55,6 +111,7 @@ impl EStructData {
         Self {
             generic_arguments,
             generic_arguments_values: vec![],
+            generic_constraints: vec![],
             fields: Default::default(),
             ident,
             // id_field: None,
@@ -143,6 +200,38 @@ impl EStructData {
         Ok(())
     }
 
+    /// Parses the `required_if` and `visible_if` conditions of the fields,
+    /// checking that they only compare the sibling fields
+    ///
+    /// Compared values are checked once all types are built, see
+    /// [FieldCondition::check_values]
+    pub(crate) fn parse_conditions(&mut self) -> miette::Result<()> {
+        let names = self.fields.iter().map(|f| f.name).collect_vec();
+        for field in &mut self.fields {
+            m_try(|| {
+                let props = field.ty.extra_properties();
+                let parse = |condition: Option<Ustr>, prop: &str| {
+                    condition
+                        .map(|condition| -> miette::Result<FieldCondition> {
+                            let condition = FieldCondition::parse(&condition)?;
+                            condition.check_fields(&names)?;
+                            Ok(condition)
+                        })
+                        .transpose()
+                        .with_context(|| format!("bad `{}` condition", prop))
+                };
+                let required_if = parse(PROP_FIELD_REQUIRED_IF.try_get(props), "required_if")?;
+                let visible_if = parse(PROP_FIELD_VISIBLE_IF.try_get(props), "visible_if")?;
+                field.required_if = required_if;
+                field.visible_if = visible_if;
+                Ok(())
+            })
+            .with_context(|| format!("failed to initialize field {}", field.name))?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn parse_json(
         &self,
         registry: &ETypesRegistry,
@@ -168,6 +257,15 @@ impl EStructData {
             Ok(data)
         }
 
+        // Inline and repr-backed structs don't carry their own version field
+        let mut pending_graphs = vec![];
+        if !inline && self.repr.is_none() {
+            if let Some(version) = migration::type_version(&self.extra_properties)? {
+                pending_graphs =
+                    migration::migrate(registry, self.ident, version, j_fields(json_data)?)?;
+            }
+        }
+
         let mut fields = BTreeMap::<Ustr, EValue>::default();
 
         for field in &self.fields {
@@ -204,14 +302,27 @@ impl EStructData {
                     "struct `{}` contains unknown fields: {}",
                     self.ident,
                     data.keys().map(|k| format!("`{k}`")).join(", ")
-                )
+                );
+                UNKNOWN_FIELDS.with_borrow_mut(|unknown| {
+                    if let Some(unknown) = unknown {
+                        unknown.push(UnknownFields {
+                            ty: self.ident,
+                            fields: data.keys().cloned().collect(),
+                        });
+                    }
+                });
             }
         }
 
-        Ok(EValue::Struct {
+        let value = EValue::Struct {
             ident: self.ident,
             fields,
-        })
+        };
+        if pending_graphs.is_empty() {
+            return Ok(value);
+        }
+
+        migration::run_graphs(registry, self.ident, &pending_graphs, value)
     }
 
     pub(crate) fn write_json(
@@ -227,6 +338,7 @@ impl EStructData {
 
         // always save default values if the object has a repr
         let save_default = self.repr.is_some()
+            || FORCE_DEFAULT_VALUES.get()
             || PROP_OBJECT_SAVE_DEFAULT_VALUES.get(&self.extra_properties, false);
 
         // TODO: throw an error if provided fields map contains unknown fields
@@ -249,6 +361,10 @@ impl EStructData {
                 if field.is_inline() {
                     if let JsonValue::Object(obj) = json_value {
                         for (k, v) in obj {
+                            // Inline structs are not versioned on their own
+                            if k == migration::VERSION_FIELD {
+                                continue;
+                            }
                             if json_fields.contains_key(&k) {
                                 bail!(conflicting_fields(&k))
                             } else {
@@ -274,13 +390,17 @@ impl EStructData {
             .with_context(|| format!("in field `{}`", field.name))?;
         }
 
-        let json = JsonValue::Object(json_fields);
-
         if let Some(repr) = &self.repr {
-            return repr.into_repr(registry, json);
+            return repr.into_repr_of(registry, self.ident, JsonValue::Object(json_fields));
+        }
+
+        if let Some(version) = migration::type_version(&self.extra_properties)? {
+            if version > 0 {
+                json_fields.insert(migration::VERSION_FIELD.to_string(), version.into());
+            }
         }
 
-        Ok(json)
+        Ok(JsonValue::Object(json_fields))
     }
 }
 
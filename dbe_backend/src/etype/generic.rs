@@ -0,0 +1,285 @@
+//! Constraints on generic arguments of KDL types
+//!
+//! Generic arguments accept any type by default. A generic type may restrict
+//! its arguments with `constraint` children:
+//!
+//! ```kdl
+//! struct "Id" repr="ids/numeric" {
+//!     constraint "Id" "const_string"
+//!     number "id" integer=true
+//! }
+//! ```
+//!
+//! Supported constraints are `const_string`, `struct`, `enum`, `one_of`,
+//! which is followed by the list of allowed types: `boolean`, `number`,
+//! `string`, or object IDs, and `list_of`, which is followed by the
+//! constraint of the list items, e.g. `constraint "Flags" "list_of" "enum"`.
+//! Constraints are checked when the generic type is instantiated, so
+//! violations are reported at the field that uses the type.
+
+use crate::etype::econst::ETypeConst;
+use crate::etype::eitem::EItemInfo;
+use crate::etype::eobject::EObject;
+use crate::etype::EDataType;
+use crate::registry::EObjectType;
+use crate::value::id::{EListId, ETypeId};
+use itertools::Itertools;
+use miette::{bail, miette};
+use std::fmt::{Display, Formatter};
+use ustr::{Ustr, UstrMap};
+use utils::whatever_ref::WhateverRef;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenericConstraint {
+    /// Argument must be a constant string, e.g. `const "Id" "Ship"`
+    ConstString,
+    /// Argument must be a struct type
+    Struct,
+    /// Argument must be an enum type
+    Enum,
+    /// Argument must be one of the listed types. Generic object types also
+    /// match their instantiations
+    OneOf(Vec<EDataType>),
+    /// Argument must be a list with items satisfying the inner constraint
+    ListOf(Box<GenericConstraint>),
+}
+
+impl GenericConstraint {
+    pub fn parse(kind: &str, types: &[ETypeConst]) -> miette::Result<Self> {
+        let constraint = match kind {
+            "const_string" => Self::ConstString,
+            "struct" => Self::Struct,
+            "enum" => Self::Enum,
+            "one_of" => {
+                if types.is_empty() {
+                    bail!("`one_of` constraint requires at least one type");
+                }
+                let types = types
+                    .iter()
+                    .map(|ty| {
+                        let ETypeConst::String(ty) = ty else {
+                            bail!("expected a type name, got `{}`", ty);
+                        };
+                        Ok(match ty.as_str() {
+                            "boolean" => EDataType::Boolean,
+                            "number" => EDataType::Number,
+                            "string" => EDataType::String,
+                            id => EDataType::Object {
+                                ident: ETypeId::parse(id)?,
+                            },
+                        })
+                    })
+                    .collect::<miette::Result<_>>()?;
+                return Ok(Self::OneOf(types));
+            }
+            "list_of" => {
+                let Some((ETypeConst::String(inner), types)) = types.split_first() else {
+                    bail!("`list_of` constraint requires the constraint of the list items");
+                };
+                return Ok(Self::ListOf(Box::new(Self::parse(inner, types)?)));
+            }
+            other => bail!(
+                "unknown generic constraint `{}`, expected one of `const_string`, `struct`, `enum`, `one_of`, `list_of`",
+                other
+            ),
+        };
+
+        if !types.is_empty() {
+            bail!("`{}` constraint doesn't accept any types", kind);
+        }
+
+        Ok(constraint)
+    }
+
+    /// Checks that the generic argument satisfies the constraint
+    ///
+    /// `list_item` resolves the item types of the list arguments
+    pub fn check<'a>(
+        &self,
+        arg: &EItemInfo,
+        list_item: &impl Fn(&EListId) -> Option<EDataType>,
+        get_object: &mut impl FnMut(&ETypeId) -> miette::Result<WhateverRef<'a, EObjectType>>,
+    ) -> miette::Result<bool> {
+        let ty = arg.ty();
+        Ok(match self {
+            GenericConstraint::ConstString => matches!(
+                ty,
+                EDataType::Const {
+                    value: ETypeConst::String(_)
+                }
+            ),
+            GenericConstraint::Struct | GenericConstraint::Enum => {
+                let EDataType::Object { ident } = ty else {
+                    return Ok(false);
+                };
+                let obj = get_object(&ident)?;
+                match self {
+                    GenericConstraint::Struct => matches!(*obj, EObjectType::Struct(_)),
+                    _ => matches!(*obj, EObjectType::Enum(_)),
+                }
+            }
+            GenericConstraint::OneOf(types) => {
+                let parent = match ty {
+                    EDataType::Object { ident } => get_object(&ident)?.generic_parent_id(),
+                    _ => None,
+                };
+                types.iter().any(|allowed| match allowed {
+                    EDataType::Object { ident } => {
+                        ty == *allowed || parent.is_some_and(|p| p == *ident)
+                    }
+                    _ => ty == *allowed,
+                })
+            }
+            GenericConstraint::ListOf(inner) => {
+                let EDataType::List { id } = ty else {
+                    return Ok(false);
+                };
+                let Some(item) = list_item(&id) else {
+                    return Ok(false);
+                };
+                inner.check(&EItemInfo::simple_type(item), list_item, get_object)?
+            }
+        })
+    }
+}
+
+impl Display for GenericConstraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenericConstraint::ConstString => write!(f, "a constant string"),
+            GenericConstraint::Struct => write!(f, "a struct"),
+            GenericConstraint::Enum => write!(f, "an enum"),
+            GenericConstraint::OneOf(types) => write!(
+                f,
+                "one of {}",
+                types.iter().map(|ty| format!("`{}`", ty.name())).join(", ")
+            ),
+            GenericConstraint::ListOf(inner) => write!(f, "a list of {}", inner),
+        }
+    }
+}
+
+/// Checks the arguments of the generic type `id` against its constraints
+pub fn check_generic_constraints<'a>(
+    id: ETypeId,
+    constraints: &[(Ustr, GenericConstraint)],
+    arguments: &UstrMap<EItemInfo>,
+    list_item: &impl Fn(&EListId) -> Option<EDataType>,
+    get_object: &mut impl FnMut(&ETypeId) -> miette::Result<WhateverRef<'a, EObjectType>>,
+) -> miette::Result<()> {
+    for (name, constraint) in constraints {
+        let arg = arguments
+            .get(name)
+            .ok_or_else(|| miette!("generic argument `{}` is not provided", name))?;
+
+        // Arguments forwarded from another generic type are checked once
+        // that type is instantiated
+        if arg.is_generic() {
+            continue;
+        }
+
+        if !constraint.check(arg, list_item, get_object)? {
+            bail!(
+                "generic argument `{}` of `{}` must be {}, but got `{}`",
+                name,
+                id,
+                constraint,
+                arg.ty().name()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GenericConstraint;
+    use crate::etype::econst::ETypeConst;
+    use crate::etype::eitem::EItemInfo;
+    use crate::etype::EDataType;
+    use crate::project::io::embedded_registry;
+    use crate::registry::ETypesRegistry;
+    use crate::value::id::ETypeId;
+    use rstest::rstest;
+    use ustr::Ustr;
+
+    fn types(names: &[&str]) -> Vec<ETypeConst> {
+        names
+            .iter()
+            .map(|n| ETypeConst::String((*n).into()))
+            .collect()
+    }
+
+    #[rstest]
+    #[case("const_string", &[])]
+    #[case("struct", &[])]
+    #[case("one_of", &["number", "string", "sys:vec2"])]
+    #[case("list_of", &["enum"])]
+    #[case("list_of", &["one_of", "number", "string"])]
+    fn should_parse_constraint(#[case] kind: &str, #[case] names: &[&str]) {
+        assert!(GenericConstraint::parse(kind, &types(names)).is_ok())
+    }
+
+    #[rstest]
+    #[case("const_number", &[])]
+    #[case("struct", &["number"])]
+    #[case("one_of", &[])]
+    #[case("list_of", &[])]
+    #[case("list_of", &["enum", "number"])]
+    fn should_fail_parse(#[case] kind: &str, #[case] names: &[&str]) {
+        assert!(GenericConstraint::parse(kind, &types(names)).is_err())
+    }
+
+    fn registry() -> ETypesRegistry {
+        let flag = r#"
+            enum {
+                const "first" 0
+                const "second" 1
+            }
+        "#;
+        embedded_registry([(ETypeId::from_raw("test:flag".into()), flag.to_string())]).unwrap()
+    }
+
+    enum Arg {
+        Number,
+        ConstString,
+        Flag,
+        ListOf(&'static Arg),
+    }
+
+    fn arg_type(registry: &ETypesRegistry, arg: &Arg) -> EDataType {
+        match arg {
+            Arg::Number => EDataType::Number,
+            Arg::ConstString => EDataType::Const {
+                value: ETypeConst::String("Ship".into()),
+            },
+            Arg::Flag => EDataType::Object {
+                ident: ETypeId::from_raw("test:flag".into()),
+            },
+            Arg::ListOf(item) => registry.list_of(arg_type(registry, item)),
+        }
+    }
+
+    #[rstest]
+    #[case("sys:bitflags", "Flags", Arg::ListOf(&Arg::Flag), true)]
+    #[case("sys:bitflags", "Flags", Arg::Flag, false)]
+    #[case("sys:bitflags", "Flags", Arg::ListOf(&Arg::Number), false)]
+    #[case("sys:bitflags", "Flags", Arg::ListOf(&Arg::ListOf(&Arg::Flag)), false)]
+    #[case("sys:ids/numeric", "Id", Arg::ConstString, true)]
+    #[case("sys:ids/numeric", "Id", Arg::Number, false)]
+    #[case("sys:ids/string_ref", "Id", Arg::ConstString, true)]
+    #[case("sys:ids/string_ref", "Id", Arg::ListOf(&Arg::ConstString), false)]
+    fn should_check_instantiation(
+        #[case] ty: &str,
+        #[case] name: &str,
+        #[case] arg: Arg,
+        #[case] valid: bool,
+    ) {
+        let mut registry = registry();
+        let arg = EItemInfo::simple_type(arg_type(&registry, &arg));
+        let arguments = [(Ustr::from(name), arg)].into_iter().collect();
+        let result = registry.make_generic(ETypeId::from_raw(ty.into()), arguments);
+        assert_eq!(result.is_ok(), valid, "{:?}", result)
+    }
+}
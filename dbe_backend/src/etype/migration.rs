@@ -0,0 +1,871 @@
+//! Versioned data migrations
+//!
+//! Structs and tagged enums can declare a `version` property. Data saved with
+//! an older version is upgraded on load by running the migrations declared in
+//! the `migrations` types config entry:
+//!
+//! ```json5
+//! {
+//!   "migrations": {
+//!     types: {
+//!       "eh:objects/ship": [
+//!         { version: 1, steps: [{ op: "rename", from: "Speed", to: "MaxSpeed" }] },
+//!         { version: 2, graph: "migrations/ship_v2.dbegraph" },
+//!       ]
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Migrations of a type must form a continuous chain ending at the current
+//! version of the type, and data older than the first migration can't be
+//! loaded.
+//!
+//! Declarative steps are applied to the raw JSON before the value is parsed.
+//! Node group migrations operate on the parsed value, and run right after the
+//! struct is parsed, so they can only come after all declarative steps of the
+//! chain. Node group migrations are only supported for structs, and only run
+//! while loading project files, see [collect_applied].
+//!
+//! Enums store the version next to their tag, so only externally and
+//! adjacently tagged enums can be versioned.
+
+use crate::etype::econst::ETypeConst;
+use crate::etype::eenum::pattern::{EnumPattern, Tagged};
+use crate::etype::eobject::EObject;
+use crate::etype::property::default_properties::PROP_OBJECT_VERSION;
+use crate::etype::property::ObjectPropertyId;
+use crate::etype::EDataType;
+use crate::graph::execution::GraphExecutionContext;
+use crate::json_utils::{json_kind, JsonMap, JsonValue};
+use crate::m_try;
+use crate::project::project_graph::ProjectGraphs;
+use crate::project::side_effects::SideEffectsContext;
+use crate::registry::config::merge::ConfigMerge;
+use crate::registry::{EObjectType, ETypesRegistry};
+use crate::value::id::ETypeId;
+use crate::value::EValue;
+use camino::{Utf8Path, Utf8PathBuf};
+use itertools::Itertools;
+use miette::{bail, miette, Context};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
+use utils::map::HashMap;
+
+/// Name of the JSON field holding the data version of a versioned struct
+pub const VERSION_FIELD: &str = "$version";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MigrationsConfig {
+    #[serde(default)]
+    types: HashMap<ETypeId, Vec<Migration>>,
+}
+
+impl ConfigMerge for MigrationsConfig {
+    fn merge(
+        &mut self,
+        paths: &[&Utf8PathBuf],
+        other: Self,
+        other_path: &Utf8PathBuf,
+    ) -> miette::Result<()> {
+        ConfigMerge::merge(&mut self.types, paths, other.types, other_path)
+    }
+}
+
+/// A single migration, upgrading data to the given version
+#[derive(Debug, Clone, Deserialize)]
+pub struct Migration {
+    /// Version that this migration upgrades the data to
+    pub version: u32,
+    /// Declarative steps, applied to the raw JSON in order
+    #[serde(default)]
+    pub steps: Vec<MigrationStep>,
+    /// Path to the node group that should be run on the parsed value
+    ///
+    /// The node group must have a single input and a single output of the
+    /// migrated type
+    #[serde(default)]
+    pub graph: Option<Utf8PathBuf>,
+}
+
+/// Declarative migration step
+///
+/// Paths are dot-separated field names, relative to the migrated struct
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum MigrationStep {
+    /// Renames a field
+    Rename { from: String, to: String },
+    /// Moves a value from one path to another
+    Move { from: String, to: String },
+    /// Wraps a field value into an enum variant
+    ///
+    /// Without `tag`, the value is wrapped as an externally tagged variant.
+    /// With `tag` and `content`, the value is wrapped as an adjacently tagged
+    /// variant. With only `tag`, the value must be an object, and the tag
+    /// field is inserted into it
+    Wrap {
+        field: String,
+        variant: JsonValue,
+        #[serde(default)]
+        tag: Option<String>,
+        #[serde(default)]
+        content: Option<String>,
+    },
+    /// Sets the value at the path if it is missing
+    SetDefault { field: String, value: JsonValue },
+}
+
+impl MigrationStep {
+    fn apply(&self, obj: &mut JsonMap) -> miette::Result<()> {
+        match self {
+            MigrationStep::Rename { from, to } => {
+                if let Some(value) = obj.remove(from) {
+                    if obj.contains_key(to) {
+                        bail!("can't rename `{}` to `{}`: field already exists", from, to);
+                    }
+                    obj.insert(to.clone(), value);
+                }
+            }
+            MigrationStep::Move { from, to } => {
+                if let Some(value) = take_path(obj, from)? {
+                    insert_path(obj, to, value)?;
+                }
+            }
+            MigrationStep::Wrap {
+                field,
+                variant,
+                tag,
+                content,
+            } => {
+                let Some(value) = take_path(obj, field)? else {
+                    return Ok(());
+                };
+
+                let wrapped = match (tag, content) {
+                    (None, _) => {
+                        let JsonValue::String(variant) = variant else {
+                            bail!(
+                                "externally tagged variant name must be a string, but got `{}`",
+                                json_kind(variant)
+                            );
+                        };
+                        let mut map = JsonMap::new();
+                        map.insert(variant.clone(), value);
+                        JsonValue::Object(map)
+                    }
+                    (Some(tag), Some(content)) => {
+                        let mut map = JsonMap::new();
+                        map.insert(tag.clone(), variant.clone());
+                        map.insert(content.clone(), value);
+                        JsonValue::Object(map)
+                    }
+                    (Some(tag), None) => {
+                        let JsonValue::Object(mut map) = value else {
+                            bail!(
+                                "internally tagged variant must be an object, but got `{}`",
+                                json_kind(&value)
+                            );
+                        };
+                        if map.contains_key(tag) {
+                            bail!("tag field `{}` is already present", tag);
+                        }
+                        map.insert(tag.clone(), variant.clone());
+                        JsonValue::Object(map)
+                    }
+                };
+
+                insert_path(obj, field, wrapped)?;
+            }
+            MigrationStep::SetDefault { field, value } => {
+                if get_path(obj, field).is_none() {
+                    insert_path(obj, field, value.clone())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn get_path<'a>(obj: &'a JsonMap, path: &str) -> Option<&'a JsonValue> {
+    let mut segments = path.split('.');
+    let mut value = obj.get(segments.next()?)?;
+    for segment in segments {
+        value = value.as_object()?.get(segment)?;
+    }
+    Some(value)
+}
+
+fn take_path(obj: &mut JsonMap, path: &str) -> miette::Result<Option<JsonValue>> {
+    let Some((parent, last)) = path.rsplit_once('.') else {
+        return Ok(obj.remove(path));
+    };
+
+    let mut current = obj;
+    for segment in parent.split('.') {
+        match current.get_mut(segment) {
+            None => return Ok(None),
+            Some(JsonValue::Object(next)) => current = next,
+            Some(other) => bail!(
+                "expected `{}` to be an object, but got `{}`",
+                segment,
+                json_kind(other)
+            ),
+        }
+    }
+
+    Ok(current.remove(last))
+}
+
+fn insert_path(obj: &mut JsonMap, path: &str, value: JsonValue) -> miette::Result<()> {
+    let (parent, last) = path.rsplit_once('.').unwrap_or(("", path));
+
+    let mut current = obj;
+    for segment in parent.split('.').filter(|s| !s.is_empty()) {
+        let next = current
+            .entry(segment)
+            .or_insert_with(|| JsonValue::Object(JsonMap::new()));
+        let kind = json_kind(next);
+        let JsonValue::Object(next) = next else {
+            bail!("expected `{}` to be an object, but got `{}`", segment, kind);
+        };
+        current = next;
+    }
+
+    if current.contains_key(last) {
+        bail!("field `{}` already exists", path);
+    }
+    current.insert(last.to_string(), value);
+
+    Ok(())
+}
+
+/// Migration that was applied while loading the data
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct AppliedMigration {
+    pub ty: ETypeId,
+    pub from: u32,
+    pub to: u32,
+    /// Node group that was run on the parsed values of this type
+    pub graph: Option<Utf8PathBuf>,
+}
+
+/// Migrations applied during [collect_applied], along with the graphs
+/// used by node group migrations
+struct Collector {
+    applied: Vec<AppliedMigration>,
+    graphs: Rc<ProjectGraphs>,
+}
+
+thread_local! {
+    static COLLECTOR: RefCell<Option<Collector>> = const { RefCell::new(None) };
+}
+
+/// Runs the provided function, collecting all migrations applied during it
+///
+/// Node group migrations can only run inside of this function, the graphs
+/// are lent to the parser for its duration. The returned list is sorted and
+/// deduplicated
+pub fn collect_applied<T>(
+    graphs: &mut ProjectGraphs,
+    func: impl FnOnce() -> T,
+) -> (T, Vec<AppliedMigration>) {
+    let collector = Collector {
+        applied: vec![],
+        graphs: Rc::new(std::mem::take(graphs)),
+    };
+    let previous = COLLECTOR.with_borrow_mut(|c| c.replace(collector));
+    let result = func();
+    let collector = COLLECTOR
+        .with_borrow_mut(|c| std::mem::replace(c, previous))
+        .expect("collector should be present");
+    *graphs = Rc::into_inner(collector.graphs)
+        .expect("graphs should not be retained after the migrations");
+    let mut applied = collector.applied;
+    applied.sort();
+    applied.dedup();
+    (result, applied)
+}
+
+fn record_applied(migration: AppliedMigration) -> miette::Result<()> {
+    COLLECTOR.with_borrow_mut(|collector| {
+        if let Some(collector) = collector {
+            collector.applied.push(migration);
+        } else if let Some(graph) = &migration.graph {
+            bail!(
+                "node group migration `{}` of `{}` to version {} can only run while loading project files",
+                graph,
+                migration.ty,
+                migration.to
+            );
+        }
+        Ok(())
+    })
+}
+
+/// Returns the current data version of the type, if the type is versioned
+pub fn type_version(props: &HashMap<ObjectPropertyId, ETypeConst>) -> miette::Result<Option<u32>> {
+    let Some(version) = PROP_OBJECT_VERSION.try_get(props) else {
+        return Ok(None);
+    };
+
+    if version.0.fract() != 0.0 || version.0 < 0.0 || version.0 > u32::MAX as f64 {
+        bail!(
+            "type version must be a non-negative integer, but got {}",
+            version
+        );
+    }
+
+    Ok(Some(version.0 as u32))
+}
+
+/// Upgrades the JSON data of a versioned object to the given version
+///
+/// The version field is removed from the data. Declarative steps are applied
+/// right away, and the node groups of the applied migrations are returned in
+/// version order, to be run on the parsed value with [run_graphs]
+pub(crate) fn migrate(
+    registry: &ETypesRegistry,
+    ty: ETypeId,
+    version: u32,
+    obj: &mut JsonMap,
+) -> miette::Result<Vec<(u32, Utf8PathBuf)>> {
+    let data_version = take_version(ty, version, obj)?;
+    let (_, graphs) = upgrade(registry, ty, data_version, version, obj, None)?;
+    Ok(graphs)
+}
+
+/// Upgrades the top-level object of a prefab file with the declarative steps
+/// of its migrations, so the file can be merged with its base
+///
+/// `base` is the already upgraded and merged JSON of the prefab base. It
+/// provides the enum tag when the file doesn't override it, and the steps
+/// setting default values skip the fields inherited from it.
+///
+/// Node group migrations need the parsed value, so the upgrade stops before
+/// the first of them, and the rest of the chain runs when the merged data is
+/// parsed. The reached version is written into the data and returned. Returns
+/// `None` if the top-level object is not versioned
+pub fn migrate_prefab(
+    registry: &ETypesRegistry,
+    ty: EDataType,
+    json: &mut JsonValue,
+    base: Option<&JsonMap>,
+) -> miette::Result<Option<u32>> {
+    let EDataType::Object { ident } = ty else {
+        return Ok(None);
+    };
+    let Some(obj) = registry.get_object(&ident) else {
+        return Ok(None);
+    };
+
+    let version = match obj.deref() {
+        EObjectType::Struct(data) if data.repr.is_none() => type_version(&data.extra_properties)?,
+        EObjectType::Enum(data) if data.repr.is_none() => {
+            match type_version(&data.extra_properties)? {
+                Some(version) => Some(version),
+                None => {
+                    let mut merged = base.cloned().unwrap_or_default();
+                    if let JsonValue::Object(json) = &*json {
+                        merged.extend(json.clone());
+                    }
+                    let Some(variant) = data.matching_variant(&JsonValue::Object(merged)) else {
+                        return Ok(None);
+                    };
+                    // Only internally tagged and untagged variants store their
+                    // fields at the top level
+                    if matches!(
+                        variant.pat,
+                        EnumPattern::Tagged {
+                            repr: Tagged::External | Tagged::Adjacent { .. },
+                            ..
+                        }
+                    ) {
+                        return Ok(None);
+                    }
+                    return migrate_prefab(registry, variant.data.ty(), json, base);
+                }
+            }
+        }
+        _ => None,
+    };
+    let Some(version) = version else {
+        return Ok(None);
+    };
+
+    let kind = json_kind(json);
+    let JsonValue::Object(obj) = json else {
+        bail!("versioned object must be a JSON object, but got `{}`", kind);
+    };
+    let data_version = take_version(ident, version, obj)?;
+    let (reached, _) = upgrade(
+        registry,
+        ident,
+        data_version,
+        version,
+        obj,
+        Some(base.unwrap_or(&JsonMap::new())),
+    )?;
+    obj.insert(VERSION_FIELD.to_string(), reached.into());
+
+    Ok(Some(reached))
+}
+
+/// Removes the version field from the data, returning the data version
+fn take_version(ty: ETypeId, version: u32, obj: &mut JsonMap) -> miette::Result<u32> {
+    let data_version = match obj.remove(VERSION_FIELD) {
+        None => 0,
+        Some(value) => value
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| {
+                miette!(
+                    "`{}` must be a non-negative integer, but got `{}`",
+                    VERSION_FIELD,
+                    value
+                )
+            })?,
+    };
+
+    if data_version > version {
+        bail!(
+            "data version {} is newer than the version {} of type `{}`",
+            data_version,
+            version,
+            ty
+        );
+    }
+
+    Ok(data_version)
+}
+
+/// Applies the migration chain from the data version to the given version,
+/// returning the reached version and the node groups to run
+///
+/// With the fields inherited from a prefab base, the chain stops before the
+/// first node group migration, see [migrate_prefab]
+fn upgrade(
+    registry: &ETypesRegistry,
+    ty: ETypeId,
+    data_version: u32,
+    version: u32,
+    obj: &mut JsonMap,
+    inherited: Option<&JsonMap>,
+) -> miette::Result<(u32, Vec<(u32, Utf8PathBuf)>)> {
+    if data_version == version {
+        return Ok((version, vec![]));
+    }
+
+    let config = config(registry)?;
+    let migrations = config
+        .types
+        .get(&ty.strip_generics())
+        .map(|m| m.as_slice())
+        .unwrap_or_default();
+
+    let mut current = data_version;
+    let mut graphs = vec![];
+    for migration in migrations
+        .iter()
+        .filter(|m| m.version > data_version && m.version <= version)
+        .sorted_by_key(|m| m.version)
+    {
+        if migration.version != current + 1 {
+            break;
+        }
+        if inherited.is_some() && migration.graph.is_some() {
+            return Ok((current, graphs));
+        }
+
+        m_try(|| {
+            for step in &migration.steps {
+                if let (MigrationStep::SetDefault { field, .. }, Some(inherited)) =
+                    (step, inherited)
+                {
+                    if get_path(inherited, field).is_some() {
+                        continue;
+                    }
+                }
+                step.apply(obj)?;
+            }
+            Ok(())
+        })
+        .with_context(|| {
+            format!(
+                "failed to migrate `{}` from version {} to {}",
+                ty, current, migration.version
+            )
+        })?;
+
+        record_applied(AppliedMigration {
+            ty,
+            from: current,
+            to: migration.version,
+            graph: migration.graph.clone(),
+        })?;
+        if let Some(graph) = &migration.graph {
+            graphs.push((migration.version, graph.clone()));
+        }
+        current = migration.version;
+    }
+
+    if current != version {
+        bail!(
+            "no migration of type `{}` upgrades data from version {} to {}",
+            ty,
+            current,
+            current + 1
+        );
+    }
+
+    Ok((current, graphs))
+}
+
+/// Runs the node group migrations returned by [migrate] on the parsed value
+pub(crate) fn run_graphs(
+    registry: &ETypesRegistry,
+    ty: ETypeId,
+    graphs: &[(u32, Utf8PathBuf)],
+    mut value: EValue,
+) -> miette::Result<EValue> {
+    let project_graphs = COLLECTOR
+        .with_borrow(|c| c.as_ref().map(|c| c.graphs.clone()))
+        .expect("node group migrations should only be returned while collecting");
+
+    for (version, graph_path) in graphs {
+        value = run_graph(registry, &project_graphs, value, graph_path).with_context(|| {
+            format!(
+                "failed to run migration node group `{}` for `{}` version {}",
+                graph_path, ty, version
+            )
+        })?;
+    }
+
+    Ok(value)
+}
+
+/// Runs a single node group migration on the value
+fn run_graph(
+    registry: &ETypesRegistry,
+    graphs: &ProjectGraphs,
+    value: EValue,
+    graph_path: &Utf8Path,
+) -> miette::Result<EValue> {
+    let graph = graphs
+        .graph_by_path(graph_path)
+        .ok_or_else(|| miette!("graph `{}` is not found", graph_path))?;
+
+    if !graph.is_node_group {
+        bail!("graph `{}` is not a node group", graph_path);
+    }
+
+    if graph.inputs().len() != 1 || graph.outputs().len() != 1 {
+        bail!(
+            "migration node group must have exactly one input and one output, but got {} inputs and {} outputs",
+            graph.inputs().len(),
+            graph.outputs().len()
+        );
+    }
+
+    let input = [value];
+    let mut outputs = None;
+    let mut ctx = GraphExecutionContext::from_graph(
+        graph.graph(),
+        registry,
+        Some(graphs),
+        SideEffectsContext::unavailable(),
+        true,
+        &input,
+        &mut outputs,
+    );
+    ctx.full_eval(true)?;
+    drop(ctx);
+
+    let output = outputs
+        .and_then(|o| o.into_iter().next())
+        .ok_or_else(|| miette!("migration node group did not emit any outputs"))?;
+
+    if output.ty() != input[0].ty() {
+        bail!(
+            "migration node group returned a value of type `{}`, but `{}` was expected",
+            output.ty().name(),
+            input[0].ty().name()
+        );
+    }
+
+    Ok(output)
+}
+
+/// Checks the versions of all registered types against the configured
+/// migrations
+pub fn check_migrations(registry: &ETypesRegistry) -> miette::Result<()> {
+    let mut versions = HashMap::default();
+    for obj in registry.all_ready_objects() {
+        if obj.generic_parent_id().is_some() {
+            continue;
+        }
+        let Some(version) = type_version(obj.extra_properties())
+            .with_context(|| format!("in type `{}`", obj.ident()))?
+        else {
+            continue;
+        };
+        if let Some(data) = obj.as_enum() {
+            if !data.is_versionable() {
+                bail!(
+                    "enum `{}` is versioned, but only externally and adjacently tagged enums can be versioned",
+                    obj.ident()
+                );
+            }
+        }
+        versions.insert(obj.ident(), (version, obj.as_struct().is_some()));
+    }
+
+    for (ty, migrations) in config(registry)?.types.iter() {
+        m_try(|| {
+            let Some((version, is_struct)) = versions.get(ty) else {
+                bail!("type is not found or has no `version` property");
+            };
+
+            let mut expected = None;
+            let mut first_graph = None;
+            for migration in migrations.iter().sorted_by_key(|m| m.version) {
+                if migration.version == 0 || migration.version > *version {
+                    bail!(
+                        "migration to version {} is outside of the type versions 1..={}",
+                        migration.version,
+                        version
+                    );
+                }
+                if migration.graph.is_some() && !is_struct {
+                    bail!(
+                        "migration to version {} uses a node group, but only structs support node group migrations",
+                        migration.version
+                    );
+                }
+                if let Some(first_graph) = first_graph {
+                    if !migration.steps.is_empty() {
+                        bail!(
+                            "migration to version {} has declarative steps after the node group migration to version {}, but declarative steps always run before node groups",
+                            migration.version,
+                            first_graph
+                        );
+                    }
+                }
+                if migration.graph.is_some() {
+                    first_graph.get_or_insert(migration.version);
+                }
+                if let Some(expected) = expected {
+                    if migration.version < expected {
+                        bail!(
+                            "more than one migration to version {}",
+                            migration.version
+                        );
+                    }
+                    if migration.version != expected {
+                        bail!(
+                            "migration chain has a gap: expected a migration to version {}, but got {}",
+                            expected,
+                            migration.version
+                        );
+                    }
+                }
+                expected = Some(migration.version + 1);
+            }
+
+            if let Some(expected) = expected {
+                if expected != *version + 1 {
+                    bail!(
+                        "migration chain ends at version {}, but the type is at version {}",
+                        expected - 1,
+                        version
+                    );
+                }
+            }
+
+            Ok(())
+        })
+        .with_context(|| format!("invalid migrations of type `{}`", ty))?;
+    }
+
+    Ok(())
+}
+
+fn config(registry: &ETypesRegistry) -> miette::Result<Arc<MigrationsConfig>> {
+    registry.config().get::<MigrationsConfig>("migrations")
+}
+
+/// Returns the paths of all node groups used by migrations
+pub fn migration_graphs(registry: &ETypesRegistry) -> miette::Result<Vec<Utf8PathBuf>> {
+    Ok(config(registry)?
+        .types
+        .values()
+        .flatten()
+        .filter_map(|migration| migration.graph.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_migrations, insert_path, take_path, MigrationStep};
+    use crate::json_utils::{JsonMap, JsonValue};
+    use crate::project::io::embedded_registry;
+    use crate::registry::ETypesRegistry;
+    use crate::value::id::ETypeId;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn obj(value: JsonValue) -> JsonMap {
+        let JsonValue::Object(map) = value else {
+            panic!("expected an object");
+        };
+        map
+    }
+
+    fn step(value: JsonValue) -> MigrationStep {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn registry(migrations: JsonValue) -> ETypesRegistry {
+        let kdl = r#"struct version=2 { number "Speed" }"#.to_string();
+        let mut registry =
+            embedded_registry([(ETypeId::from_raw("test:ship".into()), kdl)]).unwrap();
+        registry.extra_config_mut("migrations".to_string()).push((
+            "types.json5".into(),
+            json!({"types": {"test:ship": migrations}}),
+        ));
+        registry
+    }
+
+    #[rstest]
+    #[case("a", Some(json!(1)), json!({"b": {"c": 2}}))]
+    #[case("b.c", Some(json!(2)), json!({"a": 1, "b": {}}))]
+    #[case("b.missing", None, json!({"a": 1, "b": {"c": 2}}))]
+    #[case("missing.c", None, json!({"a": 1, "b": {"c": 2}}))]
+    fn should_take_path(
+        #[case] path: &str,
+        #[case] expected: Option<JsonValue>,
+        #[case] rest: JsonValue,
+    ) {
+        let mut data = obj(json!({"a": 1, "b": {"c": 2}}));
+        assert_eq!(take_path(&mut data, path).unwrap(), expected);
+        assert_eq!(JsonValue::Object(data), rest);
+    }
+
+    #[test]
+    fn should_fail_take_through_non_object() {
+        let mut data = obj(json!({"a": 1}));
+        assert!(take_path(&mut data, "a.b").is_err());
+    }
+
+    #[rstest]
+    #[case("c", json!({"a": {"b": 1}, "c": 2}))]
+    #[case("a.c", json!({"a": {"b": 1, "c": 2}}))]
+    #[case("x.y.z", json!({"a": {"b": 1}, "x": {"y": {"z": 2}}}))]
+    fn should_insert_path(#[case] path: &str, #[case] expected: JsonValue) {
+        let mut data = obj(json!({"a": {"b": 1}}));
+        insert_path(&mut data, path, json!(2)).unwrap();
+        assert_eq!(JsonValue::Object(data), expected);
+    }
+
+    #[rstest]
+    #[case("a.b")]
+    #[case("a.b.c")]
+    fn should_fail_insert_path(#[case] path: &str) {
+        let mut data = obj(json!({"a": {"b": 1}}));
+        assert!(insert_path(&mut data, path, json!(2)).is_err());
+    }
+
+    #[rstest]
+    #[case::rename(
+        json!({"op": "rename", "from": "speed", "to": "max_speed"}),
+        json!({"speed": 1}),
+        json!({"max_speed": 1})
+    )]
+    #[case::rename_missing(
+        json!({"op": "rename", "from": "speed", "to": "max_speed"}),
+        json!({"other": 1}),
+        json!({"other": 1})
+    )]
+    #[case::move_nested(
+        json!({"op": "move", "from": "engine.speed", "to": "stats.speed"}),
+        json!({"engine": {"speed": 1}}),
+        json!({"engine": {}, "stats": {"speed": 1}})
+    )]
+    #[case::wrap_external(
+        json!({"op": "wrap", "field": "weapon", "variant": "Laser"}),
+        json!({"weapon": {"damage": 1}}),
+        json!({"weapon": {"Laser": {"damage": 1}}})
+    )]
+    #[case::wrap_adjacent(
+        json!({"op": "wrap", "field": "weapon", "variant": "Laser", "tag": "type", "content": "data"}),
+        json!({"weapon": 1}),
+        json!({"weapon": {"type": "Laser", "data": 1}})
+    )]
+    #[case::wrap_internal(
+        json!({"op": "wrap", "field": "weapon", "variant": "Laser", "tag": "type"}),
+        json!({"weapon": {"damage": 1}}),
+        json!({"weapon": {"type": "Laser", "damage": 1}})
+    )]
+    #[case::set_default(
+        json!({"op": "set_default", "field": "stats.speed", "value": 5}),
+        json!({}),
+        json!({"stats": {"speed": 5}})
+    )]
+    #[case::set_default_present(
+        json!({"op": "set_default", "field": "speed", "value": 5}),
+        json!({"speed": 1}),
+        json!({"speed": 1})
+    )]
+    fn should_apply_step(
+        #[case] migration: JsonValue,
+        #[case] data: JsonValue,
+        #[case] expected: JsonValue,
+    ) {
+        let mut data = obj(data);
+        step(migration).apply(&mut data).unwrap();
+        assert_eq!(JsonValue::Object(data), expected);
+    }
+
+    #[rstest]
+    #[case::rename_conflict(
+        json!({"op": "rename", "from": "speed", "to": "max_speed"}),
+        json!({"speed": 1, "max_speed": 2})
+    )]
+    #[case::wrap_external_non_string(
+        json!({"op": "wrap", "field": "weapon", "variant": 1}),
+        json!({"weapon": 1})
+    )]
+    #[case::wrap_internal_non_object(
+        json!({"op": "wrap", "field": "weapon", "variant": "Laser", "tag": "type"}),
+        json!({"weapon": 1})
+    )]
+    #[case::wrap_internal_tag_present(
+        json!({"op": "wrap", "field": "weapon", "variant": "Laser", "tag": "type"}),
+        json!({"weapon": {"type": "Rocket"}})
+    )]
+    fn should_fail_step(#[case] migration: JsonValue, #[case] data: JsonValue) {
+        let mut data = obj(data);
+        assert!(step(migration).apply(&mut data).is_err());
+    }
+
+    #[rstest]
+    #[case::steps_then_graph(json!([
+        {"version": 1, "steps": [{"op": "rename", "from": "Velocity", "to": "Speed"}]},
+        {"version": 2, "graph": "ship_v2.dbegraph"},
+    ]), true)]
+    #[case::steps_with_graph(json!([
+        {"version": 1, "steps": [{"op": "rename", "from": "Velocity", "to": "Speed"}], "graph": "ship_v1.dbegraph"},
+        {"version": 2, "graph": "ship_v2.dbegraph"},
+    ]), true)]
+    #[case::steps_after_graph(json!([
+        {"version": 1, "graph": "ship_v1.dbegraph"},
+        {"version": 2, "steps": [{"op": "rename", "from": "Velocity", "to": "Speed"}]},
+    ]), false)]
+    fn should_check_steps_before_graphs(#[case] migrations: JsonValue, #[case] valid: bool) {
+        assert_eq!(check_migrations(&registry(migrations)).is_ok(), valid);
+    }
+}
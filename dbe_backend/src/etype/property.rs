@@ -1,7 +1,7 @@
 use crate::etype::econst::ETypeConst;
 use atomic_refcell::AtomicRefCell;
 use itertools::Itertools;
-use miette::Context;
+use miette::{bail, Context};
 pub use paste;
 use std::borrow::Cow;
 use std::fmt::Debug;
@@ -116,8 +116,9 @@ macro_rules! extra_properties {
 }
 
 pub fn field_props(
-    props: HashMap<String, ETypeConst>,
+    mut props: HashMap<String, ETypeConst>,
 ) -> miette::Result<HashMap<FieldPropertyId, ETypeConst>> {
+    legacy_number_type(&mut props)?;
     let all_props = ALL_PROPERTIES.borrow();
     props
         .into_iter()
@@ -128,6 +129,41 @@ pub fn field_props(
         .try_collect()
 }
 
+/// Translates the legacy `type="int"` and `type="float"` number field
+/// properties into the `integer` property
+///
+/// The `type` property itself is kept for the external tools that read it
+fn legacy_number_type(props: &mut HashMap<String, ETypeConst>) -> miette::Result<()> {
+    let Some(&ty) = props.get("type") else {
+        return Ok(());
+    };
+
+    let integer = match ty.as_string().as_deref() {
+        Some("int") => true,
+        Some("float") => false,
+        _ => bail!(
+            "failed to parse property type: expected `int` or `float`, but got {}",
+            ty
+        ),
+    };
+
+    let key = default_properties::PROP_FIELD_INTEGER.info().id;
+    match props.get(key) {
+        None => {
+            props.insert(key.to_string(), ETypeConst::Boolean(integer));
+        }
+        Some(ETypeConst::Boolean(value)) if *value == integer => {}
+        Some(value) => bail!(
+            "property type={} contradicts property {}={}",
+            ty,
+            key,
+            value
+        ),
+    }
+
+    Ok(())
+}
+
 pub fn object_props(
     props: HashMap<String, ETypeConst>,
 ) -> miette::Result<HashMap<ObjectPropertyId, ETypeConst>> {
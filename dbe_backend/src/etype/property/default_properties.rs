@@ -1,6 +1,7 @@
 use crate::etype::econst::ETypeConst;
 use crate::etype::property::wrappers::parser::ParsedFmtProp;
 use crate::extra_properties;
+use crate::value::ENumber;
 use ustr::Ustr;
 
 extra_properties! {
@@ -8,6 +9,42 @@ extra_properties! {
     pub prop<field> default: ETypeConst;
     pub prop<field> inline: bool;
 
+    /// Whether the numeric field only accepts integer values
+    ///
+    /// The legacy `type="int"` property sets this property as well
+    pub prop<field> integer: bool;
+
+    /// Bit value of the enum variant when used in a `bitflags` set
+    pub prop<field> bit: ENumber;
+
+    /// Matching priority of the untagged enum variant, variants with higher
+    /// priority are tried first
+    pub prop<field> priority: ENumber;
+
+    /// Comma-separated list of file extensions allowed for the asset path
+    pub prop<field> extensions: Ustr;
+
+    /// Project directory that the asset path is relative to
+    pub prop<field> base_dir: Ustr;
+
+    /// Expression over the sibling number fields that the field value is computed from
+    pub prop<field> computed: Ustr;
+
+    /// Path to the node group that the field value is computed by
+    pub prop<field> computed_graph: Ustr;
+
+    /// Condition under which the field must hold a non-default value, e.g. `kind == Beam`
+    pub prop<field> required_if: Ustr;
+
+    /// Condition under which the field is shown in the editor, e.g. `kind == Beam`
+    pub prop<field> visible_if: Ustr;
+
+    /// Short description of the field or enum variant, merged into the type docs
+    pub prop<field> description: Ustr;
+
+    /// Long-form documentation of the field or enum variant, usually set via `doc` children
+    pub prop<field> doc: Ustr;
+
     /// Whether to save default values for fields in this strict
     pub prop<object> save_default_values: bool;
 
@@ -29,4 +66,13 @@ extra_properties! {
 
     /// Whether this type should have an inline value in the graph input
     pub prop<object> graph_inline: bool;
+
+    /// Current data version of the type, used to run migrations on older data
+    pub prop<object> version: ENumber;
+
+    /// Short description of the type, merged into the type docs
+    pub prop<object> description: Ustr;
+
+    /// Long-form documentation of the type, usually set via `doc` children
+    pub prop<object> doc: Ustr;
 }
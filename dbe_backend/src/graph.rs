@@ -30,6 +30,7 @@ pub mod execution;
 pub mod inputs;
 pub mod node;
 pub mod region;
+pub mod validation;
 
 /// A container of a graph with inline values. It contains all the data
 /// that is unique to this graph and is required for both node groups and standalone graphs
@@ -37,6 +37,7 @@ macro_rules! node_context {
 pub struct GraphExecutionContext<'a, 'snarl> {
     pub snarl: &'snarl Snarl<SnarlNode>,
     pub ctx: PartialGraphExecutionContext<'a>,
+    failed_node: Option<NodeId>,
 }
 
 impl<'a> GraphExecutionContext<'a, 'a> {
@@ -98,6 +99,7 @@ impl<'a> GraphExecutionContext<'a, 'a> {
                 region_graph,
                 regional_data: Default::default(),
             },
+            failed_node: None,
         }
     }
 }
@@ -127,6 +129,7 @@ impl GraphExecutionContext<'_, '_> {
             .context("Graph structure is invalid")?;
 
         self.cache.clear();
+        self.failed_node = None;
         for (id, node) in self.snarl.node_ids() {
             if !node.has_side_effects() {
                 continue;
@@ -144,6 +147,11 @@ impl GraphExecutionContext<'_, '_> {
         Ok(())
     }
 
+    /// Returns the innermost node that failed during the last evaluation
+    pub fn failed_node(&self) -> Option<NodeId> {
+        self.failed_node
+    }
+
     pub fn read_output(&mut self, id: OutPinId) -> miette::Result<EValue> {
         self.read_node_output_inner(id, false)
     }
@@ -286,7 +294,7 @@ impl GraphExecutionContext<'_, '_> {
     fn eval_node_inner(&mut self, id: NodeId, run_side_effects: bool) -> miette::Result<()> {
         // trace!("Evaluating node {:?}", id);
         let mut iteration = 0;
-        m_try(|| {
+        let result = m_try(|| {
             loop {
                 iteration += 1;
                 let node = self
@@ -397,7 +405,13 @@ impl GraphExecutionContext<'_, '_> {
                     iteration, id
                 )
             }
-        })
+        });
+
+        if result.is_err() && self.failed_node.is_none() {
+            self.failed_node = Some(id);
+        }
+
+        result
     }
 }
 
@@ -516,6 +530,7 @@ impl<'a> PartialGraphExecutionContext<'a> {
                 region_graph: self.region_graph,
                 regional_data: MaybeOwnedMut::Borrowed(&mut self.regional_data),
             },
+            failed_node: None,
         }
     }
 }
@@ -8,7 +8,9 @@ use crate::graph::node::expression_node::ExpressionNodeFactory;
 use crate::graph::node::extras::ExecutionExtras;
 use crate::graph::node::format_node::FormatNodeFactory;
 use crate::graph::node::functional::functional_nodes;
-use crate::graph::node::generic::destructuring::DestructuringNodeFactory;
+use crate::graph::node::generic::destructuring::{
+    DestructuringNodeFactory, TupleDestructuringNodeFactory,
+};
 use crate::graph::node::groups::input::GroupInputNodeFactory;
 use crate::graph::node::groups::output::GroupOutputNodeFactory;
 use crate::graph::node::groups::subgraph::SubgraphNodeFactory;
@@ -109,6 +111,7 @@ fn default_nodes() -> impl Iterator<Item = (Ustr, Arc<dyn NodeFactory>)> {
     push(v, FormatNodeFactory);
     push(v, ExpressionNodeFactory);
     push(v, DestructuringNodeFactory);
+    push(v, TupleDestructuringNodeFactory);
     // regional
     push(v, RegionalNodeFactory::<RepeatNode>::INSTANCE);
     push(v, RegionalNodeFactory::<ConditionalIfNode>::INSTANCE);
@@ -18,6 +18,7 @@ mod impls;
 mod macros;
 mod values;
 
+mod bitflags;
 mod debug;
 mod list;
 mod mappings;
@@ -411,6 +412,7 @@ pub fn functional_nodes() -> Vec<Arc<dyn NodeFactory>> {
         ),
     ];
 
+    nodes.extend(bitflags::nodes());
     nodes.extend(debug::nodes());
     nodes.extend(list::nodes());
     nodes.extend(mappings::nodes());
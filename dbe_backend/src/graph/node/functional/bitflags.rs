@@ -0,0 +1,71 @@
+use crate::graph::node::functional::generic::GenericValue;
+use crate::graph::node::functional::{functional_node, C};
+use crate::graph::node::NodeFactory;
+use crate::json_utils::repr::bitflags::FLAGS_FIELD;
+use crate::registry::ETypesRegistry;
+use crate::value::EValue;
+use miette::{bail, miette};
+use std::sync::Arc;
+use ustr::Ustr;
+
+/// Returns the list of set flags of the bitflags value, checking that the
+/// flag belongs to it
+fn flags_of<'a>(
+    registry: &ETypesRegistry,
+    value: &'a mut EValue,
+    flag: &EValue,
+) -> miette::Result<&'a mut Vec<EValue>> {
+    let EValue::Struct { ident, fields } = value else {
+        bail!("expected bitflags, got `{}`", value.ty().name());
+    };
+
+    let ident = *ident;
+    let Some(EValue::List { id, values }) = fields.get_mut(&Ustr::from(FLAGS_FIELD)) else {
+        bail!("`{}` is not a bitflags struct", ident);
+    };
+
+    let list = registry
+        .get_list(id)
+        .ok_or_else(|| miette!("!!INTERNAL ERROR!! unknown list `{}`", id))?;
+
+    if list.value_type != flag.ty() {
+        bail!(
+            "flag of type `{}` does not belong to `{}`",
+            flag.ty().name(),
+            ident
+        );
+    }
+
+    Ok(values)
+}
+
+pub(super) fn nodes() -> Vec<Arc<dyn NodeFactory>> {
+    vec![
+        functional_node(
+            |ctx: C, mut flags: GenericValue<0>, flag: GenericValue<1>| {
+                let values = flags_of(ctx.context.registry, &mut flags.0, &flag.0)?;
+                Ok(values.contains(&flag.0))
+            },
+            "bitflags_test",
+            &["flags", "flag"],
+            &["is set"],
+            &["bitflags"],
+        ),
+        functional_node(
+            |ctx: C, mut flags: GenericValue<0>, flag: GenericValue<1>, set: bool| {
+                let values = flags_of(ctx.context.registry, &mut flags.0, &flag.0)?;
+                let is_set = values.contains(&flag.0);
+                if set && !is_set {
+                    values.push(flag.0);
+                } else if !set && is_set {
+                    values.retain(|v| v != &flag.0);
+                }
+                Ok(flags)
+            },
+            "bitflags_set",
+            &["flags", "flag", "set"],
+            &["flags"],
+            &["bitflags"],
+        ),
+    ]
+}
@@ -12,7 +12,7 @@ use crate::json_utils::JsonValue;
 use crate::m_try;
 use crate::project::docs::{Docs, DocsWindowRef};
 use crate::registry::{ETypesRegistry, OPTIONAL_ID};
-use crate::value::id::ETypeId;
+use crate::value::id::{ETupleId, ETypeId};
 use crate::value::EValue;
 use downcast_rs::Downcast;
 use dyn_clone::DynClone;
@@ -37,6 +37,8 @@ pub enum GenericNodeField<'a> {
     Option(&'a Option<EDataType>),
     /// A type is a struct id.
     Object(&'a Option<ETypeId>),
+    /// A type is a tuple id.
+    Tuple(&'a Option<ETupleId>),
     /// A type is fixed.
     Fixed(EDataType),
 }
@@ -47,6 +49,7 @@ pub enum GenericNodeFieldMut<'a> {
     Value(&'a mut Option<EDataType>),
     Option(&'a mut Option<EDataType>),
     Object(&'a mut Option<ETypeId>),
+    Tuple(&'a mut Option<ETupleId>),
     Fixed(EDataType),
 }
 
@@ -57,6 +60,7 @@ impl GenericNodeFieldMut<'_> {
             GenericNodeFieldMut::Value(ty) => GenericNodeField::Value(ty),
             GenericNodeFieldMut::Option(ty) => GenericNodeField::Option(ty),
             GenericNodeFieldMut::Object(id) => GenericNodeField::Object(id),
+            GenericNodeFieldMut::Tuple(id) => GenericNodeField::Tuple(id),
             GenericNodeFieldMut::Fixed(ty) => GenericNodeField::Fixed(*ty),
         }
     }
@@ -102,6 +106,17 @@ impl GenericNodeFieldMut<'_> {
 
                 **id = Some(ident);
             }
+            GenericNodeFieldMut::Tuple(id) => {
+                if id.is_some() {
+                    bail!("Tuple type already set");
+                }
+
+                let EDataType::Tuple { id: incoming } = incoming.ty() else {
+                    return Ok(false);
+                };
+
+                **id = Some(incoming);
+            }
             GenericNodeFieldMut::Option(id) => {
                 if id.is_some() {
                     bail!("Option type already set");
@@ -152,6 +167,16 @@ impl GenericNodeFieldMut<'_> {
                 };
                 **ident = Some(incoming);
             }
+            GenericNodeFieldMut::Tuple(id) => {
+                let Some(incoming) = incoming else {
+                    **id = None;
+                    return Ok(());
+                };
+                let EDataType::Tuple { id: incoming } = incoming else {
+                    bail!("Expected tuple type");
+                };
+                **id = Some(incoming);
+            }
             GenericNodeFieldMut::Fixed(_) => {}
         }
         Ok(())
@@ -165,6 +190,7 @@ impl GenericNodeField<'_> {
             GenericNodeField::Value(ty) => ty.is_some(),
             GenericNodeField::Option(ty) => ty.is_some(),
             GenericNodeField::Object(ident) => ident.is_some(),
+            GenericNodeField::Tuple(id) => id.is_some(),
             GenericNodeField::Fixed(_) => true,
         }
     }
@@ -179,6 +205,9 @@ impl GenericNodeField<'_> {
             GenericNodeField::Object(ident) => ident
                 .map(|ident| EDataType::Object { ident })
                 .unwrap_or_else(EDataType::null),
+            GenericNodeField::Tuple(id) => id
+                .map(|id| EDataType::Tuple { id })
+                .unwrap_or_else(EDataType::null),
             GenericNodeField::Fixed(ty) => *ty,
         }
     }
@@ -222,6 +251,12 @@ impl GenericNodeField<'_> {
                 }
                 Ok(incoming.ty().is_object())
             }
+            GenericNodeField::Tuple(id) => {
+                if id.is_some() {
+                    bail!("Tuple type already set");
+                }
+                Ok(incoming.ty().is_tuple())
+            }
             GenericNodeField::Fixed(_) => {
                 bail!("Fixed type cannot be changed");
             }
@@ -257,6 +292,7 @@ impl GenericNodeField<'_> {
             GenericNodeField::Option(ty) => **ty,
             GenericNodeField::Fixed(ty) => Some(*ty),
             GenericNodeField::Object(ident) => ident.map(|ident| EDataType::Object { ident }),
+            GenericNodeField::Tuple(id) => id.map(|id| EDataType::Tuple { id }),
         }
     }
 }
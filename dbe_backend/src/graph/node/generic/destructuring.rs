@@ -1,4 +1,6 @@
+use crate::etype::eitem::EItemInfo;
 use crate::etype::eobject::EObject;
+use crate::etype::EDataType;
 use crate::graph::node::commands::{SnarlCommand, SnarlCommands};
 use crate::graph::node::extras::ExecutionExtras;
 use crate::graph::node::generic::{generic_try_connect, GenericNodeField, GenericNodeFieldMut};
@@ -8,7 +10,7 @@ use crate::graph::node::serde_node::impl_serde_node;
 use crate::graph::node::struct_node::StructNodeFieldMapper;
 use crate::graph::node::{ExecutionResult, Node, NodeContext, NodeFactory};
 use crate::project::docs::{Docs, DocsRef};
-use crate::value::id::ETypeId;
+use crate::value::id::{ETupleId, ETypeId};
 use crate::value::EValue;
 use egui_snarl::{InPin, NodeId, OutPin, OutPinId};
 use miette::{bail, miette};
@@ -193,3 +195,140 @@ impl NodeFactory for DestructuringNodeFactory {
         Box::new(DestructuringNode::default())
     }
 }
+
+#[derive(Debug, Clone, Hash, Default, Serialize, Deserialize)]
+pub struct TupleDestructuringNode {
+    pub id: Option<ETupleId>,
+}
+
+impl Node for TupleDestructuringNode {
+    impl_serde_node!();
+
+    fn id(&self) -> Ustr {
+        TupleDestructuringNodeFactory.id()
+    }
+
+    fn title(&self, context: NodeContext, _docs: &Docs) -> String {
+        let Some(id) = self.id else {
+            return "Tuple Destructuring".into();
+        };
+
+        EDataType::Tuple { id }.title(context.registry)
+    }
+
+    fn has_inline_values(&self) -> miette::Result<bool> {
+        Ok(false)
+    }
+
+    fn inputs_count(&self, _context: NodeContext) -> usize {
+        1
+    }
+
+    fn input_unchecked(&self, context: NodeContext, input: usize) -> miette::Result<InputData> {
+        if input != 0 {
+            bail!("Tuple destructuring only has one input")
+        }
+        Ok(GenericNodeField::Tuple(&self.id).as_input_ty(context, "input"))
+    }
+
+    fn outputs_count(&self, context: NodeContext) -> usize {
+        self.id
+            .and_then(|id| context.registry.get_tuple(&id))
+            .map_or(0, |data| data.item_types.len())
+    }
+
+    fn output_unchecked(&self, context: NodeContext, output: usize) -> miette::Result<OutputData> {
+        let Some(id) = self.id else {
+            bail!("Tuple id is not set");
+        };
+        let Some(data) = context.registry.get_tuple(&id) else {
+            bail!("Unknown tuple `{}`", id);
+        };
+
+        let Some(ty) = data.item_types.get(output) else {
+            bail!("Tuple `{}` has no item at index {}", id, output);
+        };
+
+        Ok(OutputData::new(
+            EItemInfo::simple_type(*ty).into(),
+            output.to_string().into(),
+        ))
+    }
+
+    fn try_connect(
+        &mut self,
+        context: NodeContext,
+        commands: &mut SnarlCommands,
+        from: &OutPin,
+        to: &InPin,
+        incoming_type: &NodePortType,
+    ) -> miette::Result<bool> {
+        let old_outputs = self.outputs_count(context);
+        let changed = match generic_try_connect(
+            context,
+            to.id.input,
+            incoming_type,
+            &mut [GenericNodeFieldMut::Tuple(&mut self.id)],
+        )? {
+            ControlFlow::Continue(changed) => changed,
+            ControlFlow::Break(_) => return Ok(false),
+        };
+        if changed {
+            for idx in 0..old_outputs {
+                commands.push(SnarlCommand::DropOutputs {
+                    from: OutPinId {
+                        node: to.id.node,
+                        output: idx,
+                    },
+                });
+            }
+        }
+
+        self._default_try_connect(context, commands, from, to, incoming_type)
+    }
+
+    fn execute(
+        &self,
+        _context: NodeContext,
+        inputs: &[EValue],
+        outputs: &mut Vec<EValue>,
+        _variables: &mut ExecutionExtras,
+    ) -> miette::Result<ExecutionResult> {
+        let Some(id) = self.id else {
+            return Ok(ExecutionResult::Done);
+        };
+
+        let EValue::Tuple {
+            id: value_id,
+            values,
+        } = &inputs[0]
+        else {
+            bail!("expected tuple, got `{}`", inputs[0].ty().name());
+        };
+
+        if *value_id != id {
+            bail!("expected tuple `{}`, got `{}`", id, value_id);
+        }
+
+        outputs.extend(values.iter().cloned());
+
+        Ok(ExecutionResult::Done)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TupleDestructuringNodeFactory;
+
+impl NodeFactory for TupleDestructuringNodeFactory {
+    fn id(&self) -> Ustr {
+        "tuple_destructuring".into()
+    }
+
+    fn categories(&self) -> &'static [&'static str] {
+        &["objects"]
+    }
+
+    fn create(&self) -> Box<dyn Node> {
+        Box::new(TupleDestructuringNode::default())
+    }
+}
@@ -148,6 +148,15 @@ impl NodePortType {
                     .get_map(&id)
                     .map(|map| has_inline_value(registry, map.value_type))
                     .unwrap_or(true),
+                EDataType::Tuple { id } => registry
+                    .get_tuple(&id)
+                    .map(|tuple| {
+                        tuple
+                            .item_types
+                            .iter()
+                            .all(|ty| has_inline_value(registry, *ty))
+                    })
+                    .unwrap_or(true),
                 EDataType::Unknown => false,
             }
         }
@@ -0,0 +1,214 @@
+//! Structural validation of graph files
+//!
+//! Graphs can get out of sync with the rest of the project, for example when
+//! a type changes and a connection between two ports is no longer possible,
+//! or when a node group used by a subgraph node is deleted. These checks
+//! report such problems without modifying the graph.
+
+use crate::etype::EDataType;
+use crate::graph::node::groups::subgraph::SubgraphNode;
+use crate::graph::node::ports::NodePortType;
+use crate::graph::node::{Node, NodeContext};
+use crate::graph::region::region_graph::RegionGraph;
+use crate::graph::Graph;
+use crate::project::project_graph::{ProjectGraph, ProjectGraphs};
+use crate::registry::ETypesRegistry;
+use diagnostic::context::DiagnosticContextMut;
+use diagnostic::path::DiagnosticPathSegment;
+use diagnostic::rules::with_code;
+use egui_snarl::{InPinId, NodeId, OutPinId};
+use miette::{miette, Context};
+use utils::map::HashSet;
+use uuid::Uuid;
+
+/// Diagnostic path segment of the graph node
+pub fn node_key(id: NodeId) -> String {
+    format!("node {}", id.0)
+}
+
+/// Diagnostic field of the graph file holding evaluation errors
+///
+/// Evaluation errors are emitted by
+/// [`Project::evaluate_graphs`](crate::project::Project::evaluate_graphs)
+/// and are kept intact by the structural validation.
+pub const EVALUATION_FIELD: &str = "evaluation";
+
+/// Validates the structure of the graph, emitting problems under the given
+/// context
+pub fn validate_graph(
+    registry: &ETypesRegistry,
+    graphs: &ProjectGraphs,
+    graph: &ProjectGraph,
+    mut ctx: DiagnosticContextMut,
+) {
+    ctx.clear_downstream_except(DiagnosticPathSegment::Field(EVALUATION_FIELD.into()));
+
+    let graph_id = graph.id;
+    let graph = graph.graph();
+    let snarl = graph.snarl();
+    let region_graph = RegionGraph::build_regions_graph(snarl);
+    let context = NodeContext {
+        registry,
+        inputs: graph.inputs(),
+        outputs: graph.outputs(),
+        regions: graph.regions(),
+        region_graph: &region_graph,
+        graphs: Some(graphs),
+    };
+
+    if let Err(err) = region_graph.try_as_data() {
+        ctx.emit_error(miette!(
+            code = "graph/invalid-regions",
+            "invalid region structure: {}",
+            err
+        ));
+    }
+
+    for (id, node) in snarl.node_ids() {
+        let mut ctx = ctx.enter_map_key(node_key(id));
+
+        if let Some(subgraph) = node.downcast_ref::<SubgraphNode>() {
+            check_subgraph(graphs, graph_id, subgraph.graph_id, &mut ctx);
+        }
+
+        for input in 0..node.inputs_count(context) {
+            let pin = InPinId { node: id, input };
+            if snarl.in_pin(pin).remotes.is_empty() {
+                check_unconnected_input(context, &**node, input, &mut ctx);
+            }
+        }
+    }
+
+    for (out_pin, in_pin) in snarl.wires() {
+        if let Err(err) = check_connection(context, graph, out_pin, in_pin) {
+            ctx.enter_map_key(node_key(in_pin.node))
+                .enter_index(in_pin.input)
+                .emit_error(with_code(err, "graph/incompatible-connection"));
+        }
+    }
+}
+
+/// Checks that the connected ports are still compatible
+fn check_connection(
+    context: NodeContext,
+    graph: &Graph,
+    out_pin: OutPinId,
+    in_pin: InPinId,
+) -> miette::Result<()> {
+    let snarl = graph.snarl();
+    let from_node = &snarl[out_pin.node];
+    let to_node = &snarl[in_pin.node];
+
+    let from = from_node
+        .try_output(context, out_pin.output)
+        .context("connected output no longer exists")?;
+    let to = to_node
+        .try_input(context, in_pin.input)
+        .context("connected input no longer exists")?;
+
+    let compatible = if from.ty.is_based_on_target() {
+        from_node.can_output_to(
+            context,
+            &snarl.out_pin(out_pin),
+            &snarl.in_pin(in_pin),
+            &to.ty,
+        )?
+    } else {
+        NodePortType::compatible(context.registry, &from.ty, &to.ty)
+    };
+
+    if !compatible {
+        return Err(miette!(
+            "output `{}` of type `{}` is connected to input `{}` of incompatible type `{}`",
+            from.name,
+            from.ty.ty().title(context.registry),
+            to.name,
+            to.ty.ty().title(context.registry),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Warns about inputs that can't be edited inline and have no connection, so
+/// the node will always receive the default value
+fn check_unconnected_input(
+    context: NodeContext,
+    node: &dyn Node,
+    input: usize,
+    ctx: &mut DiagnosticContextMut,
+) {
+    let Ok(data) = node.try_input(context, input) else {
+        return;
+    };
+
+    let NodePortType::Specific(info) = &data.ty else {
+        return;
+    };
+
+    if matches!(info.ty(), EDataType::Const { .. } | EDataType::Unknown) {
+        return;
+    }
+
+    if node.has_inline_values().unwrap_or(false) && !data.ty.has_inline_value(context.registry) {
+        ctx.enter_index(input).emit_warning(miette!(
+            code = "graph/unconnected-input",
+            "required input `{}` is not connected",
+            data.name
+        ));
+    }
+}
+
+/// Checks that the node group used by the subgraph node exists, and doesn't
+/// use the graph containing the node
+fn check_subgraph(graphs: &ProjectGraphs, current: Uuid, id: Uuid, ctx: &mut DiagnosticContextMut) {
+    let Some(target) = graphs.graphs.get(&id) else {
+        ctx.emit_error(miette!(
+            code = "graph/missing-group",
+            "node group {} is not found",
+            id
+        ));
+        return;
+    };
+
+    if !target.is_node_group {
+        ctx.emit_error(miette!(
+            code = "graph/not-a-group",
+            "graph `{}` is not a node group",
+            target.display_name()
+        ));
+        return;
+    }
+
+    if id == current {
+        ctx.emit_error(miette!(
+            code = "graph/recursive-group",
+            "node group can't use itself"
+        ));
+    } else if uses_group(graphs, id, current, &mut HashSet::default()) {
+        ctx.emit_error(miette!(
+            code = "graph/recursive-group",
+            "node group `{}` uses this graph, forming a cycle",
+            target.display_name()
+        ));
+    }
+}
+
+/// Checks whether the graph `id` reaches the `target` node group through its
+/// subgraph nodes
+fn uses_group(graphs: &ProjectGraphs, id: Uuid, target: Uuid, visited: &mut HashSet<Uuid>) -> bool {
+    if !visited.insert(id) {
+        return false;
+    }
+
+    let Some(graph) = graphs.graphs.get(&id) else {
+        return false;
+    };
+
+    graph
+        .graph()
+        .snarl()
+        .nodes()
+        .filter_map(|node| node.downcast_ref::<SubgraphNode>())
+        .any(|node| node.graph_id == target || uses_group(graphs, node.graph_id, target, visited))
+}
@@ -6,6 +6,7 @@ pub use serde_json::Value as JsonValue;
 pub mod formatter;
 pub mod json_serde;
 pub mod repr;
+pub mod spans;
 
 pub fn json_kind(value: &JsonValue) -> &'static str {
     match value {
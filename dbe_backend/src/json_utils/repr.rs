@@ -1,8 +1,12 @@
 use crate::etype::eenum::pattern::EnumPattern;
+use crate::etype::EDataType;
 use crate::json_utils::repr::colors::ColorStringRepr;
 use crate::json_utils::JsonValue;
 use crate::registry::ETypesRegistry;
+use crate::validation::ids::numeric::NumericIds;
+use crate::validation::ids::string::StringIds;
 use crate::validation::Validator;
+use crate::value::id::ETypeId;
 use crate::value::EValue;
 use miette::{bail, miette};
 use parking_lot::RwLock;
@@ -12,8 +16,12 @@ use std::str::FromStr;
 use std::sync::{Arc, LazyLock};
 use ustr::{Ustr, UstrMap};
 
+pub mod base64;
+pub mod bitflags;
 mod colors;
 mod ids;
+pub mod localized;
+mod vec2;
 
 #[allow(clippy::wrong_self_convention)]
 pub trait JsonRepr: Send + Sync + Debug {
@@ -30,6 +38,34 @@ pub trait JsonRepr: Send + Sync + Debug {
     /// Converts from the consumable data to the serialized data representation
     fn into_repr(&self, registry: &ETypesRegistry, data: JsonValue) -> miette::Result<JsonValue>;
 
+    /// Same as [JsonRepr::from_repr], but also receives the ID of the object
+    /// being converted
+    ///
+    /// Reprs whose data shape depends on the object type (for example on its
+    /// generic arguments) should override this method
+    fn from_repr_of(
+        &self,
+        registry: &ETypesRegistry,
+        ident: ETypeId,
+        data: &mut JsonValue,
+        ignore_extra_fields: bool,
+    ) -> miette::Result<JsonValue> {
+        let _ = ident;
+        self.from_repr(registry, data, ignore_extra_fields)
+    }
+
+    /// Same as [JsonRepr::into_repr], but also receives the ID of the object
+    /// being converted
+    fn into_repr_of(
+        &self,
+        registry: &ETypesRegistry,
+        ident: ETypeId,
+        data: JsonValue,
+    ) -> miette::Result<JsonValue> {
+        let _ = ident;
+        self.into_repr(registry, data)
+    }
+
     /// Custom enum pattern for this repr. Leave none if this repr does not change the shape of the data
     fn enum_pat(&self) -> Option<EnumPattern>;
 
@@ -119,6 +155,26 @@ impl JsonRepr for Repr {
         self.0.into_repr(registry, data)
     }
 
+    fn from_repr_of(
+        &self,
+        registry: &ETypesRegistry,
+        ident: ETypeId,
+        data: &mut JsonValue,
+        ignore_extra_fields: bool,
+    ) -> miette::Result<JsonValue> {
+        self.0
+            .from_repr_of(registry, ident, data, ignore_extra_fields)
+    }
+
+    fn into_repr_of(
+        &self,
+        registry: &ETypesRegistry,
+        ident: ETypeId,
+        data: JsonValue,
+    ) -> miette::Result<JsonValue> {
+        self.0.into_repr_of(registry, ident, data)
+    }
+
     fn enum_pat(&self) -> Option<EnumPattern> {
         self.0.enum_pat()
     }
@@ -188,8 +244,17 @@ static REPR_REGISTRY: LazyLock<RwLock<UstrMap<Repr>>> = LazyLock::new(|| {
         let reprs: Vec<Arc<dyn JsonRepr>> = vec![
             Arc::new(ColorStringRepr::ARGB),
             Arc::new(ColorStringRepr::RGBA),
-            Arc::new(ids::numeric::Id),
-            Arc::new(ids::numeric::Ref),
+            Arc::new(ColorStringRepr::ARGB_BARE),
+            Arc::new(ColorStringRepr::RGBA_BARE),
+            Arc::new(vec2::Vec2Repr::ARRAY),
+            Arc::new(vec2::Vec2Repr::STRING),
+            Arc::new(base64::Base64Repr),
+            Arc::new(localized::Localized),
+            Arc::new(ids::Id::<NumericIds>::default()),
+            Arc::new(ids::Ref::<NumericIds>::default()),
+            Arc::new(ids::Id::<StringIds>::default()),
+            Arc::new(ids::Ref::<StringIds>::default()),
+            Arc::new(bitflags::BitflagsRepr),
         ];
 
         reprs
@@ -203,8 +268,57 @@ pub fn get_repr(name: &Ustr) -> Option<Repr> {
     REPR_REGISTRY.read().get(name).cloned()
 }
 
+/// Checks whenever both types are distinct structs with the same set of
+/// fields, so values of one can be reinterpreted as the other
+///
+/// This is used by reprs that only change the serialized form of a struct,
+/// allowing them to be connected with the plain struct form in graphs
+pub(crate) fn is_same_struct_shape(
+    registry: &ETypesRegistry,
+    this: &EItemInfo,
+    other: &EItemInfo,
+) -> bool {
+    let (EDataType::Object { ident: this }, EDataType::Object { ident: other }) =
+        (this.ty(), other.ty())
+    else {
+        return false;
+    };
+
+    if this == other {
+        return false;
+    }
+
+    let (Some(this), Some(other)) = (registry.get_struct(&this), registry.get_struct(&other))
+    else {
+        return false;
+    };
+
+    this.fields.len() == other.fields.len()
+        && this.fields.iter().all(|field| {
+            other
+                .fields
+                .iter()
+                .any(|f| f.name == field.name && f.ty.ty() == field.ty.ty())
+        })
+}
+
+/// Reinterprets the struct value as the struct of the given type
+///
+/// Types must be checked with [is_same_struct_shape] beforehand
+pub(crate) fn convert_struct_shape(target: &EItemInfo, value: EValue) -> miette::Result<EValue> {
+    let EDataType::Object { ident } = target.ty() else {
+        bail!("expected struct type, got `{}`", target.ty().name());
+    };
+
+    let EValue::Struct { fields, .. } = value else {
+        bail!("expected struct value, got {:?}", value);
+    };
+
+    Ok(EValue::Struct { ident, fields })
+}
+
 macro_rules! transparent {
-    ($field_name:literal, $cast_fn:path, $expected:literal, $pattern:expr) => {
+    ($field_name:literal, $cast_fn:path, $expected:expr, $pattern:expr) => {
         $crate::json_utils::repr::transparent_from!($field_name, $cast_fn, $expected);
         $crate::json_utils::repr::transparent_to!($field_name, $cast_fn, $expected);
 
@@ -215,7 +329,7 @@ macro_rules! transparent {
 }
 
 macro_rules! transparent_from {
-    ($field_name:literal, $cast_fn:path, $expected:literal) => {
+    ($field_name:literal, $cast_fn:path, $expected:expr) => {
         fn from_repr(
             &self,
             _registry: &$crate::registry::ETypesRegistry,
@@ -233,7 +347,7 @@ macro_rules! transparent_from {
     };
 }
 macro_rules! transparent_to {
-    ($field_name:literal, $cast_fn:path, $expected:literal) => {
+    ($field_name:literal, $cast_fn:path, $expected:expr) => {
         fn into_repr(
             &self,
             _registry: &$crate::registry::ETypesRegistry,
@@ -0,0 +1,159 @@
+//! Binary payloads stored as base64 strings
+//!
+//! The consumable form is a struct with a single `data` field holding a list
+//! of byte values
+
+use crate::etype::eenum::pattern::EnumPattern;
+use crate::etype::eitem::EItemInfo;
+use crate::json_utils::repr::{convert_struct_shape, is_same_struct_shape, JsonRepr};
+use crate::json_utils::{json_expected, JsonMap, JsonValue};
+use crate::registry::ETypesRegistry;
+use crate::validation::{validator_by_name, Validator};
+use crate::value::EValue;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use miette::{bail, IntoDiagnostic, WrapErr};
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+/// Name of the field holding the list of bytes
+pub const DATA_FIELD: &str = "data";
+
+/// Standard base64 alphabet, padding is written but is optional when reading
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+static BASE64_VALIDATOR: LazyLock<Validator> =
+    LazyLock::new(|| validator_by_name("base64".into()).unwrap());
+
+/// Checks that the number is a valid byte value
+pub fn as_byte(value: f64) -> Option<u8> {
+    (value.fract() == 0.0 && (0.0..=255.0).contains(&value)).then_some(value as u8)
+}
+
+#[derive(Debug)]
+pub struct Base64Repr;
+
+impl JsonRepr for Base64Repr {
+    fn id(&self) -> &'static str {
+        "base64"
+    }
+
+    fn from_repr(
+        &self,
+        _registry: &ETypesRegistry,
+        data: &mut JsonValue,
+        _ignore_extra_fields: bool,
+    ) -> miette::Result<JsonValue> {
+        let str = json_expected(data.as_str(), data, "base64 string")?;
+        let bytes = BASE64
+            .decode(str)
+            .into_diagnostic()
+            .context("failed to decode base64 data")?;
+
+        let mut fields = JsonMap::new();
+        fields.insert(DATA_FIELD.to_string(), bytes.into());
+
+        Ok(fields.into())
+    }
+
+    fn into_repr(&self, _registry: &ETypesRegistry, data: JsonValue) -> miette::Result<JsonValue> {
+        let obj = json_expected(data.as_object(), &data, "object")?;
+
+        let bytes = match obj.get(DATA_FIELD) {
+            None => vec![],
+            Some(items) => {
+                let items = json_expected(items.as_array(), items, "array")?;
+                items
+                    .iter()
+                    .map(|item| {
+                        let Some(byte) = item.as_f64().and_then(as_byte) else {
+                            bail!("expected a byte value, got `{}`", item);
+                        };
+                        Ok(byte)
+                    })
+                    .collect::<miette::Result<Vec<_>>>()?
+            }
+        };
+
+        Ok(BASE64.encode(bytes).into())
+    }
+
+    fn enum_pat(&self) -> Option<EnumPattern> {
+        Some(EnumPattern::String)
+    }
+
+    fn validators(&self) -> Cow<'static, [Validator]> {
+        Cow::Owned(vec![BASE64_VALIDATOR.clone()])
+    }
+
+    fn is_convertible_both_way(
+        &self,
+        registry: &ETypesRegistry,
+        this: &EItemInfo,
+        other: &EItemInfo,
+    ) -> bool {
+        is_same_struct_shape(registry, this, other)
+    }
+
+    fn convert_from(
+        &self,
+        _registry: &ETypesRegistry,
+        this: &EItemInfo,
+        _other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        convert_struct_shape(this, value)
+    }
+
+    fn convert_to(
+        &self,
+        _registry: &ETypesRegistry,
+        _this: &EItemInfo,
+        other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        convert_struct_shape(other, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BASE64;
+    use base64::Engine;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(b"", "")]
+    #[case(b"f", "Zg==")]
+    #[case(b"fo", "Zm8=")]
+    #[case(b"foo", "Zm9v")]
+    #[case(b"foob", "Zm9vYg==")]
+    #[case(b"fooba", "Zm9vYmE=")]
+    #[case(b"foobar", "Zm9vYmFy")]
+    #[case(&[0xFF, 0xFE, 0x00], "//4A")]
+    fn should_round_trip(#[case] data: &[u8], #[case] encoded: &str) {
+        assert_eq!(BASE64.encode(data), encoded);
+        assert_eq!(BASE64.decode(encoded).expect("Should decode"), data);
+    }
+
+    #[rstest]
+    #[case("Zg", b"f")]
+    #[case("Zm8", b"fo")]
+    fn should_decode_unpadded(#[case] encoded: &str, #[case] data: &[u8]) {
+        assert_eq!(BASE64.decode(encoded).expect("Should decode"), data);
+    }
+
+    #[rstest]
+    #[case("Z")]
+    #[case("Zm9v!")]
+    #[case("Zg=")]
+    #[case("Zm 9v")]
+    #[case("Zm9v====")]
+    fn should_fail_on_invalid(#[case] encoded: &str) {
+        assert!(BASE64.decode(encoded).is_err());
+    }
+}
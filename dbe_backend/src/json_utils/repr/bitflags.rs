@@ -0,0 +1,330 @@
+//! Flag sets stored as a single integer bitmask
+//!
+//! The repr is used by the `sys:bitflags` struct, which holds a list of flags
+//! of an enum with const variants. Each variant declares its bit value via
+//! the `bit` property. Bits that don't correspond to any variant are kept in
+//! the read-only `unknown_bits` field, so they survive a load/save round trip
+//! and can be reported by the validator.
+
+use crate::etype::econst::ETypeConst;
+use crate::etype::eenum::pattern::EnumPattern;
+use crate::etype::eenum::variant::EEnumVariantId;
+use crate::etype::eitem::EItemInfo;
+use crate::etype::property::default_properties::PROP_FIELD_BIT;
+use crate::etype::EDataType;
+use crate::json_utils::repr::JsonRepr;
+use crate::json_utils::{json_expected, JsonMap, JsonValue};
+use crate::registry::ETypesRegistry;
+use crate::validation::{validator_by_name, Validator, MAX_SAFE_INTEGER};
+use crate::value::id::{EListId, ETypeId};
+use crate::value::EValue;
+use miette::{bail, miette};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+use ustr::Ustr;
+
+/// Name of the field holding the list of set flags
+pub const FLAGS_FIELD: &str = "flags";
+/// Name of the field holding the set bits that don't match any flag
+pub const UNKNOWN_BITS_FIELD: &str = "unknown_bits";
+
+/// Flag variant of the bitflags enum
+#[derive(Debug, Copy, Clone)]
+pub struct FlagBit {
+    pub variant: EEnumVariantId,
+    pub value: ETypeConst,
+    pub bit: u64,
+}
+
+impl FlagBit {
+    pub fn as_value(&self) -> EValue {
+        EValue::Enum {
+            variant: self.variant,
+            data: Box::new(self.value.default_value()),
+        }
+    }
+}
+
+/// Returns the ID of the flags list of the given bitflags struct
+fn flags_list_id(registry: &ETypesRegistry, ident: ETypeId) -> miette::Result<EListId> {
+    let data = registry
+        .get_struct(&ident)
+        .ok_or_else(|| miette!("unknown object type or not a struct: `{}`", ident))?;
+
+    let field = data
+        .fields
+        .iter()
+        .find(|f| f.name == FLAGS_FIELD)
+        .ok_or_else(|| {
+            miette!(
+                "bitflags struct `{}` must have a `{}` field",
+                ident,
+                FLAGS_FIELD
+            )
+        })?;
+
+    let EDataType::List { id } = field.ty.ty() else {
+        bail!(
+            "field `{}` of bitflags struct `{}` must be a list of enum flags",
+            FLAGS_FIELD,
+            ident
+        );
+    };
+
+    Ok(id)
+}
+
+/// Returns the flags of the given bitflags struct, along with their bit values
+pub fn flag_bits(registry: &ETypesRegistry, ident: ETypeId) -> miette::Result<Vec<FlagBit>> {
+    let id = flags_list_id(registry, ident)?;
+    let list = registry
+        .get_list(&id)
+        .ok_or_else(|| miette!("!!INTERNAL ERROR!! unknown list `{}`", id))?;
+
+    let EDataType::Object { ident: enum_id } = list.value_type else {
+        bail!(
+            "bitflags only support lists of enums, got `{}`",
+            list.value_type.name()
+        );
+    };
+
+    let enum_data = registry
+        .get_enum(&enum_id)
+        .ok_or_else(|| miette!("bitflags only support lists of enums, got `{}`", enum_id))?;
+
+    let mut flags = Vec::with_capacity(enum_data.variants().len());
+    let mut used_bits = 0u64;
+    for (variant, id) in enum_data.variants_with_ids() {
+        let EDataType::Const { value } = variant.data.ty() else {
+            bail!(
+                "bitflags only support enums with const variants, found variant `{}` with type `{}`",
+                variant.name(),
+                variant.data.ty().name()
+            );
+        };
+
+        let Some(bit) = PROP_FIELD_BIT.try_get(variant.data.extra_properties()) else {
+            bail!(
+                "variant `{}` of enum `{}` has no `bit` value",
+                variant.name(),
+                enum_id
+            );
+        };
+
+        let Some(bit) = bit_value(bit.0) else {
+            bail!(
+                "bit value of variant `{}` must be a power of two, but got {}",
+                variant.name(),
+                bit
+            );
+        };
+
+        if used_bits & bit != 0 {
+            bail!(
+                "bit value {} of variant `{}` is already used by another variant",
+                bit,
+                variant.name()
+            );
+        }
+        used_bits |= bit;
+
+        flags.push(FlagBit {
+            variant: *id,
+            value,
+            bit,
+        });
+    }
+
+    Ok(flags)
+}
+
+fn bit_value(value: f64) -> Option<u64> {
+    if value.fract() != 0.0 || !(1.0..=MAX_SAFE_INTEGER).contains(&value) {
+        return None;
+    }
+    let value = value as u64;
+    value.is_power_of_two().then_some(value)
+}
+
+fn json_bits(json: &JsonValue) -> miette::Result<u64> {
+    let value = json_expected(json.as_f64(), json, "integer bitmask")?;
+    if value.fract() != 0.0 || !(0.0..=MAX_SAFE_INTEGER).contains(&value) {
+        bail!("bitmask must be a non-negative integer, but got {}", value);
+    }
+    Ok(value as u64)
+}
+
+/// Converts the bitmask into the value of the given bitflags struct
+pub fn bits_to_value(
+    registry: &ETypesRegistry,
+    ident: ETypeId,
+    bits: u64,
+) -> miette::Result<EValue> {
+    let id = flags_list_id(registry, ident)?;
+    let flags = flag_bits(registry, ident)?;
+
+    let known = flags.iter().fold(0, |acc, f| acc | f.bit);
+    let values = flags
+        .iter()
+        .filter(|f| bits & f.bit != 0)
+        .map(FlagBit::as_value)
+        .collect();
+
+    let mut fields = BTreeMap::new();
+    fields.insert(Ustr::from(FLAGS_FIELD), EValue::List { id, values });
+    fields.insert(
+        Ustr::from(UNKNOWN_BITS_FIELD),
+        ((bits & !known) as f64).into(),
+    );
+
+    Ok(EValue::Struct { ident, fields })
+}
+
+/// Converts the value of a bitflags struct into the bitmask
+pub fn value_to_bits(registry: &ETypesRegistry, value: &EValue) -> miette::Result<u64> {
+    let EValue::Struct { ident, fields } = value else {
+        bail!("expected bitflags struct, got {:?}", value);
+    };
+
+    let flags = flag_bits(registry, *ident)?;
+
+    let mut bits = match fields.get(&Ustr::from(UNKNOWN_BITS_FIELD)) {
+        None => 0,
+        Some(value) => value.try_as_number()?.0 as u64,
+    };
+
+    if let Some(value) = fields.get(&Ustr::from(FLAGS_FIELD)) {
+        let EValue::List { values, .. } = value else {
+            bail!("field `{}` must be a list, got {:?}", FLAGS_FIELD, value);
+        };
+
+        for value in values {
+            let flag = flags
+                .iter()
+                .find(|f| &f.as_value() == value)
+                .ok_or_else(|| miette!("value {} is not a flag of `{}`", value, ident))?;
+            bits |= flag.bit;
+        }
+    }
+
+    Ok(bits)
+}
+
+static BITFLAGS_VALIDATOR: LazyLock<Validator> =
+    LazyLock::new(|| validator_by_name("bitflags".into()).unwrap());
+
+#[derive(Debug)]
+pub struct BitflagsRepr;
+
+impl JsonRepr for BitflagsRepr {
+    fn id(&self) -> &'static str {
+        "bitflags"
+    }
+
+    fn from_repr(
+        &self,
+        _registry: &ETypesRegistry,
+        _data: &mut JsonValue,
+        _ignore_extra_fields: bool,
+    ) -> miette::Result<JsonValue> {
+        bail!("`bitflags` repr requires the object type")
+    }
+
+    fn into_repr(&self, _registry: &ETypesRegistry, _data: JsonValue) -> miette::Result<JsonValue> {
+        bail!("`bitflags` repr requires the object type")
+    }
+
+    fn from_repr_of(
+        &self,
+        registry: &ETypesRegistry,
+        ident: ETypeId,
+        data: &mut JsonValue,
+        _ignore_extra_fields: bool,
+    ) -> miette::Result<JsonValue> {
+        let EValue::Struct { fields, .. } = bits_to_value(registry, ident, json_bits(data)?)?
+        else {
+            bail!("!!INTERNAL ERROR!! bitflags value is not a struct");
+        };
+
+        let mut obj = JsonMap::new();
+        for (name, value) in fields {
+            obj.insert(name.to_string(), value.write_json(registry)?);
+        }
+
+        Ok(obj.into())
+    }
+
+    fn into_repr_of(
+        &self,
+        registry: &ETypesRegistry,
+        ident: ETypeId,
+        data: JsonValue,
+    ) -> miette::Result<JsonValue> {
+        let obj = json_expected(data.as_object(), &data, "object")?;
+
+        let mut fields = BTreeMap::new();
+        if let Some(mut items) = obj.get(FLAGS_FIELD).cloned() {
+            let id = flags_list_id(registry, ident)?;
+            fields.insert(
+                Ustr::from(FLAGS_FIELD),
+                EDataType::List { id }.parse_json(registry, &mut items, false)?,
+            );
+        }
+        if let Some(unknown) = obj.get(UNKNOWN_BITS_FIELD) {
+            fields.insert(
+                Ustr::from(UNKNOWN_BITS_FIELD),
+                (json_bits(unknown)? as f64).into(),
+            );
+        }
+
+        Ok(value_to_bits(registry, &EValue::Struct { ident, fields })?.into())
+    }
+
+    fn enum_pat(&self) -> Option<EnumPattern> {
+        Some(EnumPattern::Number)
+    }
+
+    fn validators(&self) -> Cow<'static, [Validator]> {
+        Cow::Owned(vec![BITFLAGS_VALIDATOR.clone()])
+    }
+
+    fn is_convertible_both_way(
+        &self,
+        _registry: &ETypesRegistry,
+        _this: &EItemInfo,
+        other: &EItemInfo,
+    ) -> bool {
+        // bitflags can be converted to and from their integer bitmask
+        other.ty().is_number()
+    }
+
+    fn convert_from(
+        &self,
+        registry: &ETypesRegistry,
+        this: &EItemInfo,
+        _other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        let EDataType::Object { ident } = this.ty() else {
+            bail!("bitflags can only be converted to object types");
+        };
+
+        let bits = value.try_as_number()?.0;
+        if bits.fract() != 0.0 || !(0.0..=MAX_SAFE_INTEGER).contains(&bits) {
+            bail!("bitmask must be a non-negative integer, but got {}", bits);
+        }
+
+        bits_to_value(registry, ident, bits as u64)
+    }
+
+    fn convert_to(
+        &self,
+        registry: &ETypesRegistry,
+        _this: &EItemInfo,
+        _other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        Ok((value_to_bits(registry, &value)? as f64).into())
+    }
+}
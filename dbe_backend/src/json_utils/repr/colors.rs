@@ -1,7 +1,12 @@
 use crate::etype::eenum::pattern::EnumPattern;
-use crate::json_utils::repr::JsonRepr;
+use crate::etype::eitem::EItemInfo;
+use crate::json_utils::repr::{convert_struct_shape, is_same_struct_shape, JsonRepr};
 use crate::json_utils::{json_expected, JsonValue};
 use crate::registry::ETypesRegistry;
+use crate::validation::{validator_by_name, Validator};
+use crate::value::EValue;
+use std::borrow::Cow;
+use std::sync::LazyLock;
 use utils::color_format::ecolor::Rgba;
 use utils::color_format::ColorFormat;
 
@@ -9,20 +14,39 @@ use utils::color_format::ColorFormat;
 pub struct ColorStringRepr {
     id: &'static str,
     alpha_repr: ColorFormat,
+    /// Whenever the `#` prefix is omitted when writing the color
+    bare: bool,
 }
 
 impl ColorStringRepr {
     pub const ARGB: ColorStringRepr = ColorStringRepr {
         id: "argb",
         alpha_repr: ColorFormat::argb(),
+        bare: false,
     };
 
     pub const RGBA: ColorStringRepr = ColorStringRepr {
         id: "rgba",
         alpha_repr: ColorFormat::argb(),
+        bare: false,
+    };
+
+    pub const ARGB_BARE: ColorStringRepr = ColorStringRepr {
+        id: "argb/bare",
+        alpha_repr: ColorFormat::argb(),
+        bare: true,
+    };
+
+    pub const RGBA_BARE: ColorStringRepr = ColorStringRepr {
+        id: "rgba/bare",
+        alpha_repr: ColorFormat::rgba(),
+        bare: true,
     };
 }
 
+static COLOR_VALIDATOR: LazyLock<Validator> =
+    LazyLock::new(|| validator_by_name("color".into()).unwrap());
+
 impl JsonRepr for ColorStringRepr {
     fn id(&self) -> &'static str {
         self.id
@@ -67,10 +91,51 @@ impl JsonRepr for ColorStringRepr {
                 .format(Rgba::from_rgba_unmultiplied(r, g, b, a))
         };
 
-        Ok(str.into())
+        if self.bare {
+            Ok(str.trim_start_matches('#').into())
+        } else {
+            Ok(str.into())
+        }
     }
 
     fn enum_pat(&self) -> Option<EnumPattern> {
         Some(EnumPattern::String)
     }
+
+    fn validators(&self) -> Cow<'static, [Validator]> {
+        if self.bare {
+            Cow::Owned(vec![COLOR_VALIDATOR.clone()])
+        } else {
+            Cow::Borrowed(&[])
+        }
+    }
+
+    fn is_convertible_both_way(
+        &self,
+        registry: &ETypesRegistry,
+        this: &EItemInfo,
+        other: &EItemInfo,
+    ) -> bool {
+        is_same_struct_shape(registry, this, other)
+    }
+
+    fn convert_from(
+        &self,
+        _registry: &ETypesRegistry,
+        this: &EItemInfo,
+        _other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        convert_struct_shape(this, value)
+    }
+
+    fn convert_to(
+        &self,
+        _registry: &ETypesRegistry,
+        _this: &EItemInfo,
+        other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        convert_struct_shape(other, value)
+    }
 }
@@ -1 +1,184 @@
-pub mod numeric;
+use crate::etype::eenum::pattern::EnumPattern;
+use crate::etype::eitem::EItemInfo;
+use crate::etype::EDataType;
+use crate::json_utils::repr::{transparent, JsonRepr};
+use crate::json_utils::JsonValue;
+use crate::registry::ETypesRegistry;
+use crate::validation::ids::registry::IdRegistry;
+use crate::validation::ids::IdKind;
+use crate::validation::{validator_by_name, Validator};
+use crate::value::id::ETypeId;
+use crate::value::{estruct, EValue};
+use miette::{bail, miette};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+/// Repr of the ID definitions of the [IdKind]
+#[derive(Debug, Default)]
+pub struct Id<K>(PhantomData<K>);
+
+fn validators(name: &'static str) -> Cow<'static, [Validator]> {
+    Cow::Owned(validator_by_name(name.into()).into_iter().collect())
+}
+
+impl<K: IdKind> JsonRepr for Id<K> {
+    fn id(&self) -> &'static str {
+        K::ID
+    }
+
+    transparent!("id", K::id_json, K::EXPECTED, K::enum_pattern());
+
+    fn validators(&self) -> Cow<'static, [Validator]> {
+        validators(K::ID)
+    }
+
+    fn is_convertible_to(
+        &self,
+        registry: &ETypesRegistry,
+        this: &EItemInfo,
+        other: &EItemInfo,
+    ) -> bool {
+        // IDs can be converted to the plain ID type or to the references
+
+        if K::is_id_type(&other.ty()) {
+            return true;
+        }
+
+        if other.repr(registry).is_none_or(|r| r.id() != K::REF) {
+            return false;
+        };
+
+        generics_compatible::<K>(registry, this, other)
+    }
+
+    fn convert_to(
+        &self,
+        registry: &ETypesRegistry,
+        _this: &EItemInfo,
+        other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        // IDs can be converted to the plain ID type or to the references
+
+        if K::is_id_type(&other.ty()) {
+            return Ok(K::to_value(value_id::<K>(&value)?));
+        }
+
+        if other.repr(registry).is_none_or(|r| r.id() != K::REF) {
+            bail!(
+                "{} can only be converted to {} or {}",
+                K::ID,
+                K::EXPECTED,
+                K::REF
+            );
+        };
+
+        let EDataType::Object { ident } = other.ty() else {
+            bail!(
+                "{} can only be converted to {} or object types",
+                K::ID,
+                K::EXPECTED
+            );
+        };
+
+        value_to_obj::<K>(ident, value)
+    }
+}
+
+/// Repr of the ID references of the [IdKind]
+#[derive(Debug, Default)]
+pub struct Ref<K>(PhantomData<K>);
+
+impl<K: IdKind> JsonRepr for Ref<K> {
+    fn id(&self) -> &'static str {
+        K::REF
+    }
+
+    transparent!("id", K::id_json, K::EXPECTED, K::enum_pattern());
+
+    fn validators(&self) -> Cow<'static, [Validator]> {
+        validators(K::REF)
+    }
+
+    fn is_convertible_from(
+        &self,
+        registry: &ETypesRegistry,
+        this: &EItemInfo,
+        other: &EItemInfo,
+    ) -> bool {
+        // references can be converted from IDs
+
+        if other.repr(registry).is_none_or(|r| r.id() != K::ID) {
+            return false;
+        };
+
+        generics_compatible::<K>(registry, this, other)
+    }
+
+    fn is_convertible_to(
+        &self,
+        _registry: &ETypesRegistry,
+        _this: &EItemInfo,
+        other: &EItemInfo,
+    ) -> bool {
+        // references can be converted to the plain ID type
+
+        K::is_id_type(&other.ty())
+    }
+
+    fn convert_from(
+        &self,
+        _registry: &ETypesRegistry,
+        this: &EItemInfo,
+        _other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        // references can be converted from IDs
+
+        let EDataType::Object { ident } = this.ty() else {
+            bail!("{} can only be applied to object types", K::REF);
+        };
+
+        value_to_obj::<K>(ident, value)
+    }
+
+    fn convert_to(
+        &self,
+        _registry: &ETypesRegistry,
+        _this: &EItemInfo,
+        other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        // references can be converted to the plain ID type
+
+        if !K::is_id_type(&other.ty()) {
+            bail!("{} can only be converted to {} types", K::REF, K::EXPECTED);
+        };
+
+        Ok(K::to_value(value_id::<K>(&value)?))
+    }
+}
+
+fn generics_compatible<K: IdKind>(reg: &ETypesRegistry, a: &EItemInfo, b: &EItemInfo) -> bool {
+    let a = a.ty();
+    let b = b.ty();
+
+    IdRegistry::<K>::of(reg)
+        .is_id_assignable_ty(a, b)
+        .expect("types should be objects at this point")
+}
+
+fn value_id<K: IdKind>(value: &EValue) -> miette::Result<K::Id> {
+    let id = value.try_get_field("id")?;
+    K::from_value(id).ok_or_else(|| miette!("`id` field must be a {}", K::EXPECTED))
+}
+
+fn value_to_obj<K: IdKind>(ident: ETypeId, value: EValue) -> miette::Result<EValue> {
+    let id = if let EValue::Struct { .. } = &value {
+        value_id::<K>(&value)?
+    } else {
+        bail!("conversion not supported")
+    };
+
+    Ok(estruct!(ident {"id": K::to_value(id)}))
+}
@@ -0,0 +1,71 @@
+use crate::etype::eenum::pattern::EnumPattern;
+use crate::etype::eitem::EItemInfo;
+use crate::etype::EDataType;
+use crate::json_utils::repr::{transparent, JsonRepr};
+use crate::json_utils::JsonValue;
+use crate::registry::ETypesRegistry;
+use crate::validation::{validator_by_name, Validator};
+use crate::value::{estruct, EValue};
+use miette::bail;
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+/// Name of the field holding the translation key
+pub const KEY_FIELD: &str = "key";
+
+static LOCALIZED_VALIDATOR: LazyLock<Validator> =
+    LazyLock::new(|| validator_by_name("localized".into()).unwrap());
+
+#[derive(Debug)]
+pub struct Localized;
+
+impl JsonRepr for Localized {
+    fn id(&self) -> &'static str {
+        "localized"
+    }
+
+    transparent!("key", JsonValue::as_str, "string", EnumPattern::String);
+
+    fn validators(&self) -> Cow<'static, [Validator]> {
+        Cow::Owned(vec![LOCALIZED_VALIDATOR.clone()])
+    }
+
+    fn is_convertible_both_way(
+        &self,
+        _registry: &ETypesRegistry,
+        _this: &EItemInfo,
+        other: &EItemInfo,
+    ) -> bool {
+        // localized strings can be converted to and from their keys
+        other.ty().is_string()
+    }
+
+    fn convert_from(
+        &self,
+        _registry: &ETypesRegistry,
+        this: &EItemInfo,
+        _other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        let EDataType::Object { ident } = this.ty() else {
+            bail!("localized repr can only be applied to object types");
+        };
+
+        let key = value.try_as_string()?.clone();
+        Ok(estruct!(ident { KEY_FIELD: key }))
+    }
+
+    fn convert_to(
+        &self,
+        _registry: &ETypesRegistry,
+        _this: &EItemInfo,
+        _other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        Ok(value
+            .try_get_field(KEY_FIELD)
+            .and_then(|f| f.try_as_string())?
+            .clone()
+            .into())
+    }
+}
@@ -0,0 +1,146 @@
+use crate::etype::eenum::pattern::EnumPattern;
+use crate::etype::eitem::EItemInfo;
+use crate::json_utils::repr::{convert_struct_shape, is_same_struct_shape, JsonRepr};
+use crate::json_utils::{json_expected, JsonMap, JsonValue};
+use crate::registry::ETypesRegistry;
+use crate::validation::{validator_by_name, Validator};
+use crate::value::EValue;
+use miette::{bail, miette, Context, IntoDiagnostic};
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Vec2Format {
+    /// `[x, y]`
+    Array,
+    /// `"x,y"`
+    String,
+}
+
+#[derive(Debug)]
+pub struct Vec2Repr {
+    id: &'static str,
+    format: Vec2Format,
+}
+
+impl Vec2Repr {
+    pub const ARRAY: Vec2Repr = Vec2Repr {
+        id: "vec2/array",
+        format: Vec2Format::Array,
+    };
+
+    pub const STRING: Vec2Repr = Vec2Repr {
+        id: "vec2/string",
+        format: Vec2Format::String,
+    };
+}
+
+static VEC2_VALIDATOR: LazyLock<Validator> =
+    LazyLock::new(|| validator_by_name("vec2".into()).unwrap());
+
+fn parse_component(str: &str, name: &str) -> miette::Result<f64> {
+    str.trim()
+        .parse::<f64>()
+        .into_diagnostic()
+        .with_context(|| format!("failed to parse `{}` component: `{}`", name, str))
+}
+
+fn get_component(obj: &JsonMap, name: &str) -> miette::Result<f64> {
+    match obj.get(name) {
+        None => Ok(0.0),
+        Some(value) => value
+            .as_f64()
+            .ok_or_else(|| miette!("`{}` field must be a number", name)),
+    }
+}
+
+impl JsonRepr for Vec2Repr {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn from_repr(
+        &self,
+        _registry: &ETypesRegistry,
+        data: &mut JsonValue,
+        _ignore_extra_fields: bool,
+    ) -> miette::Result<JsonValue> {
+        let (x, y) = match self.format {
+            Vec2Format::Array => {
+                let arr = json_expected(data.as_array(), data, "array")?;
+                let [x, y] = arr.as_slice() else {
+                    bail!("expected an array of 2 numbers, got {} items", arr.len());
+                };
+                (
+                    json_expected(x.as_f64(), x, "number")?,
+                    json_expected(y.as_f64(), y, "number")?,
+                )
+            }
+            Vec2Format::String => {
+                let str = json_expected(data.as_str(), data, "vector string")?;
+                let Some((x, y)) = str.split_once(',') else {
+                    bail!("expected a vector in `x,y` format, got `{}`", str);
+                };
+                (parse_component(x, "x")?, parse_component(y, "y")?)
+            }
+        };
+
+        let mut fields = JsonMap::new();
+        fields.insert("x".to_string(), x.into());
+        fields.insert("y".to_string(), y.into());
+
+        Ok(fields.into())
+    }
+
+    fn into_repr(&self, _registry: &ETypesRegistry, data: JsonValue) -> miette::Result<JsonValue> {
+        let obj = json_expected(data.as_object(), &data, "object")?;
+
+        let x = get_component(obj, "x")?;
+        let y = get_component(obj, "y")?;
+
+        Ok(match self.format {
+            Vec2Format::Array => vec![x, y].into(),
+            Vec2Format::String => format!("{},{}", x, y).into(),
+        })
+    }
+
+    fn enum_pat(&self) -> Option<EnumPattern> {
+        Some(match self.format {
+            Vec2Format::Array => EnumPattern::List,
+            Vec2Format::String => EnumPattern::String,
+        })
+    }
+
+    fn validators(&self) -> Cow<'static, [Validator]> {
+        Cow::Owned(vec![VEC2_VALIDATOR.clone()])
+    }
+
+    fn is_convertible_both_way(
+        &self,
+        registry: &ETypesRegistry,
+        this: &EItemInfo,
+        other: &EItemInfo,
+    ) -> bool {
+        is_same_struct_shape(registry, this, other)
+    }
+
+    fn convert_from(
+        &self,
+        _registry: &ETypesRegistry,
+        this: &EItemInfo,
+        _other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        convert_struct_shape(this, value)
+    }
+
+    fn convert_to(
+        &self,
+        _registry: &ETypesRegistry,
+        _this: &EItemInfo,
+        other: &EItemInfo,
+        value: EValue,
+    ) -> miette::Result<EValue> {
+        convert_struct_shape(other, value)
+    }
+}
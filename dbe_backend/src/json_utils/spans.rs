@@ -0,0 +1,306 @@
+//! Source spans of the JSON values
+//!
+//! Item files are parsed via serde, which doesn't keep source locations, so
+//! spans are collected by a separate lightweight pass over the source. The
+//! scanner accepts the JSON5 syntax used by the item files: comments,
+//! unquoted keys, single-quoted strings and trailing commas.
+
+use diagnostic::path::{DiagnosticPath, DiagnosticPathSegment};
+use std::ops::Range;
+
+/// Span of the JSON value and its children
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonSpan {
+    /// Byte range of the value in the source
+    pub range: Range<usize>,
+    pub children: JsonSpanChildren,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonSpanChildren {
+    None,
+    Object(Vec<(String, JsonSpan)>),
+    Array(Vec<JsonSpan>),
+}
+
+impl JsonSpan {
+    /// Parses the spans of the JSON source, returns `None` if the source is
+    /// malformed
+    pub fn parse(source: &str) -> Option<Self> {
+        let mut scanner = Scanner {
+            src: source.as_bytes(),
+            pos: 0,
+        };
+        let span = scanner.value()?;
+        scanner.skip_whitespace();
+        (scanner.pos == scanner.src.len()).then_some(span)
+    }
+
+    /// Returns the span of the object field
+    pub fn get(&self, key: &str) -> Option<&JsonSpan> {
+        let JsonSpanChildren::Object(entries) = &self.children else {
+            return None;
+        };
+        entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Finds the span of the value at the diagnostic path
+    ///
+    /// Diagnostic paths follow the structure of the values rather than of
+    /// the JSON, so segments without a matching JSON value, such as internally
+    /// tagged enum variants or inline fields, are skipped. Values that are
+    /// not present in the JSON resolve to their closest present parent.
+    pub fn locate(&self, path: &DiagnosticPath) -> &JsonSpan {
+        let mut span = self;
+        for segment in path.iter() {
+            let next = match (segment, &span.children) {
+                (
+                    DiagnosticPathSegment::Field(key)
+                    | DiagnosticPathSegment::Variant(key)
+                    | DiagnosticPathSegment::MapKey(key),
+                    _,
+                ) => span.get(key),
+                (DiagnosticPathSegment::Index(idx), JsonSpanChildren::Array(items)) => {
+                    items.get(*idx)
+                }
+                (DiagnosticPathSegment::Index(idx), JsonSpanChildren::Object(entries)) => {
+                    entries.get(*idx).map(|(_, v)| v)
+                }
+                (DiagnosticPathSegment::Index(_), JsonSpanChildren::None) => None,
+            };
+            if let Some(next) = next {
+                span = next;
+            }
+        }
+        span
+    }
+}
+
+struct Scanner<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                b' ' | b'\t' | b'\n' | b'\r' => self.pos += 1,
+                b'/' if self.src.get(self.pos + 1) == Some(&b'/') => {
+                    while self.peek().is_some_and(|c| c != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                b'/' if self.src.get(self.pos + 1) == Some(&b'*') => {
+                    self.pos += 2;
+                    while self.pos < self.src.len() && !self.src[self.pos..].starts_with(b"*/") {
+                        self.pos += 1;
+                    }
+                    self.pos = (self.pos + 2).min(self.src.len());
+                }
+                // Non-breaking space and BOM
+                0xC2 if self.src.get(self.pos + 1) == Some(&0xA0) => self.pos += 2,
+                0xEF if self.src[self.pos..].starts_with(&[0xEF, 0xBB, 0xBF]) => self.pos += 3,
+                _ => return,
+            }
+        }
+    }
+
+    fn value(&mut self) -> Option<JsonSpan> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let children = match self.peek()? {
+            b'{' => self.object()?,
+            b'[' => self.array()?,
+            b'"' | b'\'' => {
+                self.string()?;
+                JsonSpanChildren::None
+            }
+            _ => {
+                while self.peek().is_some_and(|c| {
+                    !matches!(c, b',' | b'}' | b']' | b'/') && !c.is_ascii_whitespace()
+                }) {
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return None;
+                }
+                JsonSpanChildren::None
+            }
+        };
+        Some(JsonSpan {
+            range: start..self.pos,
+            children,
+        })
+    }
+
+    fn object(&mut self) -> Option<JsonSpanChildren> {
+        self.pos += 1;
+        let mut entries = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.peek()? == b'}' {
+                self.pos += 1;
+                return Some(JsonSpanChildren::Object(entries));
+            }
+            let key = self.key()?;
+            self.skip_whitespace();
+            if self.peek()? != b':' {
+                return None;
+            }
+            self.pos += 1;
+            entries.push((key, self.value()?));
+            if !self.separator(b'}')? {
+                return Some(JsonSpanChildren::Object(entries));
+            }
+        }
+    }
+
+    fn array(&mut self) -> Option<JsonSpanChildren> {
+        self.pos += 1;
+        let mut items = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.peek()? == b']' {
+                self.pos += 1;
+                return Some(JsonSpanChildren::Array(items));
+            }
+            items.push(self.value()?);
+            if !self.separator(b']')? {
+                return Some(JsonSpanChildren::Array(items));
+            }
+        }
+    }
+
+    /// Consumes the separator after a collection element. Returns `false` if
+    /// the collection was closed
+    fn separator(&mut self, close: u8) -> Option<bool> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b',' => {
+                self.pos += 1;
+                Some(true)
+            }
+            c if c == close => {
+                self.pos += 1;
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+
+    fn key(&mut self) -> Option<String> {
+        if matches!(self.peek()?, b'"' | b'\'') {
+            return self.string();
+        }
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, b'_' | b'$') || c >= 0x80)
+        {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&self.src[start..self.pos]).into_owned())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let quote = self.peek()?;
+        self.pos += 1;
+        let mut bytes = vec![];
+        loop {
+            let c = self.peek()?;
+            self.pos += 1;
+            match c {
+                c if c == quote => break,
+                b'\\' => {
+                    let escaped = self.peek()?;
+                    self.pos += 1;
+                    let ch = match escaped {
+                        b'n' => '\n',
+                        b't' => '\t',
+                        b'r' => '\r',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'0' => '\0',
+                        b'u' => {
+                            let hex = self.src.get(self.pos..self.pos + 4)?;
+                            self.pos += 4;
+                            let code =
+                                u32::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?;
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        // Line continuation
+                        b'\n' => continue,
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                            continue;
+                        }
+                        other => {
+                            bytes.push(other);
+                            continue;
+                        }
+                    };
+                    bytes.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                c => bytes.push(c),
+            }
+        }
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonSpan;
+    use diagnostic::path::{DiagnosticPath, DiagnosticPathSegment};
+    use rstest::rstest;
+
+    const SOURCE: &str = r#"{
+  // comment
+  "ItemType": 1,
+  Id: 5,
+  'Name': "Ship \"A\"",
+  "Stats": { "Hp": 10, "Weapons": [1, 2, 3], },
+}"#;
+
+    #[rstest]
+    #[case(&[], SOURCE)]
+    #[case(&["Id"], "5")]
+    #[case(&["Name"], r#""Ship \"A\"""#)]
+    #[case(&["<Component>", "Stats", "Hp"], "10")]
+    #[case(&["Stats", "Weapons", "[1]"], "2")]
+    #[case(&["Stats", "Missing"], r#"{ "Hp": 10, "Weapons": [1, 2, 3], }"#)]
+    fn should_locate_path(#[case] path: &[&str], #[case] expected: &str) {
+        let spans = JsonSpan::parse(SOURCE).unwrap();
+        let mut diagnostic_path = DiagnosticPath::empty();
+        for segment in path {
+            let segment = if let Some(idx) = segment.strip_prefix('[') {
+                DiagnosticPathSegment::Index(idx.trim_end_matches(']').parse().unwrap())
+            } else if let Some(variant) = segment.strip_prefix('<') {
+                DiagnosticPathSegment::Variant(variant.trim_end_matches('>').to_string().into())
+            } else {
+                DiagnosticPathSegment::Field(segment.to_string().into())
+            };
+            diagnostic_path.push(segment);
+        }
+        let range = spans.locate(&diagnostic_path).range.clone();
+        assert_eq!(&SOURCE[range], expected);
+    }
+
+    #[rstest]
+    #[case("{")]
+    #[case(r#"{"a" 1}"#)]
+    #[case("[1, 2")]
+    fn should_fail_malformed(#[case] source: &str) {
+        assert!(JsonSpan::parse(source).is_none());
+    }
+}
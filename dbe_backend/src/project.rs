@@ -1,38 +1,68 @@
+use crate::etype::computed::{check_computed, update_computed};
+use crate::etype::estruct::{collect_unknown_fields, with_default_values, UnknownFields};
+use crate::etype::migration::{
+    check_migrations, collect_applied, migrate_prefab, AppliedMigration,
+};
 use crate::etype::EDataType;
 use crate::graph::execution::GraphExecutionContext;
+use crate::graph::validation::{node_key, validate_graph, EVALUATION_FIELD};
 use crate::json_utils::formatter::DBEJsonFormatter;
-use crate::json_utils::{json_kind, JsonValue};
+use crate::json_utils::{json_kind, JsonMap, JsonValue};
 use crate::m_try;
+use crate::project::assets::ProjectAssets;
+use crate::project::baseline::{Baseline, BASELINE_FILE};
 use crate::project::docs::{Docs, DocsFile};
 use crate::project::io::{FilesystemIO, ProjectIO};
+use crate::project::localization::{locale_file_info, Localization, LocalizationConfig};
 use crate::project::module::{find_dbemodule_path, DbeModule};
+use crate::project::prefab::{diff_prefab, resolve_prefab, Prefab, EXTENDS_FIELD};
 use crate::project::project_graph::{ProjectGraph, ProjectGraphs};
+use crate::project::renumber::{renumber_ids, IdRenumbering, RenumberReport};
+use crate::project::rules::{DiagnosticRules, DiagnosticsConfig};
+use crate::project::sarif::{sarif_report, SARIF_FILE};
+use crate::project::side_effects::mappings::STORAGE_ID;
 use crate::project::side_effects::SideEffectsContext;
+use crate::project::sources::ItemSources;
 use crate::project::undo::{UndoHistory, UndoSettings};
 use crate::registry::ETypesRegistry;
+use crate::validation::fixes::{value_at_path_mut, FixEdit};
+use crate::validation::ids::numeric::NumericIDRegistry;
 use crate::validation::{clear_validation_cache, validate};
 use crate::value::id::editor_id::Namespace;
 use crate::value::id::ETypeId;
 use crate::value::EValue;
 use camino::{Utf8Path, Utf8PathBuf};
-use diagnostic::context::DiagnosticContext;
-use diagnostic::diagnostic::DiagnosticLevel;
+use diagnostic::context::{DiagnosticContext, DiagnosticContextMut};
+use diagnostic::diagnostic::{DiagnosticFix, DiagnosticLevel};
+use diagnostic::rules::{report_code, with_code};
+use itertools::Itertools;
 use miette::{bail, miette, Context, IntoDiagnostic, Report};
 use rayon::iter::ParallelDrainFull;
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
-use std::collections::{hash_map, BTreeMap};
+use std::collections::{hash_map, BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Instant;
 use tracing::info;
+use ustr::Ustr;
 use utils::map::{HashMap, HashSet};
 use uuid::Uuid;
 
+pub mod assets;
+pub mod baseline;
 pub mod docs;
 pub mod io;
+pub mod lints;
+pub mod localization;
 pub mod module;
+pub mod prefab;
 pub mod project_graph;
+pub mod renumber;
+pub mod rules;
+pub mod sarif;
 pub mod side_effects;
+pub mod sources;
 pub mod undo;
 
 pub const EXTENSION_TYPE: &str = "kdl";
@@ -54,6 +84,11 @@ pub struct Project<IO> {
     pub docs: Docs,
     /// Diagnostic context
     pub diagnostics: DiagnosticContext,
+    /// Rule levels and suppressions applied to the diagnostics
+    pub diagnostic_rules: Arc<DiagnosticRules>,
+    /// Sources of the item files as of the last load or save, used to locate
+    /// the diagnostics
+    pub item_sources: Arc<ItemSources>,
     /// Files present in the project
     pub files: BTreeMap<Utf8PathBuf, ProjectFile>,
     /// Loaded modules
@@ -62,6 +97,19 @@ pub struct Project<IO> {
     /// Files that should be deleted on save
     pub to_delete: HashSet<Utf8PathBuf>,
     pub history: UndoHistory,
+    /// Data migrations that were applied to the files when loading the
+    /// project, until the files are saved
+    pub migrations: BTreeMap<Utf8PathBuf, Vec<AppliedMigration>>,
+    /// Prefab bases and overridden fields of the files that extend other files
+    pub prefabs: BTreeMap<Utf8PathBuf, Prefab>,
+    /// Paths of the KDL type files
+    pub type_paths: HashMap<ETypeId, Utf8PathBuf>,
+    /// Unsaved JSON of the edited files that no longer match their types
+    /// after the types were reloaded
+    ///
+    /// Later reloads parse the pending JSON again instead of reading the
+    /// file, and the project can't be saved until it parses
+    pub pending_json: BTreeMap<Utf8PathBuf, (JsonValue, Option<EDataType>)>,
     /// Root folder of the project
     pub root: Utf8PathBuf,
     pub io: IO,
@@ -103,19 +151,23 @@ impl ProjectFile {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectConfig {
     #[serde(rename = "types")]
     pub types_config: TypesConfig,
     #[serde(default = "default_emitted_dir")]
     pub emitted_dir: Utf8PathBuf,
+    #[serde(default)]
+    pub localization: LocalizationConfig,
+    #[serde(default)]
+    pub diagnostics: DiagnosticsConfig,
 }
 
 fn default_emitted_dir() -> Utf8PathBuf {
     Utf8PathBuf::from("emitted")
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TypesConfig {
     pub import: ETypeId,
 }
@@ -152,15 +204,11 @@ impl<IO: ProjectIO> Project<IO> {
         let mut types_jsons = HashMap::<Utf8PathBuf, JsonValue>::default();
         let mut graphs = HashMap::<Utf8PathBuf, JsonValue>::default();
         let mut docs = Docs::Docs(Default::default());
+        let mut type_paths = HashMap::<ETypeId, Utf8PathBuf>::default();
+        let mut localization = Localization::default();
         let mut modules = HashMap::<Utf8PathBuf, DbeModule>::default();
-
-        fn utf8str(path: &Utf8Path, data: Vec<u8>) -> miette::Result<String> {
-            String::from_utf8(data).into_diagnostic().with_context(|| {
-                format!(
-                    "failed to parse content of a file `{path}`. Are you sure it's UTF-8 encoded?"
-                )
-            })
-        }
+        let mut baseline = None;
+        let item_sources = Arc::new(ItemSources::default());
 
         fn get_module<'a, IO: ProjectIO>(
             modules: &'a mut HashMap<Utf8PathBuf, DbeModule>,
@@ -202,6 +250,18 @@ impl<IO: ProjectIO> Project<IO> {
             let module_path = find_dbemodule_path(path);
 
             m_try(|| {
+                if path.as_str() == BASELINE_FILE {
+                    baseline = Some(Baseline::from_json(&utf8str(path, io.read_file(path)?)?)?);
+                    return Ok(());
+                }
+
+                if let Some((locale, format)) = locale_file_info(path) {
+                    let content = utf8str(path, io.read_file(path)?)?;
+                    return localization
+                        .add_file(path.to_path_buf(), locale, format, &content)
+                        .context("failed to load translation file");
+                }
+
                 match ext.as_str() {
                     EXTENSION_TYPE => {
                         let Some(module) = module_path else {
@@ -213,9 +273,11 @@ impl<IO: ProjectIO> Project<IO> {
                             .context("failed to generate type identifier")?;
                         let value = utf8str(path, io.read_file(path)?)?;
                         registry_items.insert(id, value);
+                        type_paths.insert(id, path.to_path_buf());
                     }
                     "json5" | "json" => {
-                        let data = serde_json5::from_str(&utf8str(path, io.read_file(path)?)?)
+                        let source = utf8str(path, io.read_file(path)?)?;
+                        let data = serde_json5::from_str(&source)
                             .into_diagnostic()
                             .context("failed to deserialize JSON")?;
                         if let Some(module_path) = module_path {
@@ -225,17 +287,19 @@ impl<IO: ProjectIO> Project<IO> {
                             types_jsons.insert(path.to_path_buf(), data);
                         } else {
                             import_jsons.insert(path.to_path_buf(), (data, None));
+                            item_sources.insert(path, source);
                         }
                     }
                     EXTENSION_VALUE => {
                         if module_path.is_some() {
                             bail!("value files are not allowed inside dbemodule");
                         }
-                        let data: MiscJson =
-                            serde_json5::from_str(&utf8str(path, io.read_file(path)?)?)
-                                .into_diagnostic()
-                                .context("failed to deserialize dbefile JSON")?;
+                        let source = utf8str(path, io.read_file(path)?)?;
+                        let data: MiscJson = serde_json5::from_str(&source)
+                            .into_diagnostic()
+                            .context("failed to deserialize dbefile JSON")?;
                         import_jsons.insert(path.to_path_buf(), (data.value, Some(data.ty)));
+                        item_sources.insert(path, source);
                     }
                     EXTENSION_GRAPH => {
                         if let Some(module_path) = module_path {
@@ -306,22 +370,46 @@ impl<IO: ProjectIO> Project<IO> {
             }
         }
 
+        localization
+            .init_active_locale(&config.localization)
+            .context("project config is invalid")?;
+
+        let diagnostic_rules = Arc::new(
+            DiagnosticRules::new(&config.diagnostics).context("project config is invalid")?,
+        );
+        if let Some(baseline) = baseline {
+            diagnostic_rules.set_baseline(baseline);
+        }
+        let mut diagnostics = DiagnosticContext::default();
+        diagnostics.set_filter(Some(diagnostic_rules.clone()));
+        diagnostics.set_sources(Some(item_sources.clone()));
+
         let registry = ETypesRegistry::from_raws(registry_items, config)?;
+        *Localization::of(&registry).write() = localization;
 
         let mut project = Self {
             registry,
             docs,
-            diagnostics: Default::default(),
+            diagnostics,
+            diagnostic_rules,
+            item_sources,
             files: Default::default(),
             modules: project_modules,
             graphs: Default::default(),
             to_delete: Default::default(),
             history: UndoHistory::new(UndoSettings::default()),
+            migrations: Default::default(),
+            prefabs: Default::default(),
+            type_paths: Default::default(),
+            pending_json: Default::default(),
             root,
             io,
         };
 
-        project.validate_config()?;
+        project.docs.add_kdl_docs(&project.registry, &type_paths);
+        project.type_paths = type_paths;
+        Self::validate_config(&project.registry)?;
+        project.update_assets()?;
 
         for (path, json) in types_jsons {
             let JsonValue::Object(obj) = json else {
@@ -338,37 +426,74 @@ impl<IO: ProjectIO> Project<IO> {
             }
         }
 
-        for (path, (json, ty)) in import_jsons {
-            let item = match project
-                .deserialize_json(json, ty)
-                .with_context(|| format!("failed to deserialize JSON at `{}`", path))
-            {
-                Ok(data) => {
-                    validate(
-                        &project.registry,
-                        project.diagnostics.enter(path.as_str()),
-                        None,
-                        &data,
-                    )?;
-                    if project.io.file_exists(generated_marker_path(&path))? {
-                        ProjectFile::GeneratedValue(data)
-                    } else {
-                        ProjectFile::Value(data)
-                    }
+        // Graphs are loaded before the values, so node group migrations can run
+        for (path, json) in graphs {
+            let file = project.load_graph(&path, json)?;
+            project.files.insert(path, file);
+        }
+
+        // Suppressions are taken out before prefabs are merged, so they are
+        // not inherited from the bases
+        let mut load_errors = HashMap::default();
+        for (path, (json, _)) in &mut import_jsons {
+            if let Err(err) = project.diagnostic_rules.load_suppressions(path, json) {
+                load_errors.insert(path.clone(), err);
+            }
+        }
+
+        // Prefabs are merged before parsing, so migrations and validation
+        // operate on the effective values
+        let resolved = import_jsons
+            .iter()
+            .filter_map(|(path, (json, _))| {
+                let result = resolve_prefab(
+                    path,
+                    json,
+                    |path| import_jsons.get(path).map(|(json, _)| json),
+                    |path, json, base| {
+                        let ty = import_jsons.get(path).and_then(|(_, ty)| *ty);
+                        migrate_prefab_file(&project.registry, ty, json, base)
+                    },
+                );
+                result.transpose().map(|result| (path.clone(), result))
+            })
+            .collect_vec();
+
+        for (path, result) in resolved {
+            match result {
+                Ok((prefab, merged)) => {
+                    import_jsons.get_mut(&path).expect("file should exist").0 = merged;
+                    project.prefabs.insert(path, prefab);
                 }
-                Err(err) => ProjectFile::BadValue(err),
-            };
-            project.files.insert(path, item);
+                Err(err) => {
+                    load_errors.entry(path).or_insert(err);
+                }
+            }
         }
 
-        for (path, mut json) in graphs {
-            let graph = ProjectGraph::parse_json(&project.registry, &mut json)
-                .with_context(|| format!("failed to deserialize Graph at `{}`", path))?;
-            let file = project
-                .graphs
-                .add_graph(path.clone(), graph)
-                .with_context(|| format!("failed to process Graph at `{}`", path))?;
-            project.files.insert(path, file);
+        for (path, (json, ty)) in import_jsons {
+            let data = match load_errors.remove(&path) {
+                Some(err) => Err(err),
+                None => project.parse_item(&path, json, ty),
+            };
+            let item =
+                match data.with_context(|| format!("failed to deserialize JSON at `{}`", path)) {
+                    Ok(data) => {
+                        validate(
+                            &project.registry,
+                            project.diagnostics.enter(path.as_str()),
+                            None,
+                            &data,
+                        )?;
+                        if project.io.file_exists(generated_marker_path(&path))? {
+                            ProjectFile::GeneratedValue(data)
+                        } else {
+                            ProjectFile::Value(data)
+                        }
+                    }
+                    Err(err) => ProjectFile::BadValue(err),
+                };
+            project.files.insert(path, item);
         }
 
         // Validate again after all files are loaded
@@ -377,8 +502,29 @@ impl<IO: ProjectIO> Project<IO> {
         Ok(project)
     }
 
+    /// Checks that the file can be deleted without leaving undefined
+    /// references to the IDs defined in it
+    pub fn check_deletion(&self, path: impl AsRef<Utf8Path>) -> miette::Result<()> {
+        let usages =
+            NumericIDRegistry::of(&self.registry).references_to_file(path.as_ref().as_str());
+        if usages.is_empty() {
+            return Ok(());
+        }
+        bail!(
+            code = "ids/referenced-file",
+            help = "update or remove the references before deleting the file",
+            "IDs defined in `{}` are referenced by:\n\t{}",
+            path.as_ref(),
+            usages
+                .iter()
+                .map(|usage| format!("ID {} at `{}`", usage.id, usage.location))
+                .join("\n\t")
+        );
+    }
+
     pub fn delete_file(&mut self, path: impl AsRef<Utf8Path>) -> miette::Result<()> {
         let path = path.as_ref();
+        self.pending_json.remove(path);
         if let Some(removed) = self.files.remove(path) {
             if removed.is_generated() {
                 self.to_delete.insert(generated_marker_path(path));
@@ -389,6 +535,11 @@ impl<IO: ProjectIO> Project<IO> {
         Ok(())
     }
 
+    /// Evaluates all non-group graphs and runs their side effects
+    ///
+    /// Evaluation errors are emitted as diagnostics of the failing node and
+    /// don't stop evaluation of other graphs. Side effects of failed graphs
+    /// are discarded
     pub fn evaluate_graphs(&mut self) -> miette::Result<()> {
         let mut side_effects = side_effects::SideEffects::new();
         let mut generated = vec![];
@@ -399,41 +550,65 @@ impl<IO: ProjectIO> Project<IO> {
 
         for (path, file) in &self.files {
             side_effects.clear_transient_storage();
-            m_try(|| {
-                if file.is_generated() {
-                    generated.push(path.clone());
-                    return Ok(());
-                }
-                let ProjectFile::Graph(id) = file else {
-                    return Ok(());
-                };
+            if file.is_generated() {
+                generated.push(path.clone());
+                continue;
+            }
+            let ProjectFile::Graph(id) = file else {
+                continue;
+            };
 
-                let Some(graph) = self.graphs.graphs.get(id) else {
-                    bail!("graph {:?} at path {} is not found", id, path);
-                };
+            let mut diagnostics = self.diagnostics.enter(path.as_str());
+            let mut diagnostics = diagnostics.enter_field(EVALUATION_FIELD);
+            diagnostics.clear_downstream();
 
-                if graph.is_node_group {
-                    return Ok(());
-                }
-                let out_values = &mut None;
-                let mut ctx = GraphExecutionContext::from_graph(
-                    graph.graph(),
-                    &self.registry,
-                    Some(&self.graphs),
-                    SideEffectsContext::new(&mut side_effects, path.clone(), &self.files),
-                    graph.is_node_group,
-                    &[],
-                    out_values,
-                );
-                ctx.full_eval(true)?;
-                drop(ctx);
+            let Some(graph) = self.graphs.graphs.get(id) else {
+                diagnostics.emit_error(miette!(
+                    code = "graph/missing",
+                    "graph {:?} at path {} is not found",
+                    id,
+                    path
+                ));
+                continue;
+            };
+
+            if graph.is_node_group {
+                continue;
+            }
+
+            let effects_before = side_effects.len();
+            let out_values = &mut None;
+            let mut ctx = GraphExecutionContext::from_graph(
+                graph.graph(),
+                &self.registry,
+                Some(&self.graphs),
+                SideEffectsContext::new(&mut side_effects, path.clone(), &self.files),
+                graph.is_node_group,
+                &[],
+                out_values,
+            );
+            let result = ctx.full_eval(true);
+            let failed_node = ctx.failed_node();
+            drop(ctx);
+
+            let result = result.and_then(|_| {
                 if out_values.is_some() {
                     bail!("graph {:?} at path {} has outputs", id, path);
                 }
-
                 Ok(())
-            })
-            .with_context(|| format!("failed to evaluate graph at `{}`", path))?
+            });
+
+            if let Err(err) = result {
+                side_effects.truncate(effects_before);
+                let err = with_code(
+                    err.wrap_err(format!("failed to evaluate graph at `{}`", path)),
+                    "graph/evaluation",
+                );
+                match failed_node {
+                    Some(node) => diagnostics.enter_map_key(node_key(node)).emit_error(err),
+                    None => diagnostics.emit_error(err),
+                }
+            }
         }
 
         for path in generated {
@@ -448,6 +623,8 @@ impl<IO: ProjectIO> Project<IO> {
     /// Clean and validate the project, evaluating all graphs and running side effects
     pub fn clean_validate(&mut self) -> miette::Result<()> {
         self.diagnostics.diagnostics.clear();
+        self.diagnostic_rules.clear_baseline_matches();
+        self.update_assets()?;
         let graph_eval_time = Instant::now();
         self.evaluate_graphs()?;
         let graph_eval_time = graph_eval_time.elapsed().as_secs_f32();
@@ -457,6 +634,8 @@ impl<IO: ProjectIO> Project<IO> {
         self.validate_all()?;
         self.validate_all()?;
         let validate_time = validate_time.elapsed().as_secs_f32();
+        lints::run_lints(self)?;
+        self.report_fixed_baseline();
         info!(
             graph_eval_time,
             validate_time, "Project built and validated successfully"
@@ -464,6 +643,235 @@ impl<IO: ProjectIO> Project<IO> {
         Ok(())
     }
 
+    /// Reports the baseline entries that are no longer present in the project
+    fn report_fixed_baseline(&mut self) {
+        let fixed = self.diagnostic_rules.unmatched_baseline();
+        let mut ctx = self.diagnostics.enter(BASELINE_FILE);
+        ctx.clear_downstream();
+        for entry in fixed {
+            let location = if entry.path.is_empty() {
+                entry.file
+            } else {
+                format!("{}@{}", entry.file, entry.path)
+            };
+            ctx.emit_warning(miette!(
+                code = "baseline/fixed",
+                help = "update the baseline to remove fixed entries",
+                "baselined diagnostic `{}` at `{}` is no longer reported",
+                entry.rule.as_deref().unwrap_or("<no rule>"),
+                location
+            ));
+        }
+    }
+
+    /// Snapshots the current diagnostics into the baseline file, so only
+    /// diagnostics that appear later are reported
+    pub fn update_baseline(&mut self) -> miette::Result<()> {
+        self.diagnostic_rules.set_baseline(Baseline::default());
+        self.clean_validate()?;
+
+        let baseline = Baseline::snapshot(&self.diagnostics);
+        self.io
+            .write_file(BASELINE_FILE, baseline.to_json()?.as_bytes())
+            .context("failed to write diagnostics baseline")?;
+        self.io.flush()?;
+        info!(
+            entries = baseline.entries.len(),
+            "Diagnostics baseline updated"
+        );
+
+        // Everything that is currently reported is now in the baseline
+        self.diagnostic_rules.set_baseline(baseline);
+        self.diagnostics.diagnostics.clear();
+        Ok(())
+    }
+
+    /// Applies the fix of a diagnostic emitted for the file, and revalidates
+    /// the project
+    ///
+    /// Changes to the values are recorded in the undo history
+    pub fn apply_fix(&mut self, file: &Utf8Path, fix: &DiagnosticFix) -> miette::Result<()> {
+        self.apply_fix_edit(file, fix)?;
+        self.revalidate()
+    }
+
+    /// Applies the first fix of every diagnostic with the given rule code, or
+    /// of all diagnostics if no code is given. Returns the number of applied
+    /// fixes
+    ///
+    /// Fixes assigning the same unique value, such as the same newly
+    /// allocated ID, are deferred until the project is revalidated, so they
+    /// can pick another value. All fixes are recorded in the undo history as
+    /// a single change set
+    pub fn apply_all_fixes(&mut self, code: Option<&str>) -> miette::Result<usize> {
+        self.history.begin_group(&self.files, &self.graphs)?;
+        let result = self.apply_fix_rounds(code);
+        self.history.end_group(&self.files, &self.graphs)?;
+        result
+    }
+
+    fn apply_fix_rounds(&mut self, code: Option<&str>) -> miette::Result<usize> {
+        const MAX_ROUNDS: usize = 100;
+
+        let mut applied = 0;
+        for _ in 0..MAX_ROUNDS {
+            let fixes = self
+                .diagnostics
+                .diagnostics
+                .iter()
+                .flat_map(|(file, paths)| {
+                    paths
+                        .values()
+                        .flatten()
+                        .filter(move |d| code.is_none() || report_code(&d.info).as_deref() == code)
+                        .filter_map(move |d| {
+                            Some((Utf8PathBuf::from(file), d.fixes.first()?.clone()))
+                        })
+                })
+                .collect_vec();
+
+            if fixes.is_empty() {
+                break;
+            }
+
+            let mut assigned = vec![];
+            let mut deferred = false;
+            for (file, fix) in fixes {
+                if let Some(FixEdit::SetValue {
+                    value,
+                    unique: true,
+                }) = fix.edit::<FixEdit>()
+                {
+                    if assigned.contains(value) {
+                        deferred = true;
+                        continue;
+                    }
+                    assigned.push(value.clone());
+                }
+                self.apply_fix_edit(&file, &fix)?;
+                applied += 1;
+            }
+
+            self.revalidate()?;
+            if !deferred {
+                break;
+            }
+        }
+
+        Ok(applied)
+    }
+
+    fn apply_fix_edit(&mut self, file: &Utf8Path, fix: &DiagnosticFix) -> miette::Result<()> {
+        m_try(|| {
+            let Some(edit) = fix.edit::<FixEdit>() else {
+                bail!("fix can't be applied to project files");
+            };
+
+            match edit {
+                FixEdit::SetValue { value, .. } => {
+                    if !self.io.is_file_writable(file)? {
+                        bail!("file is read-only");
+                    }
+                    self.history
+                        .ensure_file_state(&self.files, &self.graphs, file)?;
+                    let Some(ProjectFile::Value(root)) = self.files.get_mut(file) else {
+                        bail!("file is not an editable value");
+                    };
+                    let target = value_at_path_mut(root, &fix.path)
+                        .ok_or_else(|| miette!("value at path `{}` is not found", fix.path))?;
+                    *target = value.clone();
+                    update_computed(&self.registry, Some(&self.graphs), root);
+                    self.history
+                        .check_file(&self.files, &self.graphs, file, true)?;
+                }
+                FixEdit::DropUnknownFields => {
+                    if !self.io.is_file_writable(file)? {
+                        bail!("file is read-only");
+                    }
+                    // Unknown fields are not kept in the parsed value, and
+                    // are left out when the file is saved. The fix is offered
+                    // for every struct with unknown fields, and the first
+                    // applied one drops them all
+                    let unknown_fields = UnknownFields::of(&self.registry);
+                    let previous = unknown_fields.write().remove(file);
+                    if let Some(previous) = previous {
+                        self.history.record_unknown_fields(
+                            unknown_fields,
+                            file.to_path_buf(),
+                            previous,
+                        );
+                    }
+                }
+            }
+
+            Ok(())
+        })
+        .with_context(|| format!("failed to apply fix `{}` in `{}`", fix.title, file))
+    }
+
+    /// Returns the paths of the `sys:mappings/storage` files
+    pub fn mapping_files(&self) -> impl Iterator<Item = &Utf8PathBuf> {
+        self.files.iter().filter_map(|(path, file)| match file {
+            ProjectFile::Value(EValue::Struct { ident, .. }) if *ident == *STORAGE_ID => Some(path),
+            _ => None,
+        })
+    }
+
+    /// Reports the changes [Project::renumber_ids] would make, without
+    /// changing the project
+    pub fn preview_renumber_ids(
+        &mut self,
+        category: Ustr,
+        renumbering: &IdRenumbering,
+        mappings: &BTreeSet<Utf8PathBuf>,
+    ) -> miette::Result<RenumberReport> {
+        renumber_ids(self, category, renumbering, mappings, false)
+            .with_context(|| format!("failed to renumber IDs of category `{}`", category))
+    }
+
+    /// Moves the numeric IDs of the category to new IDs, rewriting all
+    /// references pointing at them, as well as the provided mappings files
+    ///
+    /// Changes are recorded in the undo history as a single change set
+    pub fn renumber_ids(
+        &mut self,
+        category: Ustr,
+        renumbering: &IdRenumbering,
+        mappings: &BTreeSet<Utf8PathBuf>,
+    ) -> miette::Result<RenumberReport> {
+        let report = renumber_ids(self, category, renumbering, mappings, true)
+            .with_context(|| format!("failed to renumber IDs of category `{}`", category))?;
+        info!(
+            ids = report.ids.len(),
+            files = report.files.len(),
+            "Renumbered IDs of category `{}`",
+            category
+        );
+        Ok(report)
+    }
+
+    /// Revalidates all files from scratch, without evaluating the graphs
+    fn revalidate(&mut self) -> miette::Result<()> {
+        clear_validation_cache(&self.registry);
+        // Double validate to ensure that validation cache is populated
+        self.validate_all()?;
+        self.validate_all()
+    }
+
+    /// Writes the current diagnostics into the SARIF file in the project root
+    pub fn export_sarif(&mut self) -> miette::Result<()> {
+        let report = sarif_report(&self.diagnostics, &self.item_sources);
+        let data = serde_json::to_string_pretty(&report)
+            .into_diagnostic()
+            .context("failed to serialize SARIF report")?;
+        self.io
+            .write_file(SARIF_FILE, data.as_bytes())
+            .context("failed to write SARIF report")?;
+        self.io.flush()?;
+        info!(path = SARIF_FILE, "Diagnostics exported");
+        Ok(())
+    }
+
     pub fn validate_all(&mut self) -> miette::Result<()> {
         for (path, file) in &self.files {
             match file {
@@ -474,22 +882,364 @@ impl<IO: ProjectIO> Project<IO> {
                         None,
                         file,
                     )?;
+                    check_computed(
+                        &self.registry,
+                        Some(&self.graphs),
+                        self.diagnostics.enter(path.as_str()),
+                        file,
+                    );
+                    if let Some(unknown) = UnknownFields::of(&self.registry).read().get(path) {
+                        report_unknown_fields(self.diagnostics.enter(path.as_str()), unknown);
+                    }
+                    if let Some(applied) = self.migrations.get(path) {
+                        report_migrations(self.diagnostics.enter(path.as_str()), applied);
+                    }
                 }
                 ProjectFile::BadValue(_) => {
                     let mut ctx = self.diagnostics.enter(path.as_str());
                     ctx.clear_downstream();
-                    ctx
-                        .emit_error(miette!("failed to deserialize JSON at `{path}`, open the file in editor for details"));
+                    ctx.emit_error(miette!(
+                        code = "file/bad-json",
+                        "failed to deserialize JSON at `{path}`, open the file in editor for details"
+                    ));
                 }
-                &ProjectFile::Graph(_) => {
-                    // TODO: validate graph
+                ProjectFile::Graph(id) => {
+                    let Some(graph) = self.graphs.graphs.get(id) else {
+                        continue;
+                    };
+                    validate_graph(
+                        &self.registry,
+                        &self.graphs,
+                        graph,
+                        self.diagnostics.enter(path.as_str()),
+                    );
                 }
             }
         }
+
+        let localization = Localization::of(&self.registry);
+        for (path, unused) in localization.read().unused_keys() {
+            let mut ctx = self.diagnostics.enter(path.as_str());
+            ctx.clear_downstream();
+            for key in unused {
+                ctx.enter_map_key(key.to_string()).emit_warning(miette!(
+                    code = "localization/unused-key",
+                    "translation key `{}` is not used",
+                    key
+                ));
+            }
+        }
+
+        let mut docs_issues = BTreeMap::<_, Vec<_>>::new();
+        for (location, report) in self.docs.check_types(&self.registry) {
+            docs_issues.entry(location).or_default().push(report);
+        }
+        for (location, reports) in docs_issues {
+            let mut ctx = self.diagnostics.enter(location.as_str());
+            ctx.clear_downstream();
+            for report in reports {
+                ctx.emit_warning(with_code(report, "docs/invalid"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds the types registry from the type files, and re-parses all
+    /// loaded items and graphs against the new types
+    ///
+    /// Items are re-parsed from their in-memory state, so unsaved edits are
+    /// kept. Items that no longer match their types turn into
+    /// [ProjectFile::BadValue], with their JSON kept in
+    /// [Project::pending_json], and graphs that fail to load keep their
+    /// previous state. Undo history is converted to the new types
+    pub fn reload_types(&mut self) -> miette::Result<()> {
+        let mut registry_items = HashMap::default();
+        let mut type_paths = HashMap::<ETypeId, Utf8PathBuf>::default();
+        for path in self.io.list_files()? {
+            let Ok(path) = Utf8PathBuf::from_path_buf(path) else {
+                continue;
+            };
+            let path = path.strip_prefix(&self.root).unwrap_or(&path);
+            if !path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(EXTENSION_TYPE))
+            {
+                continue;
+            }
+
+            m_try(|| {
+                let module_path = find_dbemodule_path(path)
+                    .ok_or_else(|| miette!("Type is outside of dbemodule"))?;
+                let module = self
+                    .modules
+                    .values()
+                    .find(|module| module.path.as_path() == module_path)
+                    .ok_or_else(|| {
+                        miette!(
+                            "module at `{}` is not loaded, reopen the project",
+                            module_path
+                        )
+                    })?;
+                let id = ETypeId::from_path(module, path)
+                    .context("failed to generate type identifier")?;
+                let value = utf8str(path, self.io.read_file(path)?)?;
+                registry_items.insert(id, value);
+                type_paths.insert(id, path.to_path_buf());
+                Ok(())
+            })
+            .with_context(|| format!("failed to load file at `{}`", path))?;
+        }
+
+        let registry = self
+            .registry
+            .reload(registry_items)
+            .context("failed to reload types")?;
+        Self::validate_config(&registry)?;
+
+        // Everything is serialized with the old types before the registry is
+        // replaced
+        let mut graphs = vec![];
+        for graph in self.graphs.graphs.values() {
+            graphs.push((graph.id, graph.write_json(&self.registry)?));
+        }
+
+        let mut items = BTreeMap::new();
+        let mut bad_graphs = vec![];
+        // Files with unsaved edits, whose JSON is kept if it fails to parse
+        let mut edited = BTreeSet::new();
+        for (path, file) in &self.files {
+            let is_dbe = path_has_suffix(path, EXTENSION_VALUE);
+            let json = match file {
+                ProjectFile::BadValue(_) if path_has_suffix(path, EXTENSION_GRAPH) => {
+                    bad_graphs.push(path.clone());
+                    continue;
+                }
+                ProjectFile::Value(value) => {
+                    edited.insert(path.clone());
+                    with_default_values(|| self.serialize_json(value))
+                        .map(|json| (json, is_dbe.then(|| value.ty())))
+                }
+                ProjectFile::GeneratedValue(value) => {
+                    with_default_values(|| self.serialize_json(value))
+                        .map(|json| (json, is_dbe.then(|| value.ty())))
+                }
+                ProjectFile::BadValue(_) => match self.pending_json.get(path) {
+                    Some(pending) => {
+                        edited.insert(path.clone());
+                        Ok(pending.clone())
+                    }
+                    None => self.read_item(path),
+                },
+                ProjectFile::Graph(_) => continue,
+            };
+            items.insert(path.clone(), (json, file.is_generated()));
+        }
+
+        let old_registry = &self.registry;
+        self.history.convert_snapshots(
+            |value| {
+                let mut json = with_default_values(|| value.write_json(old_registry))?;
+                value.ty().parse_json(&registry, &mut json, false)
+            },
+            |graph| {
+                let mut json = graph.write_json(old_registry)?;
+                ProjectGraph::parse_json(&registry, &mut json)
+            },
+        );
+
+        self.registry = registry;
+        self.docs.remove_kdl_docs();
+        self.docs.add_kdl_docs(&self.registry, &type_paths);
+        self.type_paths = type_paths;
+
+        let mut graph_errors = vec![];
+        for (id, mut json) in graphs {
+            match ProjectGraph::parse_json(&self.registry, &mut json) {
+                Ok(graph) => {
+                    self.graphs.graphs.insert(id, graph);
+                }
+                Err(err) => graph_errors.push((id, err)),
+            }
+        }
+
+        for path in bad_graphs {
+            let file = m_try(|| {
+                let json = serde_json5::from_str(&utf8str(&path, self.io.read_file(&path)?)?)
+                    .into_diagnostic()
+                    .context("failed to deserialize graph JSON")?;
+                self.load_graph(&path, json)
+            })
+            .unwrap_or_else(ProjectFile::BadValue);
+            self.files.insert(path, file);
+        }
+
+        // Prefab bases are already merged into the in-memory values, so only
+        // the files re-read from disk need resolving
+        let resolved = items
+            .iter()
+            .filter_map(|(path, (json, _))| {
+                let (json, _) = json.as_ref().ok()?;
+                let item =
+                    |path: &Utf8Path| items.get(path).and_then(|(json, _)| json.as_ref().ok());
+                let result = resolve_prefab(
+                    path,
+                    json,
+                    |path| item(path).map(|(json, _)| json),
+                    |path, json, base| {
+                        let ty = item(path).and_then(|(_, ty)| *ty);
+                        migrate_prefab_file(&self.registry, ty, json, base)
+                    },
+                );
+                result.transpose().map(|result| (path.clone(), result))
+            })
+            .collect_vec();
+        for (path, result) in resolved {
+            let (json, _) = items.get_mut(&path).expect("file should exist");
+            match result {
+                Ok((prefab, merged)) => {
+                    if let Ok((json, _)) = json {
+                        *json = merged;
+                    }
+                    self.prefabs.insert(path, prefab);
+                }
+                Err(err) => *json = Err(err),
+            }
+        }
+
+        for (path, (json, generated)) in items {
+            let pending = json
+                .as_ref()
+                .ok()
+                .filter(|_| edited.contains(&path))
+                .cloned();
+            let data = json.and_then(|(json, ty)| self.parse_item(&path, json, ty));
+            let file =
+                match data.with_context(|| format!("failed to deserialize JSON at `{}`", path)) {
+                    Ok(data) if generated => ProjectFile::GeneratedValue(data),
+                    Ok(data) => ProjectFile::Value(data),
+                    Err(err) => ProjectFile::BadValue(err),
+                };
+            match (&file, pending) {
+                (ProjectFile::BadValue(_), Some(pending)) => {
+                    self.pending_json.insert(path.clone(), pending);
+                }
+                _ => {
+                    self.pending_json.remove(&path);
+                }
+            }
+            self.files.insert(path, file);
+        }
+
+        self.clean_validate()?;
+
+        for (id, err) in graph_errors {
+            let Some(path) = self
+                .files
+                .iter()
+                .find(|(_, file)| matches!(file, ProjectFile::Graph(g) if *g == id))
+                .map(|(path, _)| path.clone())
+            else {
+                continue;
+            };
+            let mut ctx = self.diagnostics.enter(path.as_str());
+            ctx.clear_downstream();
+            ctx.emit_error(with_code(
+                err.wrap_err("failed to reload graph, previous state is kept"),
+                "graph/reload",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Parses the graph and adds it to the project graphs
+    ///
+    /// Graphs that fail to parse, for example due to an unknown node type,
+    /// are kept as [ProjectFile::BadValue]
+    fn load_graph(&mut self, path: &Utf8Path, mut json: JsonValue) -> miette::Result<ProjectFile> {
+        let graph = match ProjectGraph::parse_json(&self.registry, &mut json)
+            .with_context(|| format!("failed to deserialize Graph at `{}`", path))
+        {
+            Ok(graph) => graph,
+            Err(err) => return Ok(ProjectFile::BadValue(err)),
+        };
+
+        self.graphs
+            .add_graph(path.to_path_buf(), graph)
+            .with_context(|| format!("failed to process Graph at `{}`", path))
+    }
+
+    /// Reads the raw item JSON and its type from the disk
+    fn read_item(&self, path: &Utf8Path) -> miette::Result<(JsonValue, Option<EDataType>)> {
+        let data = utf8str(path, self.io.read_file(path)?)?;
+        let (mut json, ty) = if path_has_suffix(path, EXTENSION_VALUE) {
+            let data: MiscJson = serde_json5::from_str(&data)
+                .into_diagnostic()
+                .context("failed to deserialize dbefile JSON")?;
+            (data.value, Some(data.ty))
+        } else {
+            let data = serde_json5::from_str(&data)
+                .into_diagnostic()
+                .context("failed to deserialize JSON")?;
+            (data, None)
+        };
+        self.diagnostic_rules.load_suppressions(path, &mut json)?;
+        self.item_sources.insert(path, data);
+        Ok((json, ty))
+    }
+
+    /// Recalculates computed fields of all values, recording the changed
+    /// files into the undo history as a single change set
+    fn update_all_computed(&mut self) -> miette::Result<()> {
+        let updated = self
+            .files
+            .iter()
+            .filter_map(|(path, file)| {
+                let ProjectFile::Value(value) = file else {
+                    return None;
+                };
+                let mut value = value.clone();
+                update_computed(&self.registry, Some(&self.graphs), &mut value)
+                    .then(|| (path.clone(), value))
+            })
+            .collect_vec();
+
+        if updated.is_empty() {
+            return Ok(());
+        }
+
+        self.history.begin_group(&self.files, &self.graphs)?;
+        for (path, value) in updated {
+            self.history
+                .ensure_file_state(&self.files, &self.graphs, &path)?;
+            self.files.insert(path.clone(), ProjectFile::Value(value));
+            self.history
+                .check_file(&self.files, &self.graphs, &path, true)?;
+        }
+        self.history.end_group(&self.files, &self.graphs)?;
+
         Ok(())
     }
 
     pub fn save(&mut self) -> miette::Result<()> {
+        // Inherited fields are brought up to date before the computed fields
+        // depending on them are updated
+        self.merge_prefabs();
+
+        self.update_all_computed()?;
+
+        if !self.pending_json.is_empty() {
+            bail!(
+                help = "fix the types and reload them to keep the edits",
+                "unsaved edits of {} no longer match their types, cannot save",
+                self.pending_json
+                    .keys()
+                    .map(|path| format!("`{}`", path))
+                    .join(", ")
+            );
+        }
+
         self.clean_validate()?;
 
         if self.diagnostics.has_diagnostics(DiagnosticLevel::Error) {
@@ -503,6 +1253,8 @@ impl<IO: ProjectIO> Project<IO> {
             |sender, (path, file)| -> miette::Result<()> {
                 sender.send(path.clone()).unwrap();
                 let mut generated = false;
+                let is_value =
+                    matches!(file, ProjectFile::Value(_) | ProjectFile::GeneratedValue(_));
                 fn wrap_if_dbe(path: &Utf8Path, value: &EValue, json: JsonValue) -> JsonValue {
                     if path
                         .extension()
@@ -522,7 +1274,9 @@ impl<IO: ProjectIO> Project<IO> {
                 let json_string = m_try(|| {
                     let json = match file {
                         ProjectFile::Value(value) => {
-                            wrap_if_dbe(path, value, self.serialize_json(value)?)
+                            let mut json = self.serialize_with_prefab(path, value)?;
+                            self.diagnostic_rules.write_suppressions(path, &mut json);
+                            wrap_if_dbe(path, value, json)
                         }
                         ProjectFile::GeneratedValue(value) => {
                             generated = true;
@@ -563,10 +1317,23 @@ impl<IO: ProjectIO> Project<IO> {
                     .write_file(path, json_string.as_bytes())
                     .with_context(|| format!("failed to write JSON to `{}`", path))?;
 
+                if is_value {
+                    self.item_sources.insert(path, json_string);
+                }
+
                 Ok(())
             },
         )?;
 
+        for (path, data) in Localization::of(&self.registry)
+            .write()
+            .take_dirty_files()?
+        {
+            self.io
+                .write_file(&path, &data)
+                .with_context(|| format!("failed to write translations to `{}`", path))?;
+        }
+
         for p in no_delete_receiver.try_iter() {
             self.to_delete.remove(&p);
         }
@@ -577,10 +1344,14 @@ impl<IO: ProjectIO> Project<IO> {
                 self.io
                     .delete_file(&path)
                     .with_context(|| format!("failed to delete `{}`", path))?;
+                self.item_sources.remove(&path);
 
                 Ok(())
             })?;
 
+        // Migrated files are now written with the current versions
+        self.migrations.clear();
+
         self.io.flush()?;
 
         Ok(())
@@ -590,18 +1361,106 @@ impl<IO: ProjectIO> Project<IO> {
 impl<IO> Project<IO> {
     /// See [UndoHistory::undo]
     pub fn undo(&mut self) -> miette::Result<Utf8PathBuf> {
-        self.history.undo(&mut self.files, &mut self.graphs)
+        let path = self.history.undo(&mut self.files, &mut self.graphs)?;
+        self.merge_prefabs();
+        Ok(path)
     }
 
     /// See [UndoHistory::redo]
     pub fn redo(&mut self) -> miette::Result<Utf8PathBuf> {
-        self.history.redo(&mut self.files, &mut self.graphs)
+        let path = self.history.redo(&mut self.files, &mut self.graphs)?;
+        self.merge_prefabs();
+        Ok(path)
     }
 
     /// See [UndoHistory::check_file]
+    ///
+    /// Also updates the overridden fields of the file if it extends a prefab
+    /// base, and the inherited fields of the files extending it. Translation
+    /// edits made while editing the file are recorded into the history too
     pub fn file_changed(&mut self, path: &Utf8PathBuf, force_snapshot: bool) -> miette::Result<()> {
         self.history
-            .check_file(&self.files, &self.graphs, path, force_snapshot)
+            .check_file(&self.files, &self.graphs, path, force_snapshot)?;
+
+        let localization = Localization::of(&self.registry);
+        let edits = localization.write().take_edits();
+        for edit in edits {
+            self.history.record_translation(localization.clone(), edit);
+        }
+
+        if let (Some(base), Some(ProjectFile::Value(value))) =
+            (self.prefab_base(path).cloned(), self.files.get(path))
+        {
+            if let Some(prefab) = self.prefabs.get_mut(path) {
+                prefab.track_overrides(&base, value);
+            }
+        }
+        self.merge_prefab_children(path);
+
+        Ok(())
+    }
+
+    /// Returns the effective value of the prefab base of the file
+    pub fn prefab_base(&self, path: &Utf8Path) -> Option<&EValue> {
+        match self.files.get(&self.prefabs.get(path)?.base)? {
+            ProjectFile::Value(value) | ProjectFile::GeneratedValue(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Copies the inherited fields of the prefab bases into all files
+    /// extending them
+    ///
+    /// Files extending other files are not recorded into the undo history
+    /// on their own, since their inherited fields are derived from the base
+    fn merge_prefabs(&mut self) {
+        let mut merged = HashSet::default();
+        for path in self.prefabs.keys().cloned().collect_vec() {
+            self.merge_prefab_chain(&path, &mut merged);
+        }
+    }
+
+    /// Merges the bases of the file first, and then the file itself
+    fn merge_prefab_chain(&mut self, path: &Utf8Path, merged: &mut HashSet<Utf8PathBuf>) {
+        let Some(prefab) = self.prefabs.get(path) else {
+            return;
+        };
+        if !merged.insert(path.to_path_buf()) {
+            return;
+        }
+        let base = prefab.base.clone();
+        self.merge_prefab_chain(&base, merged);
+        self.merge_prefab(path);
+    }
+
+    /// Merges the files extending the base, and the files extending them
+    fn merge_prefab_children(&mut self, base: &Utf8Path) {
+        let children = self
+            .prefabs
+            .iter()
+            .filter(|(_, prefab)| prefab.base == base)
+            .map(|(path, _)| path.clone())
+            .collect_vec();
+        for child in children {
+            if self.merge_prefab(&child) {
+                self.merge_prefab_children(&child);
+            }
+        }
+    }
+
+    /// Copies the inherited fields of the prefab base into the file,
+    /// returning whether the file was changed
+    fn merge_prefab(&mut self, path: &Utf8Path) -> bool {
+        let Some(base) = self.prefab_base(path).cloned() else {
+            return false;
+        };
+        let Some(prefab) = self.prefabs.get(path) else {
+            return false;
+        };
+        match self.files.get_mut(path) {
+            Some(ProjectFile::Value(value)) => prefab.merge_inherited(&base, value),
+            _ => false,
+        }
     }
 
     pub fn import_root(&self) -> EDataType {
@@ -611,6 +1470,45 @@ impl<IO> Project<IO> {
     }
 }
 
+/// Warns about the unknown JSON fields of the file
+fn report_unknown_fields(mut ctx: DiagnosticContextMut, unknown: &[UnknownFields]) {
+    for entry in unknown {
+        let fix = DiagnosticFix::new(
+            "Drop unknown fields",
+            ctx.path().clone(),
+            FixEdit::DropUnknownFields,
+        );
+        ctx.emit_with_fixes(
+            miette!(
+                code = "field/unknown",
+                help = "unknown fields are removed from the file when the project is saved",
+                "struct `{}` contains unknown fields: {}",
+                entry.ty,
+                entry.fields.iter().map(|k| format!("`{k}`")).join(", ")
+            ),
+            DiagnosticLevel::Warning,
+            vec![fix],
+        );
+    }
+}
+
+/// Reports the data migrations applied to the file on load
+fn report_migrations(mut ctx: DiagnosticContextMut, applied: &[AppliedMigration]) {
+    for migration in applied {
+        ctx.emit(
+            miette!(
+                code = "migration/applied",
+                help = "the file is written with the current version when the project is saved",
+                "data of type `{}` was migrated from version {} to {}",
+                migration.ty,
+                migration.from,
+                migration.to
+            ),
+            DiagnosticLevel::Info,
+        );
+    }
+}
+
 fn generated_marker_path(file: impl AsRef<Utf8Path>) -> Utf8PathBuf {
     let file = file.as_ref();
     file.parent()
@@ -623,31 +1521,104 @@ impl<IO: ProjectIO> Project<IO> {
     //     self.files.get_mut(path)
     // }
 
-    fn validate_config(&self) -> miette::Result<()> {
-        self.registry
-            .get_object(&self.registry.project_config().types_config.import)
+    fn validate_config(registry: &ETypesRegistry) -> miette::Result<()> {
+        registry
+            .get_object(&registry.project_config().types_config.import)
             .ok_or_else(|| {
                 miette!(
                     "unknown type `{}`",
-                    self.registry.project_config().types_config.import
+                    registry.project_config().types_config.import
                 )
             })
             .context("failed to validate [types.import] config entry")
             .context("project config is invalid")?;
 
+        check_migrations(registry).context("project config is invalid")?;
+
         Ok(())
     }
 
-    fn deserialize_json(
-        &self,
-        mut value: JsonValue,
+    /// Refreshes the list of project files available to `asset` fields
+    fn update_assets(&self) -> miette::Result<()> {
+        let files = self
+            .io
+            .list_files()?
+            .into_iter()
+            .filter_map(|path| {
+                let relative = path.strip_prefix(&self.root).ok()?;
+                Utf8Path::from_path(relative).map(|p| p.to_path_buf())
+            })
+            .collect();
+
+        *ProjectAssets::of(&self.registry).write() = ProjectAssets {
+            root: self.root.clone(),
+            files,
+        };
+
+        Ok(())
+    }
+
+    /// Parses the item JSON, running data migrations of its types
+    fn parse_item(
+        &mut self,
+        path: &Utf8Path,
+        mut json: JsonValue,
         ty: Option<EDataType>,
     ) -> miette::Result<EValue> {
         let ty = ty.unwrap_or_else(|| EDataType::Object {
             ident: self.registry.project_config().types_config.import,
         });
+        let registry = &self.registry;
+        let ((data, applied), unknown) = collect_unknown_fields(|| {
+            collect_applied(&mut self.graphs, || {
+                ty.parse_json(registry, &mut json, false)
+            })
+        });
+        let unknown_fields = UnknownFields::of(&self.registry);
+        if unknown.is_empty() || data.is_err() {
+            unknown_fields.write().remove(path);
+        } else {
+            unknown_fields.write().insert(path.to_path_buf(), unknown);
+        }
+        if applied.is_empty() {
+            self.migrations.remove(path);
+        } else {
+            for migration in &applied {
+                info!(
+                    %path,
+                    ty = %migration.ty,
+                    from = migration.from,
+                    to = migration.to,
+                    "migrated data"
+                );
+            }
+            self.migrations.insert(path.to_path_buf(), applied);
+        }
+
+        data
+    }
+
+    /// Serializes the value, omitting the fields inherited from the prefab base
+    fn serialize_with_prefab(&self, path: &Utf8Path, value: &EValue) -> miette::Result<JsonValue> {
+        let mut json = self.serialize_json(value)?;
+        let Some(prefab) = self.prefabs.get(path) else {
+            return Ok(json);
+        };
 
-        ty.parse_json(&self.registry, &mut value, false)
+        let Some(base) = self.prefab_base(path) else {
+            // Base is broken, so all fields are kept to avoid losing data
+            if let Some(obj) = json.as_object_mut() {
+                obj.insert(EXTENDS_FIELD.to_string(), prefab.base.as_str().into());
+            }
+            return Ok(json);
+        };
+
+        // Fields edited outside of the editor are overrides as well
+        let overrides = prefab.overridden_fields(base, value);
+
+        let full = with_default_values(|| self.serialize_json(value))?;
+
+        Ok(diff_prefab(&prefab.base, &overrides, &full, json))
     }
 
     fn serialize_json(&self, value: &EValue) -> miette::Result<JsonValue> {
@@ -661,6 +1632,25 @@ impl<IO: ProjectIO> Project<IO> {
     }
 }
 
+/// Upgrades the JSON of a prefab file before it is merged with its base
+fn migrate_prefab_file(
+    registry: &ETypesRegistry,
+    ty: Option<EDataType>,
+    json: &mut JsonValue,
+    base: Option<&JsonMap>,
+) -> miette::Result<Option<u32>> {
+    let ty = ty.unwrap_or_else(|| EDataType::Object {
+        ident: registry.project_config().types_config.import,
+    });
+    migrate_prefab(registry, ty, json, base)
+}
+
+fn utf8str(path: &Utf8Path, data: Vec<u8>) -> miette::Result<String> {
+    String::from_utf8(data).into_diagnostic().with_context(|| {
+        format!("failed to parse content of a file `{path}`. Are you sure it's UTF-8 encoded?")
+    })
+}
+
 fn path_has_suffix(path: &Utf8Path, extension: &str) -> bool {
     let path_str = path.as_str();
     if path_str.len() < extension.len() {
@@ -0,0 +1,92 @@
+//! Asset files referenced by `asset` fields
+//!
+//! The list of project files is collected via [ProjectIO](crate::project::io::ProjectIO)
+//! when the project is loaded or validated, and stored in the registry extra
+//! data, so validators and editors can access it via [ProjectAssets::of]
+
+use crate::etype::econst::ETypeConst;
+use crate::etype::property::default_properties::{PROP_FIELD_BASE_DIR, PROP_FIELD_EXTENSIONS};
+use crate::etype::property::FieldPropertyId;
+use crate::registry::ETypesRegistry;
+use camino::{Utf8Path, Utf8PathBuf};
+use parking_lot::RwLock;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use utils::map::HashMap;
+
+pub type ProjectAssetsData = RwLock<ProjectAssets>;
+
+#[derive(Debug, Default)]
+pub struct ProjectAssets {
+    /// Absolute path of the project root
+    pub root: Utf8PathBuf,
+    /// Paths of all project files, relative to the project root
+    pub files: BTreeSet<Utf8PathBuf>,
+}
+
+impl ProjectAssets {
+    /// Returns the assets data of the given registry
+    pub fn of(registry: &ETypesRegistry) -> Arc<ProjectAssetsData> {
+        registry.extra_data::<ProjectAssetsData>()
+    }
+
+    pub fn exists(&self, path: &Utf8Path) -> bool {
+        self.files.contains(path)
+    }
+
+    /// Returns all files matching the filter, as paths relative to the
+    /// filter's base directory
+    pub fn matching<'a>(&'a self, filter: &'a AssetFilter) -> impl Iterator<Item = &'a Utf8Path> {
+        self.files
+            .iter()
+            .filter_map(|path| path.strip_prefix(&filter.base_dir).ok())
+            .filter(|path| filter.extension_allowed(path))
+    }
+}
+
+/// Restrictions of the `asset` field
+#[derive(Debug, Clone, Default)]
+pub struct AssetFilter {
+    /// Directory that asset paths are relative to
+    pub base_dir: Utf8PathBuf,
+    /// Lowercase allowed extensions. Empty list allows any extension
+    pub extensions: Vec<String>,
+}
+
+impl AssetFilter {
+    pub fn from_props(props: &HashMap<FieldPropertyId, ETypeConst>) -> Self {
+        let base_dir = PROP_FIELD_BASE_DIR
+            .try_get(props)
+            .map(|dir| Utf8PathBuf::from(dir.as_str()))
+            .unwrap_or_default();
+
+        let extensions = PROP_FIELD_EXTENSIONS
+            .try_get(props)
+            .map(|ext| {
+                ext.split(',')
+                    .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+                    .filter(|e| !e.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            base_dir,
+            extensions,
+        }
+    }
+
+    pub fn extension_allowed(&self, path: &Utf8Path) -> bool {
+        if self.extensions.is_empty() {
+            return true;
+        }
+
+        path.extension()
+            .is_some_and(|ext| self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+    }
+
+    /// Returns the path of the asset relative to the project root
+    pub fn project_path(&self, asset: &str) -> Utf8PathBuf {
+        self.base_dir.join(asset.replace('\\', "/"))
+    }
+}
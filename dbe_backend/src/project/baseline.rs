@@ -0,0 +1,115 @@
+//! Baseline of the known diagnostics
+//!
+//! The baseline file records the diagnostics present in the project at the
+//! time of the snapshot, keyed by file, path and rule. Diagnostics recorded in
+//! the baseline are not reported, so new validators can be introduced without
+//! fixing all the existing data first. Baseline entries that are no longer
+//! reported are listed after each full validation, so the baseline can be
+//! updated once the data is fixed.
+
+use diagnostic::context::DiagnosticContext;
+use diagnostic::rules::report_code;
+use miette::{Context, IntoDiagnostic};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Path of the baseline file, relative to the project root
+pub const BASELINE_FILE: &str = "diagnostics.baseline.json";
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub file: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    pub entries: BTreeSet<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Records all diagnostics of the context
+    pub fn snapshot(diagnostics: &DiagnosticContext) -> Self {
+        let entries = diagnostics
+            .diagnostics
+            .iter()
+            .filter(|(file, _)| file.as_str() != BASELINE_FILE)
+            .flat_map(|(file, paths)| {
+                paths.iter().flat_map(move |(path, diagnostics)| {
+                    diagnostics.iter().map(move |diagnostic| BaselineEntry {
+                        file: file.clone(),
+                        path: path.to_string(),
+                        rule: report_code(&diagnostic.info),
+                    })
+                })
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, entry: &BaselineEntry) -> bool {
+        self.entries.contains(entry)
+    }
+
+    pub fn from_json(data: &str) -> miette::Result<Self> {
+        serde_json::from_str(data)
+            .into_diagnostic()
+            .context("failed to deserialize diagnostics baseline")
+    }
+
+    pub fn to_json(&self) -> miette::Result<String> {
+        serde_json::to_string_pretty(self)
+            .into_diagnostic()
+            .context("failed to serialize diagnostics baseline")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Baseline, BaselineEntry, BASELINE_FILE};
+    use diagnostic::context::DiagnosticContext;
+    use miette::miette;
+
+    #[test]
+    fn should_snapshot_diagnostics() {
+        let mut diagnostics = DiagnosticContext::default();
+        diagnostics
+            .enter("Ship.json")
+            .enter_field("Id")
+            .emit_error(miette!(code = "ids/duplicate", "duplicate ID"));
+        diagnostics
+            .enter("Ship.json")
+            .emit_warning(miette!("no code"));
+        diagnostics
+            .enter(BASELINE_FILE)
+            .emit_warning(miette!(code = "baseline/fixed", "fixed"));
+
+        let baseline = Baseline::snapshot(&diagnostics);
+        let expected = [
+            BaselineEntry {
+                file: "Ship.json".to_string(),
+                path: "".to_string(),
+                rule: None,
+            },
+            BaselineEntry {
+                file: "Ship.json".to_string(),
+                path: "Id".to_string(),
+                rule: Some("ids/duplicate".to_string()),
+            },
+        ];
+        assert_eq!(baseline.entries, expected.into());
+
+        let json = baseline.to_json().unwrap();
+        assert_eq!(
+            Baseline::from_json(&json).unwrap().entries,
+            baseline.entries
+        );
+    }
+}
@@ -1,9 +1,16 @@
+use crate::etype::econst::ETypeConst;
 use crate::etype::eobject::EObject;
-use crate::registry::ETypesRegistry;
+use crate::etype::property::default_properties::{
+    PROP_FIELD_DESCRIPTION, PROP_FIELD_DOC, PROP_OBJECT_DESCRIPTION, PROP_OBJECT_DOC,
+};
+use crate::etype::property::FieldPropertyId;
+use crate::graph::node::get_node_factory;
+use crate::project::EXTENSION_TYPE;
+use crate::registry::{EObjectType, ETypesRegistry};
 use crate::value::id::ETypeId;
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use duplicate::duplicate_item;
-use miette::bail;
+use miette::{bail, miette};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::collections::btree_map::Entry;
@@ -106,6 +113,230 @@ impl Docs {
         Ok(())
     }
 
+    /// Adds documentation declared in the KDL type definitions via the
+    /// `description` properties and `doc` children
+    ///
+    /// Entries that are already present, usually coming from `docs.toml`
+    /// files, take precedence over the KDL ones
+    pub fn add_kdl_docs(
+        &mut self,
+        registry: &ETypesRegistry,
+        type_paths: &HashMap<ETypeId, Utf8PathBuf>,
+    ) {
+        let Docs::Docs(docs) = self else {
+            panic!("Cannot add KDL docs to a stub docs");
+        };
+
+        for obj in registry.all_ready_objects() {
+            if obj.generic_parent_id().is_some() {
+                continue;
+            }
+            let Some(location) = type_paths.get(&obj.ident()) else {
+                continue;
+            };
+
+            let type_docs = kdl_docs(
+                PROP_OBJECT_DESCRIPTION.try_get(obj.extra_properties()),
+                PROP_OBJECT_DOC.try_get(obj.extra_properties()),
+            );
+
+            let mut fields = vec![];
+            let mut variants = vec![];
+            match obj.deref() {
+                EObjectType::Struct(data) => {
+                    for field in &data.fields {
+                        if let Some((description, docs)) =
+                            kdl_field_docs(field.ty.extra_properties())
+                        {
+                            fields.push(FieldDocs {
+                                id: field.name.to_string(),
+                                description,
+                                docs,
+                                from_kdl: true,
+                            });
+                        }
+                    }
+                }
+                EObjectType::Enum(data) => {
+                    for variant in data.variants() {
+                        if let Some((description, docs)) =
+                            kdl_field_docs(variant.data.extra_properties())
+                        {
+                            variants.push(VariantDocs {
+                                id: variant.name.to_string(),
+                                description,
+                                docs,
+                                from_kdl: true,
+                            });
+                        }
+                    }
+                }
+            }
+
+            if type_docs.is_none() && fields.is_empty() && variants.is_empty() {
+                continue;
+            }
+
+            match docs.types.entry(obj.ident()) {
+                Entry::Vacant(e) => {
+                    let (description, docs) = type_docs.unwrap_or_default();
+                    e.insert(WithLocation {
+                        value: TypeDocs {
+                            description,
+                            docs,
+                            fields,
+                            variants,
+                        },
+                        location: location.clone(),
+                    });
+                }
+                Entry::Occupied(e) => {
+                    let existing = &mut e.into_mut().value;
+                    for field in fields {
+                        if !existing.fields.iter().any(|f| f.id == field.id) {
+                            existing.fields.push(field);
+                        }
+                    }
+                    for variant in variants {
+                        if !existing.variants.iter().any(|v| v.id == variant.id) {
+                            existing.variants.push(variant);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes the documentation added by [Docs::add_kdl_docs], so the
+    /// KDL docs can be added again after the types are reloaded
+    pub fn remove_kdl_docs(&mut self) {
+        let Docs::Docs(docs) = self else {
+            panic!("Cannot remove KDL docs from a stub docs");
+        };
+
+        docs.types.retain(|_, ty| !is_kdl_location(&ty.location));
+        for ty in docs.types.values_mut() {
+            ty.value.fields.retain(|field| !field.from_kdl);
+            ty.value.variants.retain(|variant| !variant.from_kdl);
+        }
+    }
+
+    /// Checks the type documentation against the registry
+    ///
+    /// Reports entries of unknown types, fields and variants, as well as
+    /// `docs.toml` entries that override descriptions declared in KDL
+    pub fn check_types(&self, registry: &ETypesRegistry) -> Vec<(Utf8PathBuf, miette::Report)> {
+        let Docs::Docs(docs) = self else {
+            inform_stub("types");
+            return vec![];
+        };
+
+        let mut out = vec![];
+        for (id, ty) in &docs.types {
+            let location = &ty.location;
+            let Some(obj) = registry.get_object(id) else {
+                out.push((
+                    location.clone(),
+                    miette!("documented type `{}` does not exist", id),
+                ));
+                continue;
+            };
+
+            // KDL docs can't conflict with themselves
+            let check_conflicts = !is_kdl_location(location);
+            let mut conflict = |what: String, kdl: Option<Ustr>, docs: &dyn DocsDescription| {
+                if !check_conflicts {
+                    return;
+                }
+                if kdl.is_some_and(|kdl| kdl.trim() != docs.description()) {
+                    out.push((
+                        location.clone(),
+                        miette!(
+                            "description of {} overrides the description declared in KDL",
+                            what
+                        ),
+                    ));
+                }
+            };
+
+            conflict(
+                format!("type `{}`", id),
+                PROP_OBJECT_DESCRIPTION.try_get(obj.extra_properties()),
+                &ty.value,
+            );
+
+            let mut orphans = vec![];
+            match obj.deref() {
+                EObjectType::Struct(data) => {
+                    for field in &ty.fields {
+                        match data.fields.iter().find(|f| f.name.as_str() == field.id) {
+                            None => orphans.push(format!("field `{}`", field.id)),
+                            Some(f) => conflict(
+                                format!("field `{}` of `{}`", field.id, id),
+                                PROP_FIELD_DESCRIPTION.try_get(f.ty.extra_properties()),
+                                field,
+                            ),
+                        }
+                    }
+                    for variant in &ty.variants {
+                        orphans.push(format!("variant `{}`", variant.id));
+                    }
+                }
+                EObjectType::Enum(data) => {
+                    for variant in &ty.variants {
+                        match data
+                            .variants()
+                            .iter()
+                            .find(|v| v.name.as_str() == variant.id)
+                        {
+                            None => orphans.push(format!("variant `{}`", variant.id)),
+                            Some(v) => conflict(
+                                format!("variant `{}` of `{}`", variant.id, id),
+                                PROP_FIELD_DESCRIPTION.try_get(v.data.extra_properties()),
+                                variant,
+                            ),
+                        }
+                    }
+                    for field in &ty.fields {
+                        orphans.push(format!("field `{}`", field.id));
+                    }
+                }
+            }
+
+            for orphan in orphans {
+                out.push((
+                    location.clone(),
+                    miette!("documented {} does not exist in type `{}`", orphan, id),
+                ));
+            }
+        }
+
+        out
+    }
+
+    /// Reports node documentation entries of nonexistent node types
+    pub fn check_nodes(&self) -> Vec<(Utf8PathBuf, miette::Report)> {
+        let Docs::Docs(docs) = self else {
+            inform_stub("nodes");
+            return vec![];
+        };
+
+        docs.nodes
+            .iter()
+            .filter(|(id, _)| get_node_factory(&Ustr::from(id.as_str())).is_none())
+            .map(|(id, node)| {
+                (
+                    node.location.clone(),
+                    miette!(
+                        code = "lint/unknown-node-docs",
+                        "documented node `{}` does not exist",
+                        id
+                    ),
+                )
+            })
+            .collect()
+    }
+
     pub fn all_nodes(&self) -> impl Iterator<Item = (&str, &NodeDocs)> {
         match self {
             Docs::Docs(docs) => docs.nodes.iter(),
@@ -238,6 +469,9 @@ pub struct FieldDocs {
     pub description: String,
     #[serde(default)]
     pub docs: String,
+    /// Whether the entry was declared in KDL
+    #[serde(skip)]
+    pub from_kdl: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -247,6 +481,9 @@ pub struct VariantDocs {
     pub description: String,
     #[serde(default)]
     pub docs: String,
+    /// Whether the entry was declared in KDL
+    #[serde(skip)]
+    pub from_kdl: bool,
 }
 
 #[derive(Debug)]
@@ -269,6 +506,36 @@ impl<T> DerefMut for WithLocation<T> {
     }
 }
 
+/// Returns the description and the long-form docs of the KDL item
+///
+/// When the description is not provided, the first line of the docs is used
+fn kdl_docs(description: Option<Ustr>, doc: Option<Ustr>) -> Option<(String, String)> {
+    if description.is_none() && doc.is_none() {
+        return None;
+    }
+
+    let docs = doc.map(|doc| doc.trim().to_string()).unwrap_or_default();
+    let description = match description {
+        Some(description) => description.trim().to_string(),
+        None => docs.lines().next().unwrap_or_default().to_string(),
+    };
+
+    Some((description, docs))
+}
+
+fn kdl_field_docs(props: &HashMap<FieldPropertyId, ETypeConst>) -> Option<(String, String)> {
+    kdl_docs(
+        PROP_FIELD_DESCRIPTION.try_get(props),
+        PROP_FIELD_DOC.try_get(props),
+    )
+}
+
+fn is_kdl_location(location: &Utf8Path) -> bool {
+    location
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(EXTENSION_TYPE))
+}
+
 fn validate_nonempty(s: &mut String, field_name: &str) -> miette::Result<()> {
     *s = s.trim().to_string();
     if s.is_empty() {
@@ -482,3 +749,74 @@ fn inform_stub(what: &str) {
         warn!("Cannot get {} from a stub docs", what);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{kdl_docs, Docs, DocsContent, TypeDocs, WithLocation};
+    use crate::project::io::embedded_registry;
+    use crate::registry::ETypesRegistry;
+    use crate::value::id::ETypeId;
+    use camino::Utf8PathBuf;
+    use rstest::rstest;
+    use utils::map::HashMap;
+
+    #[rstest]
+    #[case(None, None, None)]
+    #[case(Some("Short"), None, Some(("Short", "")))]
+    #[case(None, Some("  First line\nSecond line  "), Some(("First line", "First line\nSecond line")))]
+    #[case(Some(" Short "), Some("Long docs"), Some(("Short", "Long docs")))]
+    fn should_split_kdl_docs(
+        #[case] description: Option<&str>,
+        #[case] doc: Option<&str>,
+        #[case] expected: Option<(&str, &str)>,
+    ) {
+        let docs = kdl_docs(description.map(Into::into), doc.map(Into::into));
+        assert_eq!(
+            docs,
+            expected.map(|(description, docs)| (description.to_string(), docs.to_string()))
+        );
+    }
+
+    fn registry(id: ETypeId, description: &str) -> ETypesRegistry {
+        let kdl = format!(
+            r#"struct {{ number "Level" description="{}" }}"#,
+            description
+        );
+        embedded_registry([(id, kdl)]).unwrap()
+    }
+
+    #[rstest]
+    fn should_replace_kdl_docs_on_reload(#[values(false, true)] toml_entry: bool) {
+        let id = ETypeId::from_raw("test:item".into());
+        let type_paths: HashMap<ETypeId, Utf8PathBuf> =
+            [(id, "test/item.kdl".into())].into_iter().collect();
+
+        let mut content = DocsContent::default();
+        if toml_entry {
+            content.types.insert(
+                id,
+                WithLocation {
+                    value: TypeDocs {
+                        description: "Item".to_string(),
+                        docs: String::new(),
+                        fields: vec![],
+                        variants: vec![],
+                    },
+                    location: "test/item.docs.toml".into(),
+                },
+            );
+        }
+        let mut docs = Docs::Docs(content);
+
+        docs.add_kdl_docs(&registry(id, "Old"), &type_paths);
+        docs.remove_kdl_docs();
+        docs.add_kdl_docs(&registry(id, "New"), &type_paths);
+
+        let Docs::Docs(content) = &docs else {
+            unreachable!()
+        };
+        let fields = &content.types[&id].fields;
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].description, "New");
+    }
+}
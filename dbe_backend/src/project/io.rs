@@ -1,9 +1,22 @@
+use camino::Utf8Path;
 pub use fs::FilesystemIO;
 use std::path::{Path, PathBuf};
 
 mod embedded;
 mod fs;
 
+#[cfg(test)]
+pub(crate) use embedded::embedded_registry;
+
+/// Directory of the modules embedded into the editor, relative to the
+/// project root
+pub const EMBEDDED_DIR: &str = "\0<embedded>\0";
+
+/// Checks whether the project path belongs to the embedded modules
+pub fn is_embedded(path: &Utf8Path) -> bool {
+    path.starts_with(EMBEDDED_DIR)
+}
+
 pub trait ProjectIO: Send + Sync {
     fn list_files(&self) -> miette::Result<impl IntoIterator<Item = PathBuf> + 'static>;
     fn read_file(&self, path: impl AsRef<Path>) -> miette::Result<Vec<u8>>;
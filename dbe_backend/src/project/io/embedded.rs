@@ -28,3 +28,55 @@ impl<'a> Iterator for WalkDirIter<'a> {
         Some(entry)
     }
 }
+
+/// Builds the registry from the types of the embedded modules, along with
+/// the given extra types
+#[cfg(test)]
+pub(crate) fn embedded_registry(
+    extra: impl IntoIterator<Item = (crate::value::id::ETypeId, String)>,
+) -> miette::Result<crate::registry::ETypesRegistry> {
+    use crate::project::module::DbeModule;
+    use crate::project::{EXTENSION_TYPE, MODULE_FILE};
+    use crate::value::id::ETypeId;
+    use camino::Utf8PathBuf;
+    use miette::{Context, IntoDiagnostic};
+
+    let mut types = vec![];
+    for module_dir in MODULES.dirs() {
+        let module_path = Utf8PathBuf::from_path_buf(module_dir.path().to_path_buf())
+            .map_err(|path| miette::miette!("non UTF-8 module path `{}`", path.display()))?;
+        let module = module_dir
+            .get_file(module_dir.path().join(MODULE_FILE))
+            .and_then(|file| file.contents_utf8())
+            .ok_or_else(|| miette::miette!("module `{}` has no module file", module_path))?;
+        let module = toml::de::from_str::<DbeModule>(module)
+            .into_diagnostic()
+            .with_context(|| format!("failed to deserialize module `{}`", module_path))?
+            .with_path(module_path);
+
+        for file in walk_files(module_dir).filter_map(DirEntry::as_file) {
+            if file.path().extension().and_then(|ext| ext.to_str()) != Some(EXTENSION_TYPE) {
+                continue;
+            }
+            let id = ETypeId::from_path(&module, file.path())?;
+            let content = file
+                .contents_utf8()
+                .ok_or_else(|| miette::miette!("type `{}` is not UTF-8", id))?;
+            types.push((id, content.to_string()));
+        }
+    }
+    types.extend(extra);
+
+    let config = toml::de::from_str("types.import = \"sys:optional\"").into_diagnostic()?;
+    crate::registry::ETypesRegistry::from_raws(types, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::embedded_registry;
+
+    #[test]
+    fn should_build_registry_from_embedded_modules() {
+        embedded_registry([]).unwrap();
+    }
+}
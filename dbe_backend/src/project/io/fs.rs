@@ -1,6 +1,6 @@
 use crate::m_try;
 use crate::project::io::embedded::{walk_files, MODULES};
-use crate::project::io::{sha256, ProjectIO};
+use crate::project::io::{sha256, ProjectIO, EMBEDDED_DIR};
 use crate::project::EXTENSION_MODULE;
 use include_dir::DirEntry;
 use itertools::Itertools;
@@ -49,7 +49,7 @@ impl FilesystemIO {
     fn load_files(&mut self) -> miette::Result<()> {
         self.files.clear();
 
-        let embedded_dir = self.root.join("\0<embedded>\0");
+        let embedded_dir = self.root.join(EMBEDDED_DIR);
 
         for file in walk_files(&MODULES).filter_map(DirEntry::as_file) {
             let path = embedded_dir.join(file.path());
@@ -0,0 +1,228 @@
+//! Project-wide lints for unused and orphaned content
+//!
+//! Lints run at the end of [Project::clean_validate], once all files are
+//! evaluated and validated, and are reported at the info level:
+//!
+//! - `lint/unused-type`: KDL types of the project modules that are not
+//!   reachable from the import root type or the types of the value files.
+//!   Types of the modules embedded into the editor are skipped
+//! - `lint/unreferenced-id`: numeric IDs that are not referenced by any
+//!   `ids/numeric_ref` value
+//! - `lint/orphaned-generated`: generated files that were not emitted by any
+//!   graph, and will be deleted on save
+//! - `lint/unused-node-group`: node groups that are not used by any subgraph
+//!   node or migration
+//! - `lint/unknown-node-docs`: `docs.toml` entries of nonexistent nodes.
+//!   Entries of nonexistent types and fields are reported by `docs/invalid`
+
+use crate::etype::eobject::EObject;
+use crate::etype::migration::migration_graphs;
+use crate::etype::EDataType;
+use crate::graph::node::groups::subgraph::SubgraphNode;
+use crate::project::io::{is_embedded, ProjectIO};
+use crate::project::{generated_marker_path, Project, ProjectFile};
+use crate::registry::{EObjectType, ETypesRegistry};
+use crate::validation::ids::numeric::NumericIDRegistry;
+use crate::value::id::ETypeId;
+use diagnostic::diagnostic::DiagnosticLevel;
+use itertools::Itertools;
+use miette::miette;
+use utils::map::HashSet;
+
+/// Runs all lints, emitting their results into the project diagnostics
+pub fn run_lints<IO: ProjectIO>(project: &mut Project<IO>) -> miette::Result<()> {
+    unused_types(project);
+    unreferenced_ids(project);
+    orphaned_generated(project);
+    unused_node_groups(project)?;
+    unknown_node_docs(project);
+    Ok(())
+}
+
+fn unused_types<IO>(project: &mut Project<IO>) {
+    let roots = project.files.values().filter_map(|file| match file {
+        ProjectFile::Value(value) | ProjectFile::GeneratedValue(value) => Some(value.ty()),
+        _ => None,
+    });
+    let reachable = reachable_types(
+        &project.registry,
+        roots.chain([project.import_root()]).collect(),
+    );
+
+    // Types of the embedded modules are not part of the project
+    for (id, path) in project
+        .type_paths
+        .iter()
+        .filter(|(_, path)| !is_embedded(path))
+        .sorted_by(|a, b| a.1.cmp(b.1))
+    {
+        if reachable.contains(id) {
+            continue;
+        }
+        project.diagnostics.enter(path.as_str()).emit(
+            miette!(
+                code = "lint/unused-type",
+                "type `{}` is not used by the import root or any value file",
+                id
+            ),
+            DiagnosticLevel::Info,
+        );
+    }
+}
+
+/// Collects all object types reachable from the given types, including the
+/// generic types of the reachable instantiations
+fn reachable_types(registry: &ETypesRegistry, mut stack: Vec<EDataType>) -> HashSet<ETypeId> {
+    let mut visited = HashSet::default();
+    let mut objects = HashSet::default();
+
+    while let Some(ty) = stack.pop() {
+        if !visited.insert(ty) {
+            continue;
+        }
+
+        match ty {
+            EDataType::Object { ident } => {
+                objects.insert(ident);
+                let Some(obj) = registry.get_object(&ident) else {
+                    continue;
+                };
+                if let Some(parent) = obj.generic_parent_id() {
+                    stack.push(EDataType::Object { ident: parent });
+                }
+                stack.extend(obj.generic_arguments_values().iter().map(|arg| arg.ty()));
+                match &*obj {
+                    EObjectType::Struct(data) => {
+                        stack.extend(data.fields.iter().map(|field| field.ty.ty()))
+                    }
+                    EObjectType::Enum(data) => {
+                        stack.extend(data.variants().iter().map(|variant| variant.data.ty()))
+                    }
+                }
+            }
+            EDataType::List { id } => {
+                if let Some(list) = registry.get_list(&id) {
+                    stack.push(list.value_type);
+                }
+            }
+            EDataType::Map { id } => {
+                if let Some(map) = registry.get_map(&id) {
+                    stack.push(map.key_type);
+                    stack.push(map.value_type);
+                }
+            }
+            EDataType::Tuple { id } => {
+                if let Some(tuple) = registry.get_tuple(&id) {
+                    stack.extend(tuple.item_types);
+                }
+            }
+            EDataType::Boolean
+            | EDataType::Number
+            | EDataType::String
+            | EDataType::Const { .. }
+            | EDataType::Unknown => {}
+        }
+    }
+
+    objects
+}
+
+fn unreferenced_ids<IO>(project: &mut Project<IO>) {
+    for (ty, id, location) in NumericIDRegistry::of(&project.registry).unreferenced_ids() {
+        let (file, path) = location.split_once('@').unwrap_or((location.as_str(), ""));
+        let at = if path.is_empty() {
+            String::new()
+        } else {
+            format!(" at `{}`", path)
+        };
+        project.diagnostics.enter(file).emit(
+            miette!(
+                code = "lint/unreferenced-id",
+                "ID {} of type `{}`{} is never referenced",
+                id,
+                ty,
+                at
+            ),
+            DiagnosticLevel::Info,
+        );
+    }
+}
+
+fn orphaned_generated<IO>(project: &mut Project<IO>) {
+    // Graph evaluation deletes all generated files before running the side
+    // effects, so files that were not emitted again stay deleted
+    let orphaned = project
+        .to_delete
+        .iter()
+        .filter(|path| {
+            !project.files.contains_key(*path)
+                && project.to_delete.contains(&generated_marker_path(path))
+        })
+        .cloned()
+        .sorted()
+        .collect_vec();
+
+    for path in orphaned {
+        project.diagnostics.enter(path.as_str()).emit(
+            miette!(
+                code = "lint/orphaned-generated",
+                help = "the emitting graph was deleted, or failed to evaluate",
+                "generated file was not emitted by any graph, and will be deleted on save"
+            ),
+            DiagnosticLevel::Info,
+        );
+    }
+}
+
+fn unused_node_groups<IO>(project: &mut Project<IO>) -> miette::Result<()> {
+    let mut used = project
+        .graphs
+        .graphs
+        .values()
+        .flat_map(|graph| {
+            graph
+                .graph()
+                .snarl()
+                .nodes()
+                .filter_map(|node| node.downcast_ref::<SubgraphNode>())
+                .map(|node| node.graph_id)
+                .collect_vec()
+        })
+        .collect::<HashSet<_>>();
+    for path in migration_graphs(&project.registry)? {
+        if let Some(graph) = project.graphs.graph_by_path(&path) {
+            used.insert(graph.id);
+        }
+    }
+
+    for (path, file) in &project.files {
+        let ProjectFile::Graph(id) = file else {
+            continue;
+        };
+        let Some(graph) = project.graphs.graphs.get(id) else {
+            continue;
+        };
+        if !graph.is_node_group || used.contains(id) {
+            continue;
+        }
+        project.diagnostics.enter(path.as_str()).emit(
+            miette!(
+                code = "lint/unused-node-group",
+                "node group `{}` is not used by any subgraph node or migration",
+                graph.display_name()
+            ),
+            DiagnosticLevel::Info,
+        );
+    }
+
+    Ok(())
+}
+
+fn unknown_node_docs<IO>(project: &mut Project<IO>) {
+    for (location, report) in project.docs.check_nodes() {
+        project
+            .diagnostics
+            .enter(location.as_str())
+            .emit(report, DiagnosticLevel::Info);
+    }
+}
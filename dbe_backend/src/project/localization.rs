@@ -0,0 +1,502 @@
+//! Translations for `sys:localized` strings
+//!
+//! Translations are stored in per-locale files anywhere in the project, named
+//! `<name>.<locale>.locale.json` or `<name>.<locale>.locale.toml` (or just
+//! `<locale>.locale.json`). Nested objects are flattened into dot-separated
+//! keys, and are kept nested when the file is saved. Multiple files may
+//! contribute to the same locale, but a key may only be defined once per
+//! locale.
+//!
+//! Translations are stored in the registry extra data, so validators and
+//! editors can access them via [Localization::of]
+
+use crate::json_utils::formatter::DBEJsonFormatter;
+use crate::json_utils::{json_kind, JsonMap, JsonValue};
+use crate::registry::ETypesRegistry;
+use camino::{Utf8Path, Utf8PathBuf};
+use miette::{bail, miette, IntoDiagnostic};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use ustr::Ustr;
+
+pub const EXTENSION_LOCALE: &str = "locale";
+
+pub type LocalizationData = RwLock<Localization>;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct LocalizationConfig {
+    /// Locale that is active when the project is opened
+    ///
+    /// Defaults to the first locale in alphabetical order
+    #[serde(default)]
+    pub default_locale: Option<Ustr>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LocaleFormat {
+    Json,
+    Toml,
+}
+
+/// Returns the locale and format of the locale file at the given path, or
+/// `None` if the path is not a locale file
+pub fn locale_file_info(path: &Utf8Path) -> Option<(Ustr, LocaleFormat)> {
+    let format = match path.extension()?.to_lowercase().as_str() {
+        "json" => LocaleFormat::Json,
+        "toml" => LocaleFormat::Toml,
+        _ => return None,
+    };
+
+    let stem = Utf8Path::new(path.file_stem()?);
+    if !stem
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(EXTENSION_LOCALE))
+    {
+        return None;
+    }
+
+    let locale = Utf8Path::new(stem.file_stem()?);
+    let locale = locale.extension().unwrap_or(locale.as_str());
+    if locale.is_empty() {
+        return None;
+    }
+
+    Some((Ustr::from(locale), format))
+}
+
+#[derive(Debug)]
+struct LocaleFile {
+    format: LocaleFormat,
+    strings: BTreeMap<String, String>,
+    /// Content of the file as it was loaded, used to keep the nesting of
+    /// the keys when saving
+    layout: JsonMap<String, JsonValue>,
+    /// Whenever the file was edited since it was loaded or saved
+    dirty: bool,
+}
+
+#[derive(Debug, Default)]
+struct Locale {
+    files: BTreeMap<Utf8PathBuf, LocaleFile>,
+}
+
+impl Locale {
+    fn file_of(&self, key: &str) -> Option<&Utf8PathBuf> {
+        self.files
+            .iter()
+            .find(|(_, file)| file.strings.contains_key(key))
+            .map(|(path, _)| path)
+    }
+}
+
+/// Change of a single translation, made via [Localization::set]
+#[derive(Debug, Clone)]
+pub struct TranslationEdit {
+    /// Translation file holding the key
+    pub path: Utf8PathBuf,
+    pub locale: Ustr,
+    pub key: String,
+    /// Translation before the change, `None` if the key was missing
+    pub previous: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Localization {
+    locales: BTreeMap<Ustr, Locale>,
+    active: Option<Ustr>,
+    /// Keys referenced by the validated values
+    ///
+    /// Behind a mutex so validators only need a read lock of the
+    /// localization
+    used_keys: Mutex<BTreeSet<String>>,
+    /// Edits that are not yet recorded in the undo history
+    edits: Vec<TranslationEdit>,
+}
+
+impl Localization {
+    /// Returns the localization data of the given registry
+    pub fn of(registry: &ETypesRegistry) -> Arc<LocalizationData> {
+        registry.extra_data::<LocalizationData>()
+    }
+
+    /// Adds the content of the locale file
+    pub fn add_file(
+        &mut self,
+        path: Utf8PathBuf,
+        locale: Ustr,
+        format: LocaleFormat,
+        content: &str,
+    ) -> miette::Result<()> {
+        let data: JsonValue = match format {
+            LocaleFormat::Json => serde_json5::from_str(content).into_diagnostic()?,
+            LocaleFormat::Toml => toml::de::from_str(content).into_diagnostic()?,
+        };
+
+        let mut strings = BTreeMap::new();
+        flatten_strings(&mut strings, String::new(), data.clone())?;
+        let JsonValue::Object(layout) = data else {
+            unreachable!("root of the translation file is checked to be an object");
+        };
+
+        let entry = self.locales.entry(locale).or_default();
+        for key in strings.keys() {
+            if let Some(other) = entry.file_of(key) {
+                bail!(
+                    "translation key `{}` for locale `{}` is already defined in `{}`",
+                    key,
+                    locale,
+                    other
+                );
+            }
+        }
+
+        entry.files.insert(
+            path,
+            LocaleFile {
+                format,
+                strings,
+                layout,
+                dirty: false,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Selects the initially active locale
+    pub fn init_active_locale(&mut self, config: &LocalizationConfig) -> miette::Result<()> {
+        self.active = match config.default_locale {
+            Some(locale) => {
+                if !self.locales.contains_key(&locale) {
+                    bail!("default locale `{}` has no translation files", locale);
+                }
+                Some(locale)
+            }
+            None => self.locales.keys().next().copied(),
+        };
+
+        Ok(())
+    }
+
+    /// Returns all known locales
+    pub fn locales(&self) -> impl Iterator<Item = Ustr> + '_ {
+        self.locales.keys().copied()
+    }
+
+    /// Returns the locale that is currently shown in the editor
+    pub fn active_locale(&self) -> Option<Ustr> {
+        self.active
+    }
+
+    pub fn set_active_locale(&mut self, locale: Ustr) -> miette::Result<()> {
+        if !self.locales.contains_key(&locale) {
+            bail!("unknown locale `{}`", locale);
+        }
+        self.active = Some(locale);
+        Ok(())
+    }
+
+    /// Returns the translation of the key in the given locale
+    pub fn get(&self, locale: Ustr, key: &str) -> Option<&str> {
+        self.locales
+            .get(&locale)?
+            .files
+            .values()
+            .find_map(|file| file.strings.get(key))
+            .map(|s| s.as_str())
+    }
+
+    /// Sets the translation of the key in the given locale
+    ///
+    /// New keys are added to the first file of the locale. Changes are
+    /// collected until [Localization::take_edits] is called
+    pub fn set(&mut self, locale: Ustr, key: &str, text: String) -> miette::Result<()> {
+        if key.is_empty() {
+            bail!("translation key must not be empty");
+        }
+
+        let entry = self
+            .locales
+            .get_mut(&locale)
+            .ok_or_else(|| miette!("unknown locale `{}`", locale))?;
+
+        let path = match entry.file_of(key) {
+            Some(path) => path.clone(),
+            None => entry
+                .files
+                .keys()
+                .next()
+                .cloned()
+                .ok_or_else(|| miette!("locale `{}` has no translation files", locale))?,
+        };
+
+        let file = entry.files.get_mut(&path).expect("file should exist");
+        let previous = match file.strings.entry(key.to_string()) {
+            Entry::Occupied(e) if e.get() == &text => return Ok(()),
+            Entry::Occupied(mut e) => Some(e.insert(text)),
+            Entry::Vacant(e) => {
+                e.insert(text);
+                None
+            }
+        };
+        file.dirty = true;
+
+        self.edits.push(TranslationEdit {
+            path,
+            locale,
+            key: key.to_string(),
+            previous,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the translation edits made since the last call
+    pub fn take_edits(&mut self) -> Vec<TranslationEdit> {
+        std::mem::take(&mut self.edits)
+    }
+
+    /// Sets or removes the translation of the key in the given file without
+    /// recording an edit, returning the replaced translation
+    ///
+    /// Used by the undo history to revert [Localization::set]
+    pub fn restore(
+        &mut self,
+        path: &Utf8Path,
+        locale: Ustr,
+        key: &str,
+        text: Option<String>,
+    ) -> miette::Result<Option<String>> {
+        let file = self
+            .locales
+            .get_mut(&locale)
+            .and_then(|entry| entry.files.get_mut(path))
+            .ok_or_else(|| miette!("translation file `{}` of `{}` is missing", path, locale))?;
+
+        let previous = match text {
+            Some(text) => file.strings.insert(key.to_string(), text),
+            None => file.strings.remove(key),
+        };
+        file.dirty = true;
+
+        Ok(previous)
+    }
+
+    /// Returns the locales that have no translation for the given key
+    pub fn missing_locales(&self, key: &str) -> Vec<Ustr> {
+        self.locales
+            .iter()
+            .filter(|(_, locale)| locale.file_of(key).is_none())
+            .map(|(name, _)| *name)
+            .collect()
+    }
+
+    /// Marks the key as referenced by some value
+    pub fn mark_used(&self, key: &str) {
+        let mut used_keys = self.used_keys.lock();
+        if !used_keys.contains(key) {
+            used_keys.insert(key.to_string());
+        }
+    }
+
+    pub fn clear_used(&self) {
+        self.used_keys.lock().clear();
+    }
+
+    /// Returns all translation files, along with the keys that are not
+    /// referenced by any value
+    pub fn unused_keys(&self) -> Vec<(&Utf8PathBuf, Vec<&str>)> {
+        let used_keys = self.used_keys.lock();
+        self.locales
+            .values()
+            .flat_map(|locale| locale.files.iter())
+            .map(|(path, file)| {
+                let unused = file
+                    .strings
+                    .keys()
+                    .filter(|key| !used_keys.contains(*key))
+                    .map(|key| key.as_str())
+                    .collect();
+                (path, unused)
+            })
+            .collect()
+    }
+
+    /// Serializes all edited translation files, and marks them as saved
+    ///
+    /// Keys keep the nesting they had in the loaded file, new keys are
+    /// nested if the file has any nested objects
+    pub fn take_dirty_files(&mut self) -> miette::Result<Vec<(Utf8PathBuf, Vec<u8>)>> {
+        let mut out = vec![];
+        for (path, file) in self
+            .locales
+            .values_mut()
+            .flat_map(|locale| locale.files.iter_mut())
+            .filter(|(_, file)| file.dirty)
+        {
+            update_layout(&mut file.layout, &file.strings);
+            let data = match file.format {
+                LocaleFormat::Json => {
+                    let mut buf = vec![];
+                    let mut serializer = serde_json::ser::Serializer::with_formatter(
+                        &mut buf,
+                        DBEJsonFormatter::pretty(),
+                    );
+                    file.layout.serialize(&mut serializer).into_diagnostic()?;
+                    buf
+                }
+                LocaleFormat::Toml => toml::to_string(&file.layout)
+                    .into_diagnostic()?
+                    .into_bytes(),
+            };
+            file.dirty = false;
+            out.push((path.clone(), data));
+        }
+
+        Ok(out)
+    }
+}
+
+/// Updates the loaded file content to hold exactly the given translations
+fn update_layout(layout: &mut JsonMap<String, JsonValue>, strings: &BTreeMap<String, String>) {
+    retain_strings(layout, "", strings);
+    let nested = layout.values().any(|value| value.is_object());
+    for (key, text) in strings {
+        set_nested(layout, key, text, nested);
+    }
+}
+
+/// Removes translations that are no longer present, along with the objects
+/// that became empty
+fn retain_strings(
+    obj: &mut JsonMap<String, JsonValue>,
+    prefix: &str,
+    strings: &BTreeMap<String, String>,
+) {
+    obj.retain(|key, value| {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            JsonValue::Object(inner) if !inner.is_empty() => {
+                retain_strings(inner, &key, strings);
+                !inner.is_empty()
+            }
+            _ => strings.contains_key(&key),
+        }
+    });
+}
+
+/// Sets the translation in the object holding it, or adds it to the deepest
+/// existing object matching the key
+fn set_nested(obj: &mut JsonMap<String, JsonValue>, key: &str, text: &str, nested: bool) {
+    if let Some(JsonValue::String(value)) = obj.get_mut(key) {
+        if value != text {
+            *value = text.to_string();
+        }
+        return;
+    }
+
+    for (i, _) in key.match_indices('.') {
+        if let Some(JsonValue::Object(inner)) = obj.get_mut(&key[..i]) {
+            set_nested(inner, &key[i + 1..], text, nested);
+            return;
+        }
+    }
+
+    match key.split_once('.') {
+        Some((head, tail)) if nested && !obj.contains_key(head) => {
+            let mut inner = JsonMap::new();
+            set_nested(&mut inner, tail, text, nested);
+            obj.insert(head.to_string(), JsonValue::Object(inner));
+        }
+        _ => {
+            obj.insert(key.to_string(), JsonValue::String(text.to_string()));
+        }
+    }
+}
+
+fn flatten_strings(
+    out: &mut BTreeMap<String, String>,
+    prefix: String,
+    value: JsonValue,
+) -> miette::Result<()> {
+    match value {
+        JsonValue::String(text) => {
+            if prefix.is_empty() {
+                bail!("translation file must be an object");
+            }
+            out.insert(prefix, text);
+        }
+        JsonValue::Object(obj) => {
+            for (key, value) in obj {
+                let key = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_strings(out, key, value)?;
+            }
+        }
+        other if prefix.is_empty() => {
+            bail!(
+                "translation file must be an object, but got {}",
+                json_kind(&other)
+            );
+        }
+        other => {
+            bail!(
+                "translation for key `{}` must be a string, but got {}",
+                prefix,
+                json_kind(&other)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_keep_nested_layout() {
+        let mut localization = Localization::default();
+        let locale = Ustr::from("en");
+        localization
+            .add_file(
+                "en.locale.json".into(),
+                locale,
+                LocaleFormat::Json,
+                r#"{"menu": {"file": "File", "edit": "Edit"}, "flat.key": "Flat"}"#,
+            )
+            .unwrap();
+
+        localization
+            .set(locale, "menu.file", "Files".into())
+            .unwrap();
+        localization.set(locale, "dialog.ok", "OK".into()).unwrap();
+        localization
+            .restore("en.locale.json".as_ref(), locale, "menu.edit", None)
+            .unwrap();
+
+        let files = localization.take_dirty_files().unwrap();
+        let saved: JsonValue = serde_json::from_slice(&files[0].1).unwrap();
+        assert_eq!(
+            saved,
+            json!({
+                "menu": {"file": "Files"},
+                "dialog": {"ok": "OK"},
+                "flat.key": "Flat",
+            })
+        );
+        assert_eq!(localization.take_edits().len(), 2);
+    }
+}
@@ -0,0 +1,434 @@
+//! Prefab inheritance between item files
+//!
+//! An item file may declare another file as its base via the `$extends`
+//! field, holding the base path relative to the project root:
+//!
+//! ```json
+//! {
+//!   "$extends": "Component/BaseShield.json",
+//!   "Id": 85,
+//!   "Level": 20
+//! }
+//! ```
+//!
+//! On load, the top-level fields of the file override the fields of the base,
+//! and the merged JSON is parsed as usual. Bases may extend other files in
+//! turn. The overridden fields are tracked per file, the remaining fields
+//! follow the base when it changes. On save, only the overridden fields are
+//! written back.
+//!
+//! Each file is upgraded with the migrations of its type before it is merged,
+//! and the `$version` field is always written back along with the overrides.
+
+use crate::etype::migration::VERSION_FIELD;
+use crate::json_utils::{json_kind, JsonMap, JsonValue};
+use crate::value::EValue;
+use camino::{Utf8Path, Utf8PathBuf};
+use miette::{bail, Context};
+use std::collections::BTreeSet;
+
+/// Name of the JSON field holding the path of the prefab base
+pub const EXTENDS_FIELD: &str = "$extends";
+
+/// Prefab base of a file, along with the fields the file overrides
+#[derive(Debug, Clone)]
+pub struct Prefab {
+    /// Path of the base file
+    pub base: Utf8PathBuf,
+    /// Top-level fields overriding the fields of the base
+    pub overrides: BTreeSet<String>,
+}
+
+impl Prefab {
+    /// Creates the prefab of the raw item JSON extending the base
+    pub fn new(base: Utf8PathBuf, json: &JsonValue) -> Self {
+        let overrides = json
+            .as_object()
+            .map(|obj| {
+                obj.keys()
+                    .filter(|key| *key != EXTENDS_FIELD && *key != VERSION_FIELD)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { base, overrides }
+    }
+
+    /// Copies the inherited top-level fields of the base into the value,
+    /// returning whether the value was changed
+    pub fn merge_inherited(&self, base: &EValue, value: &mut EValue) -> bool {
+        let (EValue::Struct { fields: base, .. }, EValue::Struct { fields, .. }) = (base, value)
+        else {
+            return false;
+        };
+
+        let mut changed = false;
+        for (key, base_value) in base {
+            if self.overrides.contains(key.as_str()) {
+                continue;
+            }
+            if fields.get(key) != Some(base_value) {
+                fields.insert(*key, base_value.clone());
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Returns the overridden fields along with the fields of the value
+    /// that differ from the base
+    pub fn overridden_fields(&self, base: &EValue, value: &EValue) -> BTreeSet<String> {
+        let mut overrides = self.overrides.clone();
+        if let (EValue::Struct { fields: base, .. }, EValue::Struct { fields, .. }) = (base, value)
+        {
+            overrides.extend(
+                fields
+                    .iter()
+                    .filter(|(key, value)| base.get(*key) != Some(*value))
+                    .map(|(key, _)| key.to_string()),
+            );
+        }
+        overrides
+    }
+
+    /// Updates the overridden fields after the value was edited
+    ///
+    /// Fields that differ from the base become overrides, and fields that
+    /// are equal to the base are inherited again
+    pub fn track_overrides(&mut self, base: &EValue, value: &EValue) {
+        let (EValue::Struct { fields: base, .. }, EValue::Struct { fields, .. }) = (base, value)
+        else {
+            return;
+        };
+
+        for (key, value) in fields {
+            if base.get(key) == Some(value) {
+                self.overrides.remove(key.as_str());
+            } else {
+                self.overrides.insert(key.to_string());
+            }
+        }
+    }
+}
+
+/// Returns the prefab base declared by the item JSON
+pub fn extends_of(json: &JsonValue) -> miette::Result<Option<Utf8PathBuf>> {
+    let Some(base) = json.as_object().and_then(|obj| obj.get(EXTENDS_FIELD)) else {
+        return Ok(None);
+    };
+
+    let Some(base) = base.as_str() else {
+        bail!(
+            "`{}` field should be a string, but got {}",
+            EXTENDS_FIELD,
+            json_kind(base)
+        );
+    };
+
+    Ok(Some(Utf8PathBuf::from(base.replace('\\', "/"))))
+}
+
+/// Merges the item JSON with all of its prefab bases, returning the prefab
+/// along with the merged JSON, or `None` if the item doesn't extend any file
+///
+/// `lookup` should return the raw JSON of the file at the given path.
+/// `migrate` should upgrade the top-level object of the file JSON, given the
+/// merged JSON of its base, see [migrate_prefab]. Every file is upgraded
+/// before it is merged, so fields saved with different versions are not
+/// mixed
+///
+/// [migrate_prefab]: crate::etype::migration::migrate_prefab
+pub fn resolve_prefab<'a>(
+    path: &Utf8Path,
+    json: &JsonValue,
+    lookup: impl Fn(&Utf8Path) -> Option<&'a JsonValue>,
+    migrate: impl Fn(&Utf8Path, &mut JsonValue, Option<&JsonMap>) -> miette::Result<Option<u32>>,
+) -> miette::Result<Option<(Prefab, JsonValue)>> {
+    let Some(base) = extends_of(json)? else {
+        return Ok(None);
+    };
+
+    let resolved = resolve_inner(path, json, &lookup, &migrate, &mut vec![])?;
+    Ok(Some((Prefab::new(base, &resolved.own), resolved.merged)))
+}
+
+struct Resolved {
+    /// Upgraded JSON of the file itself
+    own: JsonValue,
+    /// Upgraded JSON of the file merged with its bases
+    merged: JsonValue,
+    version: Option<u32>,
+}
+
+fn resolve_inner<'a>(
+    path: &Utf8Path,
+    json: &JsonValue,
+    lookup: &impl Fn(&Utf8Path) -> Option<&'a JsonValue>,
+    migrate: &impl Fn(&Utf8Path, &mut JsonValue, Option<&JsonMap>) -> miette::Result<Option<u32>>,
+    stack: &mut Vec<Utf8PathBuf>,
+) -> miette::Result<Resolved> {
+    let mut own = json.clone();
+    let Some(base_path) = extends_of(json)? else {
+        let version = migrate(path, &mut own, None)?;
+        return Ok(Resolved {
+            merged: own.clone(),
+            own,
+            version,
+        });
+    };
+
+    if stack.iter().any(|p| p == path) {
+        bail!(
+            "circular prefab inheritance: {} -> {}",
+            stack
+                .iter()
+                .map(|p| p.as_str())
+                .collect::<Vec<_>>()
+                .join(" -> "),
+            path
+        );
+    }
+
+    let Some(base) = lookup(&base_path) else {
+        bail!("prefab base `{}` is not found", base_path);
+    };
+
+    stack.push(path.to_path_buf());
+    let base = resolve_inner(&base_path, base, lookup, migrate, stack)
+        .with_context(|| format!("failed to resolve prefab base `{}`", base_path))?;
+    stack.pop();
+
+    let JsonValue::Object(mut merged) = base.merged else {
+        bail!(
+            "prefab base `{}` should be an object, but got {}",
+            base_path,
+            json_kind(&base.merged)
+        );
+    };
+
+    let version = migrate(path, &mut own, Some(&merged))?;
+    if version != base.version {
+        bail!(
+            help = "node group migrations only run on the merged value, so the base and the file must be upgraded past them together",
+            "prefab base `{}` is at data version {}, but the file is at version {}",
+            base_path,
+            base.version.unwrap_or_default(),
+            version.unwrap_or_default()
+        );
+    }
+
+    let overrides = own.as_object().expect("extending JSON should be an object");
+    for (key, value) in overrides {
+        if key != EXTENDS_FIELD {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    Ok(Resolved {
+        own,
+        merged: merged.into(),
+        version,
+    })
+}
+
+/// Keeps only the overridden top-level fields, and adds the `$extends` and
+/// `$version` fields
+///
+/// Overrides are taken from the full form, with all default values written
+/// out, since a field that is omitted from the compact form would otherwise be
+/// inherited from the base. Fields are written in the compact form when
+/// possible
+pub fn diff_prefab(
+    base_path: &Utf8Path,
+    overrides: &BTreeSet<String>,
+    full: &JsonValue,
+    compact: JsonValue,
+) -> JsonValue {
+    let Some(full) = full.as_object() else {
+        return compact;
+    };
+    let compact = match compact {
+        JsonValue::Object(compact) => compact,
+        other => return other,
+    };
+
+    let mut diff = JsonMap::new();
+    diff.insert(EXTENDS_FIELD.to_string(), base_path.as_str().into());
+    // The version is written even though the base has the same one, so the
+    // overrides are upgraded from the right version once the base is ahead
+    if let Some(version) = full.get(VERSION_FIELD) {
+        diff.insert(VERSION_FIELD.to_string(), version.clone());
+    }
+    for (key, value) in full {
+        if overrides.contains(key) {
+            let value = compact.get(key).unwrap_or(value).clone();
+            diff.insert(key.clone(), value);
+        }
+    }
+
+    diff.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_prefab, resolve_prefab, Prefab, EXTENDS_FIELD};
+    use crate::etype::migration::{migrate_prefab, VERSION_FIELD};
+    use crate::etype::EDataType;
+    use crate::json_utils::JsonValue;
+    use crate::project::io::embedded_registry;
+    use crate::value::id::ETypeId;
+    use crate::value::{estruct, EValue};
+    use camino::{Utf8Path, Utf8PathBuf};
+    use rstest::rstest;
+    use serde_json::json;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn files(files: &[(&str, JsonValue)]) -> BTreeMap<Utf8PathBuf, JsonValue> {
+        files
+            .iter()
+            .map(|(path, json)| (Utf8PathBuf::from(*path), json.clone()))
+            .collect()
+    }
+
+    fn resolve(files: &BTreeMap<Utf8PathBuf, JsonValue>, path: &str) -> miette::Result<JsonValue> {
+        let path = Utf8Path::new(path);
+        let (_, merged) = resolve_prefab(
+            path,
+            &files[path],
+            |path| files.get(path),
+            |_, _, _| Ok(None),
+        )?
+        .expect("file should extend a base");
+        Ok(merged)
+    }
+
+    #[test]
+    fn should_merge_base_chain() {
+        let files = files(&[
+            ("base.json", json!({"a": 1, "b": 1, "c": 1})),
+            ("mid.json", json!({EXTENDS_FIELD: "base.json", "b": 2})),
+            ("item.json", json!({EXTENDS_FIELD: "mid.json", "c": 3})),
+        ]);
+
+        assert_eq!(
+            resolve(&files, "item.json").unwrap(),
+            json!({"a": 1, "b": 2, "c": 3})
+        );
+    }
+
+    #[rstest]
+    #[case::base_behind(
+        json!({"Speed": 1}),
+        json!({EXTENDS_FIELD: "base.json", VERSION_FIELD: 1, "MaxSpeed": 2}),
+        json!({VERSION_FIELD: 1, "MaxSpeed": 2, "Armor": 5})
+    )]
+    #[case::file_behind(
+        json!({VERSION_FIELD: 1, "MaxSpeed": 1, "Armor": 3}),
+        json!({EXTENDS_FIELD: "base.json", "Speed": 2}),
+        json!({VERSION_FIELD: 1, "MaxSpeed": 2, "Armor": 3})
+    )]
+    fn should_migrate_before_merging(
+        #[case] base: JsonValue,
+        #[case] item: JsonValue,
+        #[case] expected: JsonValue,
+    ) {
+        let ident = ETypeId::from_raw("test:ship".into());
+        let kdl = r#"struct version=1 { number "MaxSpeed"; number "Armor" }"#.to_string();
+        let mut registry = embedded_registry([(ident, kdl)]).unwrap();
+        registry.extra_config_mut("migrations".to_string()).push((
+            "types.json5".into(),
+            json!({"types": {"test:ship": [
+                {"version": 1, "steps": [
+                    {"op": "rename", "from": "Speed", "to": "MaxSpeed"},
+                    {"op": "set_default", "field": "Armor", "value": 5},
+                ]},
+            ]}}),
+        ));
+        let files = files(&[("base.json", base), ("item.json", item)]);
+
+        let path = Utf8Path::new("item.json");
+        let (prefab, merged) = resolve_prefab(
+            path,
+            &files[path],
+            |path| files.get(path),
+            |_, json, base| migrate_prefab(&registry, EDataType::Object { ident }, json, base),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(merged, expected);
+        assert_eq!(prefab.overrides, BTreeSet::from(["MaxSpeed".to_string()]));
+    }
+
+    #[test]
+    fn should_fail_circular_chain() {
+        let files = files(&[
+            ("a.json", json!({EXTENDS_FIELD: "b.json"})),
+            ("b.json", json!({EXTENDS_FIELD: "a.json"})),
+        ]);
+
+        assert!(resolve(&files, "a.json").is_err());
+    }
+
+    #[test]
+    fn should_fail_missing_base() {
+        let files = files(&[("a.json", json!({EXTENDS_FIELD: "missing.json"}))]);
+
+        assert!(resolve(&files, "a.json").is_err());
+    }
+
+    #[test]
+    fn should_write_version() {
+        let overrides = BTreeSet::from(["b".to_string()]);
+        let full = json!({VERSION_FIELD: 2, "a": 1, "b": 0});
+
+        assert_eq!(
+            diff_prefab(Utf8Path::new("base.json"), &overrides, &full, full.clone()),
+            json!({EXTENDS_FIELD: "base.json", VERSION_FIELD: 2, "b": 0})
+        );
+    }
+
+    #[test]
+    fn should_write_only_overrides() {
+        let prefab = Prefab::new(
+            "base.json".into(),
+            &json!({EXTENDS_FIELD: "base.json", "b": 0}),
+        );
+        let full = json!({"a": 1, "b": 0, "c": {"d": 0}});
+        let compact = json!({"a": 1, "c": {}});
+
+        assert_eq!(
+            diff_prefab(&prefab.base, &prefab.overrides, &full, compact),
+            json!({EXTENDS_FIELD: "base.json", "b": 0})
+        );
+    }
+
+    #[test]
+    fn should_follow_base_changes() {
+        let ident = ETypeId::from_raw("test:item".into());
+        let mut prefab = Prefab::new(
+            "base.json".into(),
+            &json!({EXTENDS_FIELD: "base.json", "b": 2}),
+        );
+        let mut value = estruct!(ident { "a": 1.0, "b": 2.0 });
+
+        // inherited field follows the base, overridden one is kept
+        let base = estruct!(ident { "a": 5.0, "b": 1.0 });
+        assert!(prefab.merge_inherited(&base, &mut value));
+        assert_eq!(value, estruct!(ident { "a": 5.0, "b": 2.0 }));
+
+        // edited field becomes an override, reverted one is inherited again
+        let edited = estruct!(ident { "a": 6.0, "b": 1.0 });
+        prefab.track_overrides(&base, &edited);
+        assert_eq!(prefab.overrides, BTreeSet::from(["a".to_string()]));
+        assert_eq!(
+            diff_prefab(
+                &prefab.base,
+                &prefab.overrides,
+                &json!({"a": 6, "b": 1}),
+                json!({"a": 6, "b": 1})
+            ),
+            json!({EXTENDS_FIELD: "base.json", "a": 6})
+        );
+    }
+}
@@ -3,7 +3,7 @@ use crate::graph::Graph;
 use crate::json_utils::JsonValue;
 use crate::project::ProjectFile;
 use crate::registry::ETypesRegistry;
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use miette::{bail, Context, IntoDiagnostic};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
@@ -225,6 +225,14 @@ impl ProjectGraphs {
         result
     }
 
+    /// Finds the graph loaded from the given path
+    pub fn graph_by_path(&self, path: &Utf8Path) -> Option<&ProjectGraph> {
+        self.paths
+            .iter()
+            .find(|(_, p)| p.as_path() == path)
+            .and_then(|(id, _)| self.graphs.get(id))
+    }
+
     pub fn insert_new_graph(&mut self) -> Uuid {
         let id = loop {
             let id = Uuid::new_v4();
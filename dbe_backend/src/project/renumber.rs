@@ -0,0 +1,606 @@
+//! Renumbering of the numeric IDs across the project
+//!
+//! Renumbering moves the `ids/numeric` definitions of a single ID category to
+//! new IDs, and rewrites every `ids/numeric_ref` pointing at them. Mappings
+//! don't record the category of their IDs, so only the `sys:mappings/storage`
+//! files selected by the caller are rewritten, including their ID ranges.
+//! The changes can be previewed first, and are recorded into the undo
+//! history as a single change set once applied.
+
+use crate::etype::computed::update_computed;
+use crate::json_utils::repr::JsonRepr;
+use crate::m_try;
+use crate::project::io::ProjectIO;
+use crate::project::side_effects::mappings::{RANGE_ID, STORAGE_ID};
+use crate::project::{Project, ProjectFile};
+use crate::registry::ETypesRegistry;
+use crate::validation::ids::extract_generic_arg;
+use crate::validation::ids::numeric::NumericIDRegistry;
+use crate::value::{ENumber, EValue};
+use camino::Utf8PathBuf;
+use itertools::Itertools;
+use miette::{bail, Context};
+use ordered_float::OrderedFloat;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+use ustr::Ustr;
+
+/// New IDs of the renumbered category
+#[derive(Debug, Clone)]
+pub enum IdRenumbering {
+    /// Explicit mapping of the old IDs to the new ones
+    Mapping(BTreeMap<ENumber, ENumber>),
+    /// Shifts the IDs within the range by the offset
+    Offset {
+        range: RangeInclusive<ENumber>,
+        offset: ENumber,
+    },
+}
+
+impl IdRenumbering {
+    /// Returns the new ID for the old one, or `None` if the ID is kept
+    pub fn new_id(&self, id: ENumber) -> Option<ENumber> {
+        match self {
+            IdRenumbering::Mapping(mapping) => mapping.get(&id).copied(),
+            IdRenumbering::Offset { range, offset } => {
+                range.contains(&id).then(|| OrderedFloat(id.0 + offset.0))
+            }
+        }
+    }
+}
+
+/// Changes made by the renumbering
+#[derive(Debug, Clone, Default)]
+pub struct RenumberReport {
+    /// Renumbered IDs, from old to new
+    pub ids: BTreeMap<ENumber, ENumber>,
+    /// Rewritten files
+    pub files: BTreeMap<Utf8PathBuf, RenumberedFile>,
+    /// Generated files with the renumbered IDs
+    ///
+    /// Generated files are not rewritten directly, they pick up the new IDs
+    /// from the updated mappings once their graphs are evaluated again
+    pub generated: Vec<Utf8PathBuf>,
+}
+
+impl Display for RenumberReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Renumbered {} IDs in {} files",
+            self.ids.len(),
+            self.files.len()
+        )?;
+        for (path, file) in &self.files {
+            write!(
+                f,
+                "\n{}: {} definitions, {} references, {} mappings, {} ranges",
+                path, file.definitions, file.references, file.mappings, file.ranges
+            )?;
+        }
+        if !self.generated.is_empty() {
+            write!(
+                f,
+                "\nGenerated files are updated on the next graph evaluation: {}",
+                self.generated.iter().join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of the rewritten values of a single file
+#[derive(Debug, Clone, Default)]
+pub struct RenumberedFile {
+    pub definitions: usize,
+    pub references: usize,
+    pub mappings: usize,
+    pub ranges: usize,
+}
+
+impl RenumberedFile {
+    fn is_empty(&self) -> bool {
+        self.definitions == 0 && self.references == 0 && self.mappings == 0 && self.ranges == 0
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum IdKind {
+    Definition,
+    Reference,
+}
+
+/// Renumbers the IDs of the category, see [Project::renumber_ids]
+///
+/// Only the `mappings` files are checked for the mapped IDs. When `apply` is
+/// false, the project is left unchanged and only the report is produced
+pub(super) fn renumber_ids<IO: ProjectIO>(
+    project: &mut Project<IO>,
+    category: Ustr,
+    renumbering: &IdRenumbering,
+    mappings: &BTreeSet<Utf8PathBuf>,
+    apply: bool,
+) -> miette::Result<RenumberReport> {
+    for path in mappings {
+        match project.files.get(path) {
+            Some(ProjectFile::Value(EValue::Struct { ident, .. })) if *ident == *STORAGE_ID => {}
+            Some(_) => bail!("file `{}` is not a mappings file", path),
+            None => bail!("mappings file `{}` doesn't exist", path),
+        }
+    }
+
+    // IDs are collected during validation, so make sure they are up to date
+    project.revalidate()?;
+
+    let ids = NumericIDRegistry::of(&project.registry);
+    let defined = ids.used_ids(category);
+    let renumbered: BTreeMap<ENumber, ENumber> = defined
+        .iter()
+        .filter_map(|id| Some((*id, renumbering.new_id(*id)?)))
+        .filter(|(old, new)| old != new)
+        .collect();
+    if renumbered.is_empty() {
+        bail!("no IDs of category `{}` match the renumbering", category);
+    }
+
+    let kept: BTreeSet<ENumber> = defined
+        .iter()
+        .filter(|id| !renumbered.contains_key(id))
+        .copied()
+        .collect();
+    check_new_ids(&renumbered, &kept, &ids.blocked_ids(category)?)?;
+
+    // Categories of the references that can point at the renumbered IDs
+    let mut assignable = BTreeMap::new();
+    let mut is_assignable = |ty: Ustr| -> miette::Result<bool> {
+        if let Some(matches) = assignable.get(&ty) {
+            return Ok(*matches);
+        }
+        let matches = ids.is_id_assignable(category, ty)?;
+        assignable.insert(ty, matches);
+        Ok(matches)
+    };
+
+    let mut report = RenumberReport {
+        ids: renumbered.clone(),
+        ..Default::default()
+    };
+    let mut changed = vec![];
+    for (path, file) in &project.files {
+        let (ProjectFile::Value(value) | ProjectFile::GeneratedValue(value)) = file else {
+            continue;
+        };
+
+        let mut value = value.clone();
+        let mut stats = renumber_value(
+            &project.registry,
+            &mut value,
+            category,
+            &renumbered,
+            &mut is_assignable,
+        )
+        .with_context(|| format!("failed to renumber IDs in `{}`", path))?;
+        if mappings.contains(path) {
+            (stats.mappings, stats.ranges) =
+                renumber_mappings(&mut value, &renumbered, renumbering)
+                    .with_context(|| format!("failed to renumber mappings in `{}`", path))?;
+        }
+
+        if stats.is_empty() {
+            continue;
+        }
+
+        if file.is_generated() {
+            report.generated.push(path.clone());
+            continue;
+        }
+
+        if !project.io.is_file_writable(path)? {
+            bail!("file `{}` with renumbered IDs is read-only", path);
+        }
+        report.files.insert(path.clone(), stats);
+        changed.push((path.clone(), value));
+    }
+
+    if !apply {
+        return Ok(report);
+    }
+
+    project
+        .history
+        .begin_group(&project.files, &project.graphs)?;
+    let result = m_try(|| {
+        for (path, mut value) in changed {
+            project
+                .history
+                .ensure_file_state(&project.files, &project.graphs, &path)?;
+            update_computed(&project.registry, Some(&project.graphs), &mut value);
+            project
+                .files
+                .insert(path.clone(), ProjectFile::Value(value));
+            project
+                .history
+                .check_file(&project.files, &project.graphs, &path, true)?;
+        }
+        Ok(())
+    });
+    project.history.end_group(&project.files, &project.graphs)?;
+    result?;
+
+    project.revalidate()?;
+
+    Ok(report)
+}
+
+/// Checks that the new IDs are integers that are not used by the kept or
+/// other renumbered IDs, and not blocked for the category
+fn check_new_ids(
+    renumbered: &BTreeMap<ENumber, ENumber>,
+    kept: &BTreeSet<ENumber>,
+    blocked: &BTreeSet<ENumber>,
+) -> miette::Result<()> {
+    let mut taken = BTreeSet::new();
+    for (old, new) in renumbered {
+        if new.0.fract() != 0.0 {
+            bail!("new ID {} for ID {} is not an integer", new, old);
+        }
+        if kept.contains(new) || !taken.insert(*new) {
+            bail!("new ID {} for ID {} is already used", new, old);
+        }
+        if blocked.contains(new) {
+            bail!(
+                "new ID {} for ID {} is reserved or used by a conflicting category",
+                new,
+                old
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Rewrites the renumbered IDs of the category inside the value
+///
+/// References are rewritten only when `is_assignable` accepts their
+/// category, definitions only when they belong to the renumbered category
+fn renumber_value(
+    registry: &ETypesRegistry,
+    value: &mut EValue,
+    category: Ustr,
+    renumbered: &BTreeMap<ENumber, ENumber>,
+    is_assignable: &mut impl FnMut(Ustr) -> miette::Result<bool>,
+) -> miette::Result<RenumberedFile> {
+    let mut stats = RenumberedFile::default();
+    for_each_id_mut(registry, value, &mut |kind, ty, id| {
+        let Some(new) = renumbered.get(id) else {
+            return Ok(());
+        };
+        match kind {
+            IdKind::Definition if ty == category => {
+                stats.definitions += 1;
+            }
+            IdKind::Reference => {
+                if !is_assignable(ty)? {
+                    return Ok(());
+                }
+                stats.references += 1;
+            }
+            IdKind::Definition => return Ok(()),
+        }
+        *id = *new;
+        Ok(())
+    })?;
+
+    Ok(stats)
+}
+
+/// Rewrites the renumbered IDs stored in the mappings and moves the ID
+/// ranges covered by the renumbering, returning the number of rewritten
+/// mapping entries and ranges
+///
+/// Ranges are moved only when both of their ends are shifted by the same
+/// offset, partially renumbered ranges are rejected
+fn renumber_mappings(
+    value: &mut EValue,
+    renumbered: &BTreeMap<ENumber, ENumber>,
+    renumbering: &IdRenumbering,
+) -> miette::Result<(usize, usize)> {
+    let EValue::Struct { ident, fields } = value else {
+        return Ok((0, 0));
+    };
+    if *ident != *STORAGE_ID {
+        return Ok((0, 0));
+    }
+
+    let mut mappings = 0;
+    if let Some(EValue::Map { values, .. }) = fields.get_mut(&Ustr::from("values")) {
+        for value in values.values_mut() {
+            let EValue::Number { value } = value else {
+                continue;
+            };
+            if let Some(new) = renumbered.get(value) {
+                *value = *new;
+                mappings += 1;
+            }
+        }
+    }
+
+    let mut ranges = 0;
+    if let Some(EValue::List { values, .. }) = fields.get_mut(&Ustr::from("ranges")) {
+        for range in values {
+            let EValue::Struct { ident, fields } = range else {
+                continue;
+            };
+            if *ident != *RANGE_ID {
+                continue;
+            }
+            let (Some(EValue::Number { value: start }), Some(EValue::Number { value: end })) = (
+                fields.get(&Ustr::from("start")),
+                fields.get(&Ustr::from("end")),
+            ) else {
+                continue;
+            };
+            let (start, end) = (*start, *end);
+            let (new_start, new_end) = match (renumbering.new_id(start), renumbering.new_id(end)) {
+                (None, None) if !overlaps(renumbering, start..=end) => continue,
+                (Some(new_start), Some(new_end)) if new_start.0 - start.0 == new_end.0 - end.0 => {
+                    (new_start, new_end)
+                }
+                _ => bail!("ID range {}..={} is only partially renumbered", start, end),
+            };
+            fields.insert("start".into(), EValue::Number { value: new_start });
+            fields.insert("end".into(), EValue::Number { value: new_end });
+            ranges += 1;
+        }
+    }
+
+    Ok((mappings, ranges))
+}
+
+/// Checks whether the renumbering moves any ID inside the range
+fn overlaps(renumbering: &IdRenumbering, range: RangeInclusive<ENumber>) -> bool {
+    match renumbering {
+        IdRenumbering::Mapping(mapping) => mapping.keys().any(|id| range.contains(id)),
+        IdRenumbering::Offset { range: moved, .. } => {
+            moved.start() <= range.end() && range.start() <= moved.end()
+        }
+    }
+}
+
+/// Calls the provided function for the ID of every numeric ID definition and
+/// reference inside the value, along with the ID category
+fn for_each_id_mut(
+    registry: &ETypesRegistry,
+    value: &mut EValue,
+    func: &mut impl FnMut(IdKind, Ustr, &mut ENumber) -> miette::Result<()>,
+) -> miette::Result<()> {
+    match value {
+        EValue::Null | EValue::Boolean { .. } | EValue::Number { .. } | EValue::String { .. } => {}
+        EValue::Struct { ident, fields } => {
+            let kind = registry
+                .get_struct(ident)
+                .and_then(|data| match data.repr.as_ref()?.id() {
+                    "ids/numeric" => Some(IdKind::Definition),
+                    "ids/numeric_ref" => Some(IdKind::Reference),
+                    _ => None,
+                });
+            if let Some(kind) = kind {
+                let category = extract_generic_arg(registry, ident)?;
+                if let Some(EValue::Number { value }) = fields.get_mut(&Ustr::from("id")) {
+                    func(kind, category, value)?;
+                }
+                return Ok(());
+            }
+            for field in fields.values_mut() {
+                for_each_id_mut(registry, field, func)?;
+            }
+        }
+        EValue::Enum { data, .. } => for_each_id_mut(registry, data, func)?,
+        EValue::List { values, .. } | EValue::Tuple { values, .. } => {
+            for item in values {
+                for_each_id_mut(registry, item, func)?;
+            }
+        }
+        EValue::Map { values, .. } => {
+            for item in values.values_mut() {
+                for_each_id_mut(registry, item, func)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_new_ids, renumber_mappings, renumber_value, IdRenumbering};
+    use crate::etype::EDataType;
+    use crate::json_utils::JsonValue;
+    use crate::project::io::embedded_registry;
+    use crate::project::side_effects::mappings::STORAGE_ID;
+    use crate::registry::ETypesRegistry;
+    use crate::value::id::ETypeId;
+    use crate::value::{ENumber, EValue};
+    use ordered_float::OrderedFloat;
+    use rstest::rstest;
+    use serde_json::json;
+    use std::collections::{BTreeMap, BTreeSet};
+    use ustr::Ustr;
+
+    fn mapping(ids: &[(f64, f64)]) -> IdRenumbering {
+        IdRenumbering::Mapping(
+            ids.iter()
+                .map(|(old, new)| (OrderedFloat(*old), OrderedFloat(*new)))
+                .collect(),
+        )
+    }
+
+    fn offset(start: f64, end: f64, offset: f64) -> IdRenumbering {
+        IdRenumbering::Offset {
+            range: OrderedFloat(start)..=OrderedFloat(end),
+            offset: OrderedFloat(offset),
+        }
+    }
+
+    fn ids(ids: &[f64]) -> BTreeSet<ENumber> {
+        ids.iter().copied().map(OrderedFloat).collect()
+    }
+
+    fn renumbered(renumbering: &IdRenumbering, used: &[f64]) -> BTreeMap<ENumber, ENumber> {
+        ids(used)
+            .into_iter()
+            .filter_map(|id| Some((id, renumbering.new_id(id)?)))
+            .collect()
+    }
+
+    fn parse(registry: &ETypesRegistry, ident: ETypeId, mut json: JsonValue) -> EValue {
+        EDataType::Object { ident }
+            .parse_json(registry, &mut json, false)
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case::mapped(mapping(&[(1.0, 5.0)]), 1.0, Some(5.0))]
+    #[case::not_mapped(mapping(&[(1.0, 5.0)]), 2.0, None)]
+    #[case::offset(offset(1.0, 3.0, 10.0), 3.0, Some(13.0))]
+    #[case::outside_offset(offset(1.0, 3.0, 10.0), 4.0, None)]
+    fn should_get_new_id(
+        #[case] renumbering: IdRenumbering,
+        #[case] id: f64,
+        #[case] expected: Option<f64>,
+    ) {
+        assert_eq!(
+            renumbering.new_id(OrderedFloat(id)),
+            expected.map(OrderedFloat)
+        );
+    }
+
+    #[rstest]
+    #[case::free(&[(1.0, 5.0), (2.0, 6.0)], true)]
+    #[case::swapped(&[(1.0, 2.0), (2.0, 1.0)], true)]
+    #[case::kept(&[(1.0, 3.0)], false)]
+    #[case::taken_twice(&[(1.0, 5.0), (2.0, 5.0)], false)]
+    #[case::reserved(&[(1.0, 7.0)], false)]
+    #[case::not_integer(&[(1.0, 5.5)], false)]
+    fn should_check_new_ids(#[case] renumbered: &[(f64, f64)], #[case] valid: bool) {
+        let renumbered = renumbered
+            .iter()
+            .map(|(old, new)| (OrderedFloat(*old), OrderedFloat(*new)))
+            .collect();
+        let result = check_new_ids(&renumbered, &ids(&[3.0]), &ids(&[7.0]));
+        assert_eq!(result.is_ok(), valid, "{:?}", result);
+    }
+
+    #[rstest]
+    #[case::offset(
+        offset(1.0, 10.0, 100.0),
+        &[(1.0, 10.0), (20.0, 30.0)],
+        &[(101.0, 110.0), (20.0, 30.0)]
+    )]
+    #[case::mapping(mapping(&[(1.0, 11.0), (2.0, 12.0)]), &[(1.0, 2.0)], &[(11.0, 12.0)])]
+    fn should_move_ranges(
+        #[case] renumbering: IdRenumbering,
+        #[case] ranges: &[(f64, f64)],
+        #[case] expected: &[(f64, f64)],
+    ) {
+        let registry = embedded_registry([]).unwrap();
+        let to_json = |ranges: &[(f64, f64)]| {
+            ranges
+                .iter()
+                .map(|(start, end)| json!({"start": start, "end": end}))
+                .collect::<Vec<_>>()
+        };
+        let mut value = parse(
+            &registry,
+            *STORAGE_ID,
+            json!({"values": {"a": 1, "b": 20}, "ranges": to_json(ranges)}),
+        );
+
+        let renumbered = renumbered(&renumbering, &[1.0, 2.0, 20.0]);
+        let (mappings, moved) = renumber_mappings(&mut value, &renumbered, &renumbering).unwrap();
+
+        let new_a = renumbering.new_id(OrderedFloat(1.0)).unwrap().0;
+        assert_eq!((mappings, moved), (1, 1));
+        assert_eq!(
+            value.write_json(&registry).unwrap(),
+            json!({"values": {"a": new_a, "b": 20.0}, "ranges": to_json(expected)})
+        );
+    }
+
+    #[rstest]
+    #[case::start_only(offset(1.0, 5.0, 100.0), (1.0, 10.0))]
+    #[case::end_only(offset(5.0, 10.0, 100.0), (1.0, 5.0))]
+    #[case::inside_offset(offset(3.0, 5.0, 100.0), (1.0, 10.0))]
+    #[case::inside_mapping(mapping(&[(5.0, 50.0)]), (1.0, 10.0))]
+    #[case::different_offsets(mapping(&[(1.0, 2.0), (2.0, 1.0)]), (1.0, 2.0))]
+    fn should_fail_partially_renumbered_range(
+        #[case] renumbering: IdRenumbering,
+        #[case] range: (f64, f64),
+    ) {
+        let (start, end) = range;
+        let registry = embedded_registry([]).unwrap();
+        let mut value = parse(
+            &registry,
+            *STORAGE_ID,
+            json!({"ranges": [{"start": start, "end": end}]}),
+        );
+
+        let renumbered = renumbered(&renumbering, &[1.0, 2.0, 5.0, 10.0]);
+        assert!(renumber_mappings(&mut value, &renumbered, &renumbering).is_err());
+    }
+
+    #[rstest]
+    #[case::same_category(
+        &["ship"],
+        json!({"ShipId": 5.0, "Target": 5.0, "Weapon": 1.0, "WeaponId": 1.0}),
+        1
+    )]
+    #[case::assignable_category(
+        &["ship", "weapon"],
+        json!({"ShipId": 5.0, "Target": 5.0, "Weapon": 5.0, "WeaponId": 1.0}),
+        2
+    )]
+    fn should_renumber_assignable_refs(
+        #[case] assignable: &[&str],
+        #[case] expected: JsonValue,
+        #[case] references: usize,
+    ) {
+        let item = r#"
+            struct {
+                object "ShipId" "sys:ids/numeric" {
+                    const "Id" "ship"
+                }
+                object "Target" "sys:ids/numeric_ref" {
+                    const "Id" "ship"
+                }
+                object "Weapon" "sys:ids/numeric_ref" {
+                    const "Id" "weapon"
+                }
+                object "WeaponId" "sys:ids/numeric" {
+                    const "Id" "weapon"
+                }
+            }
+        "#;
+        let ident = ETypeId::from_raw("test:item".into());
+        let registry = embedded_registry([(ident, item.to_string())]).unwrap();
+        let mut value = parse(
+            &registry,
+            ident,
+            json!({"ShipId": 1, "Target": 1, "Weapon": 1, "WeaponId": 1}),
+        );
+
+        let renumbered = renumbered(&mapping(&[(1.0, 5.0)]), &[1.0]);
+        let stats = renumber_value(
+            &registry,
+            &mut value,
+            Ustr::from("ship"),
+            &renumbered,
+            &mut |ty| Ok(assignable.contains(&ty.as_str())),
+        )
+        .unwrap();
+
+        assert_eq!((stats.definitions, stats.references), (1, references));
+        assert_eq!(value.write_json(&registry).unwrap(), expected);
+    }
+}
@@ -0,0 +1,369 @@
+//! Severity configuration and suppression of diagnostic rules
+//!
+//! Every diagnostic carries a stable rule code, such as `ids/duplicate` or
+//! `ids/undefined-ref`. Levels of the rules can be changed in `project.toml`,
+//! globally or for the files matching path globs:
+//!
+//! ```toml
+//! [diagnostics.rules]
+//! "ids/reserved" = "warning"
+//!
+//! [[diagnostics.overrides]]
+//! paths = ["Vanilla/**"]
+//! rules = { "ids/duplicate" = "off" }
+//! ```
+//!
+//! Supported levels are `off`, `info`, `warning` and `error`. Overrides take
+//! precedence over the global rules, and later overrides take precedence over
+//! earlier ones.
+//!
+//! An item file can suppress rules at specific field paths via the
+//! `$suppress` field, mapping rule codes to the paths as shown in the
+//! diagnostics list. Suppressing a path also suppresses all of its children,
+//! and an empty path suppresses the rule in the whole file:
+//!
+//! ```json
+//! {
+//!   "$suppress": {
+//!     "ids/duplicate": ["<Component>.Id"]
+//!   }
+//! }
+//! ```
+
+use crate::json_utils::{json_kind, JsonMap, JsonValue};
+use crate::project::baseline::{Baseline, BaselineEntry};
+use camino::Utf8Path;
+use diagnostic::diagnostic::DiagnosticLevel;
+use diagnostic::path::DiagnosticPath;
+use diagnostic::rules::DiagnosticFilter;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use miette::{bail, Context, IntoDiagnostic};
+use parking_lot::{RwLock, RwLockUpgradableReadGuard};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Name of the JSON field holding the rule suppressions of the item file
+pub const SUPPRESS_FIELD: &str = "$suppress";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleLevel {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl RuleLevel {
+    fn level(self) -> Option<DiagnosticLevel> {
+        match self {
+            RuleLevel::Off => None,
+            RuleLevel::Info => Some(DiagnosticLevel::Info),
+            RuleLevel::Warning => Some(DiagnosticLevel::Warning),
+            RuleLevel::Error => Some(DiagnosticLevel::Error),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsConfig {
+    /// Levels of the rules in all files
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleLevel>,
+    /// Levels of the rules in the files matching the path globs
+    #[serde(default)]
+    pub overrides: Vec<RulesOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulesOverride {
+    /// Globs of the file paths, relative to the project root
+    pub paths: Vec<String>,
+    pub rules: BTreeMap<String, RuleLevel>,
+}
+
+/// Rules suppressed in the item file, mapping rule codes to the field paths
+pub type Suppressions = BTreeMap<String, Vec<String>>;
+
+/// Diagnostic filter applying the rule levels from the project config, the
+/// suppressions of the item files, and the diagnostics baseline
+#[derive(Debug)]
+pub struct DiagnosticRules {
+    rules: BTreeMap<String, RuleLevel>,
+    overrides: Vec<(GlobSet, BTreeMap<String, RuleLevel>)>,
+    suppressions: RwLock<BTreeMap<String, Suppressions>>,
+    baseline: RwLock<BaselineState>,
+}
+
+#[derive(Debug, Default)]
+struct BaselineState {
+    baseline: Baseline,
+    /// Baseline entries that were reported since the last
+    /// [DiagnosticRules::clear_baseline_matches] call
+    matched: BTreeSet<BaselineEntry>,
+}
+
+impl DiagnosticRules {
+    pub fn new(config: &DiagnosticsConfig) -> miette::Result<Self> {
+        let overrides = config
+            .overrides
+            .iter()
+            .map(|o| {
+                let mut set = GlobSetBuilder::new();
+                for path in &o.paths {
+                    let glob = GlobBuilder::new(path)
+                        .literal_separator(true)
+                        .build()
+                        .into_diagnostic()
+                        .with_context(|| format!("invalid path glob `{}`", path))?;
+                    set.add(glob);
+                }
+                let set = set.build().into_diagnostic()?;
+                Ok((set, o.rules.clone()))
+            })
+            .collect::<miette::Result<_>>()
+            .context("invalid diagnostics configuration")?;
+
+        Ok(Self {
+            rules: config.rules.clone(),
+            overrides,
+            suppressions: Default::default(),
+            baseline: Default::default(),
+        })
+    }
+
+    /// Replaces the baseline, diagnostics matching it will no longer be
+    /// reported
+    pub fn set_baseline(&self, baseline: Baseline) {
+        *self.baseline.write() = BaselineState {
+            baseline,
+            matched: Default::default(),
+        };
+    }
+
+    /// Forgets which baseline entries were reported
+    pub fn clear_baseline_matches(&self) {
+        self.baseline.write().matched.clear();
+    }
+
+    /// Returns the baseline entries that were not reported since the last
+    /// [DiagnosticRules::clear_baseline_matches] call
+    pub fn unmatched_baseline(&self) -> Vec<BaselineEntry> {
+        let state = self.baseline.read();
+        state
+            .baseline
+            .entries
+            .difference(&state.matched)
+            .cloned()
+            .collect()
+    }
+
+    /// Checks whether the diagnostic is in the baseline, remembering the
+    /// matched entry
+    fn match_baseline(&self, ident: &str, path: &DiagnosticPath, code: Option<&str>) -> bool {
+        let state = self.baseline.upgradable_read();
+        if state.baseline.is_empty() {
+            return false;
+        }
+
+        let entry = BaselineEntry {
+            file: ident.to_string(),
+            path: path.to_string(),
+            rule: code.map(|code| code.to_string()),
+        };
+
+        if !state.baseline.contains(&entry) {
+            return false;
+        }
+
+        RwLockUpgradableReadGuard::upgrade(state)
+            .matched
+            .insert(entry);
+        true
+    }
+
+    /// Removes the `$suppress` field from the item JSON, and records the
+    /// suppressions of the file
+    pub fn load_suppressions(&self, path: &Utf8Path, json: &mut JsonValue) -> miette::Result<()> {
+        let Some(value) = json
+            .as_object_mut()
+            .and_then(|obj| obj.remove(SUPPRESS_FIELD))
+        else {
+            self.suppressions.write().remove(path.as_str());
+            return Ok(());
+        };
+
+        let JsonValue::Object(_) = &value else {
+            bail!(
+                "`{}` field should be an object, but got {}",
+                SUPPRESS_FIELD,
+                json_kind(&value)
+            );
+        };
+
+        let suppressions: Suppressions = serde_json::from_value(value)
+            .into_diagnostic()
+            .with_context(|| {
+                format!(
+                    "`{}` field should map rule codes to lists of paths",
+                    SUPPRESS_FIELD
+                )
+            })?;
+
+        self.suppressions
+            .write()
+            .insert(path.to_string(), suppressions);
+
+        Ok(())
+    }
+
+    /// Writes the suppressions of the file back into the item JSON
+    pub fn write_suppressions(&self, path: &Utf8Path, json: &mut JsonValue) {
+        let suppressions = self.suppressions.read();
+        let Some(suppressions) = suppressions.get(path.as_str()) else {
+            return;
+        };
+        let JsonValue::Object(obj) = json else {
+            return;
+        };
+
+        let mut with_suppressions = JsonMap::new();
+        with_suppressions.insert(
+            SUPPRESS_FIELD.to_string(),
+            serde_json::to_value(suppressions).expect("suppressions should be serializable"),
+        );
+        with_suppressions.extend(std::mem::take(obj));
+        *obj = with_suppressions;
+    }
+
+    fn is_suppressed(&self, ident: &str, path: &DiagnosticPath, code: &str) -> bool {
+        let suppressions = self.suppressions.read();
+        let Some(paths) = suppressions.get(ident).and_then(|s| s.get(code)) else {
+            return false;
+        };
+
+        let path = path.to_string();
+        paths.iter().any(|suppressed| {
+            suppressed.is_empty()
+                || path
+                    .strip_prefix(suppressed.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[', '<']))
+        })
+    }
+}
+
+impl DiagnosticFilter for DiagnosticRules {
+    fn filter(
+        &self,
+        ident: &str,
+        path: &DiagnosticPath,
+        code: Option<&str>,
+        level: DiagnosticLevel,
+    ) -> Option<DiagnosticLevel> {
+        let level = match code {
+            None => level,
+            Some(code) => {
+                if self.is_suppressed(ident, path, code) {
+                    return None;
+                }
+
+                let configured = self
+                    .overrides
+                    .iter()
+                    .rev()
+                    .filter(|(paths, _)| paths.is_match(ident))
+                    .find_map(|(_, rules)| rules.get(code))
+                    .or_else(|| self.rules.get(code));
+
+                match configured {
+                    None => level,
+                    Some(configured) => configured.level()?,
+                }
+            }
+        };
+
+        if self.match_baseline(ident, path, code) {
+            return None;
+        }
+
+        Some(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiagnosticRules, DiagnosticsConfig, RuleLevel, RulesOverride};
+    use camino::Utf8Path;
+    use diagnostic::diagnostic::DiagnosticLevel;
+    use diagnostic::path::DiagnosticPath;
+    use diagnostic::rules::DiagnosticFilter;
+    use rstest::rstest;
+
+    fn rules() -> DiagnosticRules {
+        DiagnosticRules::new(&DiagnosticsConfig {
+            rules: [("ids/reserved".to_string(), RuleLevel::Warning)].into(),
+            overrides: vec![RulesOverride {
+                paths: vec!["Vanilla/**".to_string()],
+                rules: [
+                    ("ids/reserved".to_string(), RuleLevel::Off),
+                    ("ids/duplicate".to_string(), RuleLevel::Info),
+                ]
+                .into(),
+            }],
+        })
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case("Mod/Ship.json", Some("ids/reserved"), Some(DiagnosticLevel::Warning))]
+    #[case("Vanilla/Ship.json", Some("ids/reserved"), None)]
+    #[case(
+        "Vanilla/Ships/Ship.json",
+        Some("ids/duplicate"),
+        Some(DiagnosticLevel::Info)
+    )]
+    #[case("Mod/Ship.json", Some("ids/duplicate"), Some(DiagnosticLevel::Error))]
+    #[case("Vanilla/Ship.json", None, Some(DiagnosticLevel::Error))]
+    fn should_apply_rule_levels(
+        #[case] ident: &str,
+        #[case] code: Option<&str>,
+        #[case] expected: Option<DiagnosticLevel>,
+    ) {
+        let level = rules().filter(
+            ident,
+            &DiagnosticPath::empty(),
+            code,
+            DiagnosticLevel::Error,
+        );
+        assert_eq!(level, expected);
+    }
+
+    #[rstest]
+    #[case("Id", true)]
+    #[case("Id.id", true)]
+    #[case("Ids", false)]
+    #[case("Name", false)]
+    fn should_suppress_rule_at_path(#[case] field: &'static str, #[case] suppressed: bool) {
+        let rules = rules();
+        let mut json = serde_json::json!({
+            "$suppress": { "ids/duplicate": ["Id"] },
+            "Id": 1,
+        });
+        rules
+            .load_suppressions(Utf8Path::new("Mod/Ship.json"), &mut json)
+            .unwrap();
+        assert!(json.get("$suppress").is_none());
+
+        let mut path = DiagnosticPath::empty();
+        for segment in field.split('.') {
+            path.push(segment.to_string());
+        }
+        let level = rules.filter(
+            "Mod/Ship.json",
+            &path,
+            Some("ids/duplicate"),
+            DiagnosticLevel::Error,
+        );
+        assert_eq!(level.is_none(), suppressed);
+    }
+}
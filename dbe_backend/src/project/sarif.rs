@@ -0,0 +1,123 @@
+//! Export of the diagnostics in the SARIF format
+//!
+//! SARIF is understood by code scanning tools and editor extensions, which
+//! show the results at the reported source lines. Each diagnostic becomes a
+//! result with its rule code as the rule ID, the item file as the physical
+//! location, and the diagnostic path as the logical location.
+
+use crate::json_utils::JsonValue;
+use crate::project::sources::ItemSources;
+use diagnostic::context::DiagnosticContext;
+use diagnostic::diagnostic::DiagnosticLevel;
+use diagnostic::rules::report_code;
+use diagnostic::source::DiagnosticSources;
+use itertools::Itertools;
+use miette::SourceSpan;
+use serde_json::json;
+use std::collections::BTreeSet;
+
+/// Path of the exported SARIF file, relative to the project root
+pub const SARIF_FILE: &str = "diagnostics.sarif";
+
+/// Builds the SARIF log of all diagnostics of the context
+pub fn sarif_report(diagnostics: &DiagnosticContext, sources: &ItemSources) -> JsonValue {
+    let mut rules = BTreeSet::new();
+    let mut results = vec![];
+
+    for (file, paths) in &diagnostics.diagnostics {
+        for (path, reports) in paths {
+            let region = sources.locate(file, path).and_then(|(source, span)| {
+                let text = source.inner();
+                let (start_line, start_column) = position(text, span.offset())?;
+                let (end_line, end_column) = position(text, span_end(span))?;
+                Some(json!({
+                    "startLine": start_line,
+                    "startColumn": start_column,
+                    "endLine": end_line,
+                    "endColumn": end_column,
+                }))
+            });
+
+            for diagnostic in reports {
+                let code = report_code(&diagnostic.info);
+                if let Some(code) = &code {
+                    rules.insert(code.clone());
+                }
+
+                let mut physical = json!({
+                    "artifactLocation": {
+                        "uri": encode_uri(file),
+                        "uriBaseId": "%SRCROOT%",
+                    }
+                });
+                if let Some(region) = &region {
+                    physical["region"] = region.clone();
+                }
+
+                let mut result = json!({
+                    "level": match diagnostic.level {
+                        DiagnosticLevel::Error => "error",
+                        DiagnosticLevel::Warning => "warning",
+                        _ => "note",
+                    },
+                    "message": {
+                        "text": diagnostic.info.chain().map(|err| err.to_string()).join(": "),
+                    },
+                    "locations": [{
+                        "physicalLocation": physical,
+                        "logicalLocations": [{
+                            "fullyQualifiedName": path.to_string(),
+                        }],
+                    }],
+                });
+                if let Some(code) = code {
+                    result["ruleId"] = code.into();
+                }
+                results.push(result);
+            }
+        }
+    }
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "dbe",
+                    "rules": rules.into_iter().map(|id| json!({ "id": id })).collect_vec(),
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }]
+    })
+}
+
+fn span_end(span: SourceSpan) -> usize {
+    span.offset() + span.len()
+}
+
+/// Returns the one-based line and column of the byte offset
+fn position(text: &str, offset: usize) -> Option<(usize, usize)> {
+    let before = text.get(..offset)?;
+    let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    let line = before.matches('\n').count() + 1;
+    let column = before[line_start..].chars().count() + 1;
+    Some((line, column))
+}
+
+/// Percent-encodes the relative file path for use as a URI reference
+fn encode_uri(path: &str) -> String {
+    let mut uri = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            b'\\' => uri.push('/'),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
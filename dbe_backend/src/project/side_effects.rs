@@ -118,6 +118,15 @@ impl SideEffects {
         self.effects.is_empty()
     }
 
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// Discards side effects pushed after the first `len` ones
+    pub fn truncate(&mut self, len: usize) {
+        self.effects.truncate(len);
+    }
+
     pub fn push(&mut self, emitter: SideEffectEmitter, effect: SideEffect) {
         self.effects.push((emitter, effect));
     }
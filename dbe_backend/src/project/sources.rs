@@ -0,0 +1,60 @@
+//! Source locations of the item files
+//!
+//! Sources of the item files are kept alongside the span index of their
+//! values, so diagnostics can point at the exact JSON line they refer to.
+
+use crate::json_utils::spans::JsonSpan;
+use crate::project::{path_has_suffix, EXTENSION_VALUE};
+use camino::Utf8Path;
+use diagnostic::path::DiagnosticPath;
+use diagnostic::source::{DiagnosticSource, DiagnosticSources};
+use miette::{NamedSource, SourceSpan};
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[derive(Debug)]
+struct SourceFile {
+    source: DiagnosticSource,
+    /// Span of the item value, or `None` if the source is malformed
+    spans: Option<JsonSpan>,
+}
+
+/// Sources and span indices of the item files
+#[derive(Debug, Default)]
+pub struct ItemSources {
+    files: RwLock<BTreeMap<String, SourceFile>>,
+}
+
+impl ItemSources {
+    /// Records the source of the item file, replacing the previous one
+    pub fn insert(&self, path: &Utf8Path, source: String) {
+        let mut spans = JsonSpan::parse(&source);
+        if path_has_suffix(path, EXTENSION_VALUE) {
+            // Value files wrap the value together with its type
+            spans = spans.and_then(|spans| spans.get("value").cloned());
+        }
+
+        let source = NamedSource::new(path.as_str(), source).with_language("json");
+        self.files.write().insert(
+            path.to_string(),
+            SourceFile {
+                source: Arc::new(source),
+                spans,
+            },
+        );
+    }
+
+    pub fn remove(&self, path: &Utf8Path) {
+        self.files.write().remove(path.as_str());
+    }
+}
+
+impl DiagnosticSources for ItemSources {
+    fn locate(&self, ident: &str, path: &DiagnosticPath) -> Option<(DiagnosticSource, SourceSpan)> {
+        let files = self.files.read();
+        let file = files.get(ident)?;
+        let range = file.spans.as_ref()?.locate(path).range.clone();
+        Some((file.source.clone(), range.into()))
+    }
+}
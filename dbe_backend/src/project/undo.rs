@@ -1,12 +1,17 @@
+use crate::etype::estruct::{UnknownFields, UnknownFieldsData};
+use crate::project::localization::{LocalizationData, TranslationEdit};
 use crate::project::project_graph::{ProjectGraph, ProjectGraphs};
 use crate::project::ProjectFile;
 use crate::value::EValue;
 use camino::{Utf8Path, Utf8PathBuf};
+use itertools::Itertools;
 use miette::{bail, WrapErr};
 use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use strum::EnumIs;
 use tracing::warn;
+use ustr::Ustr;
 use utils::map::{hash_of, HashMap};
 use utils::ring_stack::RingStack;
 use uuid::Uuid;
@@ -25,6 +30,11 @@ pub struct UndoHistory {
     /// Actions that need to be performed to redo the undone history
     redo_snapshots: Vec<FileSnapshot>,
     flux: Option<Flux>,
+    /// Change set that the new snapshots are recorded into
+    group: Option<usize>,
+    group_index: usize,
+    /// Time of the last recorded translation edit
+    last_translation_time: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -59,6 +69,80 @@ impl UndoHistory {
             undone_history: Default::default(),
             redo_snapshots: Default::default(),
             last_snapshot: Default::default(),
+            group: None,
+            group_index: 0,
+            last_translation_time: 0.0,
+        }
+    }
+
+    /// Drops all undo and redo history, keeping the current time
+    pub fn clear(&mut self) {
+        let time = self.cur_time;
+        *self = Self::new(self.settings.clone());
+        self.cur_time = time;
+    }
+
+    /// Converts the values and graphs stored in the history, for example
+    /// after the types they were created with have changed
+    ///
+    /// Snapshots that fail to convert are dropped, along with their undo or
+    /// redo counterpart
+    pub fn convert_snapshots(
+        &mut self,
+        mut convert_value: impl FnMut(&EValue) -> miette::Result<EValue>,
+        mut convert_graph: impl FnMut(&ProjectGraph) -> miette::Result<ProjectGraph>,
+    ) {
+        let mut convert = |path: &Utf8Path, snapshot: &mut ItemSnapshot| -> bool {
+            let result = match snapshot {
+                ItemSnapshot::Value(value) => convert_value(value).map(|v| *value = v),
+                ItemSnapshot::Graph(_, graph) => convert_graph(graph).map(|g| *graph = g),
+                ItemSnapshot::Translation(_) | ItemSnapshot::UnknownFields(_) => Ok(()),
+            };
+            match result {
+                Ok(()) => true,
+                Err(err) => {
+                    warn!(%path, "Undo snapshot is dropped: {:?}", err);
+                    false
+                }
+            }
+        };
+        let mut convert_file = |snapshot: &mut FileSnapshot| -> bool {
+            let converted = convert(&snapshot.path, &mut snapshot.value);
+            snapshot.state = hash_of(&snapshot.value);
+            converted
+        };
+
+        self.history.retain_mut(&mut convert_file);
+
+        // Undone changes and their redo snapshots are stored in pairs
+        let pairs = self
+            .undone_history
+            .drain(..)
+            .zip(self.redo_snapshots.drain(..))
+            .filter_map(|(mut undone, mut redo)| {
+                (convert_file(&mut undone) && convert_file(&mut redo)).then_some((undone, redo))
+            })
+            .collect_vec();
+        for (undone, redo) in pairs {
+            self.undone_history.push(undone);
+            self.redo_snapshots.push(redo);
+        }
+
+        let mut dropped = vec![];
+        for (path, snapshot) in &mut self.last_snapshot {
+            if convert(path, snapshot) {
+                self.last_known_state
+                    .insert(path.clone(), hash_of(&*snapshot));
+            } else {
+                dropped.push(path.clone());
+            }
+        }
+        for path in dropped {
+            self.last_snapshot.remove(&path);
+            self.last_known_state.remove(&path);
+            if self.flux.as_ref().is_some_and(|flux| flux.path == path) {
+                self.flux = None;
+            }
         }
     }
 
@@ -78,6 +162,30 @@ impl UndoHistory {
         }
     }
 
+    /// Starts a change set. Changes of all files checked until
+    /// [UndoHistory::end_group] are undone and redone together
+    pub fn begin_group(
+        &mut self,
+        files: &BTreeMap<Utf8PathBuf, ProjectFile>,
+        graphs: &ProjectGraphs,
+    ) -> miette::Result<()> {
+        self.interrupt_flux(files, graphs)?;
+        self.group = Some(self.group_index);
+        self.group_index += 1;
+        Ok(())
+    }
+
+    /// Ends the change set started by [UndoHistory::begin_group]
+    pub fn end_group(
+        &mut self,
+        files: &BTreeMap<Utf8PathBuf, ProjectFile>,
+        graphs: &ProjectGraphs,
+    ) -> miette::Result<()> {
+        self.interrupt_flux(files, graphs)?;
+        self.group = None;
+        Ok(())
+    }
+
     pub fn ensure_file_state(
         &mut self,
         files: &BTreeMap<Utf8PathBuf, ProjectFile>,
@@ -101,8 +209,8 @@ impl UndoHistory {
     /// use [`UndoSettings::stable_time`](field@UndoSettings::stable_time) to
     /// configure how long a file must be stable before a snapshot is saved.
     ///
-    /// If [`force_snapshot`] is `true`, a snapshot will be saved regardless of
-    /// the flux system.
+    /// If [`force_snapshot`] is `true`, or a change set is started, a snapshot
+    /// will be saved regardless of the flux system.
     pub fn check_file(
         &mut self,
         files: &BTreeMap<Utf8PathBuf, ProjectFile>,
@@ -136,7 +244,7 @@ impl UndoHistory {
         self.last_known_state.insert(path.to_path_buf(), state);
 
         if let Some(flux) = &mut self.flux {
-            if force_snapshot || flux.path != *path {
+            if force_snapshot || self.group.is_some() || flux.path != *path {
                 self.interrupt_flux(files, graphs)?;
             } else {
                 // debug!(%path, "File changed, but consumed by flux");
@@ -162,20 +270,86 @@ impl UndoHistory {
             path: path.to_path_buf(),
             state: hash_of(&snapshot),
             value: snapshot,
+            group: self.group,
         };
 
         self.push_snapshot(snapshot);
 
-        self.flux = Some(Flux {
-            start_time: self.cur_time,
-            latest_change_time: self.cur_time,
-            path: path.to_path_buf(),
-        });
+        if self.group.is_none() {
+            self.flux = Some(Flux {
+                start_time: self.cur_time,
+                latest_change_time: self.cur_time,
+                path: path.to_path_buf(),
+            });
+        }
 
         Ok(())
     }
 
-    /// Undo the last change.
+    /// Records the translation edit into the history
+    ///
+    /// Consecutive edits of the same key are merged into a single change,
+    /// unless the key was stable for
+    /// [`UndoSettings::stable_time`](field@UndoSettings::stable_time)
+    pub fn record_translation(&mut self, data: Arc<LocalizationData>, edit: TranslationEdit) {
+        let merged = self.group.is_none()
+            && self.cur_time - self.last_translation_time <= self.settings.stable_time
+            && self.undone_history.is_empty()
+            && self.redo_snapshots.is_empty()
+            && self.history.iter().next_back().is_some_and(|last| {
+                matches!(&last.value, ItemSnapshot::Translation(last)
+                    if last.locale == edit.locale && last.key == edit.key)
+            });
+        self.last_translation_time = self.cur_time;
+        if merged {
+            return;
+        }
+
+        let snapshot = ItemSnapshot::Translation(TranslationSnapshot {
+            data,
+            locale: edit.locale,
+            key: edit.key,
+            text: edit.previous,
+        });
+        let snapshot = FileSnapshot {
+            id: self.next_change_index(),
+            kind: SnapshotKind::Change,
+            path: edit.path,
+            state: hash_of(&snapshot),
+            value: snapshot,
+            group: self.group,
+        };
+
+        self.push_snapshot(snapshot);
+    }
+
+    /// Records that the unknown fields of the file were dropped, so they can
+    /// be restored by undoing the change
+    pub fn record_unknown_fields(
+        &mut self,
+        data: Arc<UnknownFieldsData>,
+        path: Utf8PathBuf,
+        previous: Vec<UnknownFields>,
+    ) {
+        let snapshot = ItemSnapshot::UnknownFields(UnknownFieldsSnapshot {
+            data,
+            fields: previous,
+        });
+        let snapshot = FileSnapshot {
+            id: self.next_change_index(),
+            kind: SnapshotKind::Change,
+            path,
+            state: hash_of(&snapshot),
+            value: snapshot,
+            group: self.group,
+        };
+
+        self.push_snapshot(snapshot);
+    }
+
+    /// Undo the last change, or the whole change set it belongs to.
+    ///
+    /// Returns the path of the last changed file
     pub fn undo(
         &mut self,
         files: &mut BTreeMap<Utf8PathBuf, ProjectFile>,
@@ -186,6 +360,29 @@ impl UndoHistory {
             bail!("Nothing to undo");
         };
 
+        let group = last_snapshot.group;
+        let path = self.undo_snapshot(last_snapshot, files, graphs)?;
+        if group.is_some() {
+            while self
+                .history
+                .iter()
+                .next_back()
+                .is_some_and(|snapshot| snapshot.group == group)
+            {
+                let snapshot = self.history.pop().expect("Snapshot existence was checked");
+                self.undo_snapshot(snapshot, files, graphs)?;
+            }
+        }
+
+        Ok(path)
+    }
+
+    fn undo_snapshot(
+        &mut self,
+        last_snapshot: FileSnapshot,
+        files: &mut BTreeMap<Utf8PathBuf, ProjectFile>,
+        graphs: &mut ProjectGraphs,
+    ) -> miette::Result<Utf8PathBuf> {
         let redo_snapshot = last_snapshot
             .value
             .restore(&last_snapshot.path, files, graphs)?;
@@ -198,17 +395,23 @@ impl UndoHistory {
             path: path.clone(),
             state: hash_of(&redo_snapshot),
             value: redo_snapshot,
+            group: last_snapshot.group,
         });
 
+        let is_file = last_snapshot.value.is_file();
         self.undone_history.push(last_snapshot);
 
-        self.update_last_known_state(path.clone(), files, graphs)
-            .context("Failed to recalculate last known file state")?;
+        if is_file {
+            self.update_last_known_state(path.clone(), files, graphs)
+                .context("Failed to recalculate last known file state")?;
+        }
 
         Ok(path)
     }
 
-    /// Redo the last undone change.
+    /// Redo the last undone change, or the whole change set it belongs to.
+    ///
+    /// Returns the path of the last changed file
     pub fn redo(
         &mut self,
         files: &mut BTreeMap<Utf8PathBuf, ProjectFile>,
@@ -219,6 +422,31 @@ impl UndoHistory {
             bail!("Nothing to redo");
         };
 
+        let group = redo_snapshot.group;
+        let path = self.redo_snapshot(redo_snapshot, files, graphs)?;
+        if group.is_some() {
+            while self
+                .redo_snapshots
+                .last()
+                .is_some_and(|snapshot| snapshot.group == group)
+            {
+                let snapshot = self
+                    .redo_snapshots
+                    .pop()
+                    .expect("Snapshot existence was checked");
+                self.redo_snapshot(snapshot, files, graphs)?;
+            }
+        }
+
+        Ok(path)
+    }
+
+    fn redo_snapshot(
+        &mut self,
+        redo_snapshot: FileSnapshot,
+        files: &mut BTreeMap<Utf8PathBuf, ProjectFile>,
+        graphs: &mut ProjectGraphs,
+    ) -> miette::Result<Utf8PathBuf> {
         let undone = self
             .undone_history
             .pop()
@@ -236,10 +464,13 @@ impl UndoHistory {
             path: redo_snapshot.path.clone(),
             state: hash_of(&last_snapshot),
             value: last_snapshot,
+            group: undone.group,
         });
 
-        self.update_last_known_state(redo_snapshot.path.clone(), files, graphs)
-            .context("Failed to recalculate last known file state")?;
+        if redo_snapshot.value.is_file() {
+            self.update_last_known_state(redo_snapshot.path.clone(), files, graphs)
+                .context("Failed to recalculate last known file state")?;
+        }
 
         Ok(redo_snapshot.path)
     }
@@ -315,9 +546,19 @@ impl UndoHistory {
         for x in self.undone_history.drain(..).rev() {
             self.history.push(x);
         }
+        // Redo snapshots become the changes reverting the undone change sets,
+        // so they get change sets of their own
+        let mut groups = HashMap::<usize, usize>::default();
         for mut x in self.redo_snapshots.drain(..) {
             x.id = self.change_index;
             self.change_index += 1;
+            x.group = x.group.map(|group| {
+                *groups.entry(group).or_insert_with(|| {
+                    let index = self.group_index;
+                    self.group_index += 1;
+                    index
+                })
+            });
             self.history.push(x);
         }
         self.history.push(snapshot);
@@ -354,6 +595,8 @@ pub struct FileSnapshot {
     pub path: Utf8PathBuf,
     pub state: u64,
     value: ItemSnapshot,
+    /// Change set of the snapshot
+    pub group: Option<usize>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, EnumIs)]
@@ -378,10 +621,29 @@ fn state_of(file: &ProjectFile, graphs: &ProjectGraphs) -> miette::Result<u64> {
 }
 
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, EnumIs)]
 enum ItemSnapshot {
     Value(EValue),
     Graph(Uuid, ProjectGraph),
+    /// Translation of a single key, stored in the translation file at the
+    /// snapshot path
+    Translation(TranslationSnapshot),
+    /// Unknown JSON fields of the file at the snapshot path
+    UnknownFields(UnknownFieldsSnapshot),
+}
+
+#[derive(Debug, Clone)]
+struct TranslationSnapshot {
+    data: Arc<LocalizationData>,
+    locale: Ustr,
+    key: String,
+    text: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct UnknownFieldsSnapshot {
+    data: Arc<UnknownFieldsData>,
+    fields: Vec<UnknownFields>,
 }
 
 impl Hash for ItemSnapshot {
@@ -391,11 +653,20 @@ impl Hash for ItemSnapshot {
             ItemSnapshot::Graph(id, graph) => {
                 (id, graph).hash(state);
             }
+            ItemSnapshot::Translation(translation) => {
+                (translation.locale, &translation.key, &translation.text).hash(state);
+            }
+            ItemSnapshot::UnknownFields(unknown) => unknown.fields.hash(state),
         }
     }
 }
 
 impl ItemSnapshot {
+    /// Whether the snapshot holds the content of a project file
+    fn is_file(&self) -> bool {
+        matches!(self, ItemSnapshot::Value(_) | ItemSnapshot::Graph(..))
+    }
+
     fn from_file(file: &ProjectFile, graphs: &ProjectGraphs) -> miette::Result<Self> {
         match file {
             ProjectFile::Value(value) => Ok(Self::Value(value.clone())),
@@ -417,6 +688,31 @@ impl ItemSnapshot {
         files: &mut BTreeMap<Utf8PathBuf, ProjectFile>,
         graphs: &mut ProjectGraphs,
     ) -> miette::Result<Self> {
+        if let ItemSnapshot::Translation(translation) = self {
+            let text = translation.data.write().restore(
+                path,
+                translation.locale,
+                &translation.key,
+                translation.text.clone(),
+            )?;
+            return Ok(Self::Translation(TranslationSnapshot {
+                text,
+                ..translation.clone()
+            }));
+        }
+        if let ItemSnapshot::UnknownFields(unknown) = self {
+            let mut data = unknown.data.write();
+            let fields = if unknown.fields.is_empty() {
+                data.remove(path)
+            } else {
+                data.insert(path.clone(), unknown.fields.clone())
+            };
+            return Ok(Self::UnknownFields(UnknownFieldsSnapshot {
+                data: unknown.data.clone(),
+                fields: fields.unwrap_or_default(),
+            }));
+        }
+
         let mut old_graph = None;
         let value = match &self {
             ItemSnapshot::Value(value) => {
@@ -426,6 +722,9 @@ impl ItemSnapshot {
                 old_graph = graphs.graphs.insert(*id, graph.clone());
                 files.insert(path.clone(), ProjectFile::Graph(*id))
             }
+            ItemSnapshot::Translation(_) | ItemSnapshot::UnknownFields(_) => {
+                unreachable!("translations and unknown fields are restored above")
+            }
         };
 
         let value = value.expect("File creation and deletion are handled separately");
@@ -442,3 +741,35 @@ impl ItemSnapshot {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{UndoHistory, UndoSettings};
+    use crate::etype::estruct::{UnknownFields, UnknownFieldsData};
+    use crate::project::project_graph::ProjectGraphs;
+    use crate::value::id::ETypeId;
+    use camino::Utf8PathBuf;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn should_undo_dropped_unknown_fields() {
+        let path = Utf8PathBuf::from("item.json");
+        let fields = vec![UnknownFields {
+            ty: ETypeId::from_raw("test:item".into()),
+            fields: vec!["Speed".to_string()],
+        }];
+        let data = Arc::new(UnknownFieldsData::default());
+        let mut files = BTreeMap::new();
+        let mut graphs = ProjectGraphs::default();
+        let mut history = UndoHistory::new(UndoSettings::default());
+
+        history.record_unknown_fields(data.clone(), path.clone(), fields.clone());
+
+        history.undo(&mut files, &mut graphs).unwrap();
+        assert_eq!(data.read().get(&path), Some(&fields));
+
+        history.redo(&mut files, &mut graphs).unwrap();
+        assert_eq!(data.read().get(&path), None);
+    }
+}
@@ -4,6 +4,7 @@ use crate::etype::eenum::EEnumData;
 use crate::etype::eitem::EItemInfo;
 use crate::etype::eobject::EObject;
 use crate::etype::estruct::EStructData;
+use crate::etype::generic::check_generic_constraints;
 use crate::etype::property::{default_properties, ObjectPropertyId};
 use crate::etype::EDataType;
 use crate::graph::node::all_node_factories;
@@ -12,7 +13,7 @@ use crate::json_utils::JsonValue;
 use crate::project::ProjectConfig;
 use crate::registry::config::ExtraConfig;
 use crate::serialization::deserialize_etype;
-use crate::value::id::{EListId, EMapId, ETypeId};
+use crate::value::id::{EListId, EMapId, ETupleId, ETypeId};
 use crate::value::EValue;
 use atomic_refcell::AtomicRefCell;
 use camino::Utf8PathBuf;
@@ -53,6 +54,11 @@ pub struct MapData {
     pub value_type: EDataType,
 }
 
+#[derive(Debug, Clone)]
+pub struct TupleData {
+    pub item_types: Vec<EDataType>,
+}
+
 #[derive(Debug, Clone)]
 pub enum EObjectType {
     Struct(EStructData),
@@ -87,7 +93,7 @@ impl EObjectType {
         };
 
         let data = if let Some(repr) = repr {
-            data_holder.insert(repr.from_repr(registry, data, inline)?)
+            data_holder.insert(repr.from_repr_of(registry, self.ident(), data, inline)?)
         } else {
             data
         };
@@ -186,6 +192,8 @@ pub struct ETypesRegistry {
     lists: RwLock<BTreeMap<EListId, ListData>>,
     /// Storage for maps
     maps: RwLock<BTreeMap<EMapId, MapData>>,
+    /// Storage for tuples
+    tuples: RwLock<BTreeMap<ETupleId, TupleData>>,
     /// Cache for default values
     default_objects_cache: AtomicRefCell<BTreeMap<ETypeId, Arc<EValue>>>,
     /// Main project configuration
@@ -221,6 +229,7 @@ impl ETypesRegistry {
             pending_types: Default::default(),
             lists: Default::default(),
             maps: Default::default(),
+            tuples: Default::default(),
             default_objects_cache: Default::default(),
             project_config,
             extra_data: Default::default(),
@@ -235,13 +244,28 @@ impl ETypesRegistry {
             .register_node_requirements()
             .context("failed to register node requirements")?
             .register_optionals()
-            .context("failed to register optional types")?;
+            .context("failed to register optional types")?
+            .check_conditions()
+            .context("failed to check field conditions")?;
 
         reg.ready = true;
 
         Ok(reg)
     }
 
+    /// Builds a new registry from the updated type sources, keeping the
+    /// project configuration, extra configuration, and extra data of this
+    /// registry
+    pub fn reload(
+        &self,
+        data: impl IntoIterator<Item = (ETypeId, String)>,
+    ) -> miette::Result<Self> {
+        let mut reg = Self::from_raws(data, self.project_config.clone())?;
+        reg.extra_config = self.extra_config.clone();
+        *reg.extra_data.get_mut() = self.extra_data.read().clone();
+        Ok(reg)
+    }
+
     /// Flushes all pending types into the main storage
     pub fn apply_pending(&mut self) {
         let mut pending = self.pending_types.borrow_mut();
@@ -335,6 +359,10 @@ impl ETypesRegistry {
         self.maps.read().get(id).copied()
     }
 
+    pub fn get_tuple(&self, id: &ETupleId) -> Option<TupleData> {
+        self.tuples.read().get(id).cloned()
+    }
+
     // pub fn register_struct(&mut self, id: ETypeId, data: EStructData) -> EDataType {
     //     self.types
     //         .insert(id, RegistryItem::Ready(EObjectType::Struct(data)));
@@ -379,6 +407,26 @@ impl ETypesRegistry {
         EDataType::Map { id }
     }
 
+    pub fn tuple_of(&self, item_types: Vec<EDataType>) -> EDataType {
+        EDataType::Tuple {
+            id: self.tuple_id_of(item_types),
+        }
+    }
+    pub fn tuple_id_of(&self, item_types: Vec<EDataType>) -> ETupleId {
+        let id = format!(
+            "Tuple<{}>",
+            item_types.iter().map(|ty| ty.name()).join(", ")
+        );
+        let id = ETupleId::from_raw(id.into());
+        match self.tuples.write().entry(id) {
+            Entry::Occupied(_) => {}
+            Entry::Vacant(entry) => {
+                entry.insert(TupleData { item_types });
+            }
+        }
+        id
+    }
+
     pub fn option_data_of(&self, value_type: EDataType) -> WhateverRef<EEnumData> {
         let id = self.option_id_of(value_type);
         self.get_enum(&id).expect("Optional enum should be present")
@@ -407,10 +455,20 @@ impl ETypesRegistry {
             return Ok(long_id);
         }
 
-        let obj = Self::make_type_generic(id, arguments, long_id, |id| {
-            self.fetch_or_deserialize(*id)
-                .map(|x| WhateverRef::from_arc(x.clone()))
-        })?;
+        // Object lookups borrow the registry mutably, so list items of the
+        // arguments are resolved beforehand
+        let list_items = self.argument_list_items(&arguments);
+
+        let obj = Self::make_type_generic(
+            id,
+            arguments,
+            long_id,
+            |id| list_items.get(id).copied(),
+            |id| {
+                self.fetch_or_deserialize(*id)
+                    .map(|x| WhateverRef::from_arc(x.clone()))
+            },
+        )?;
 
         self.types
             .insert(long_id, RegistryItem::Ready(Arc::new(obj)));
@@ -439,10 +497,16 @@ impl ETypesRegistry {
             bail!("Registry is not ready yet")
         }
 
-        let obj = Self::make_type_generic(id, arguments, long_id, |id| {
-            self.get_object(id)
-                .ok_or_else(|| miette!("Type `{}` is not defined", id))
-        })?;
+        let obj = Self::make_type_generic(
+            id,
+            arguments,
+            long_id,
+            |id| self.get_list(id).map(|list| list.value_type),
+            |id| {
+                self.get_object(id)
+                    .ok_or_else(|| miette!("Type `{}` is not defined", id))
+            },
+        )?;
 
         self.pending_types
             .borrow_mut()
@@ -451,10 +515,28 @@ impl ETypesRegistry {
         Ok(long_id)
     }
 
+    /// Item types of the lists passed as generic arguments, including the
+    /// nested lists
+    fn argument_list_items(&self, arguments: &UstrMap<EItemInfo>) -> HashMap<EListId, EDataType> {
+        let mut items = HashMap::default();
+        for arg in arguments.values().filter(|arg| !arg.is_generic()) {
+            let mut ty = arg.ty();
+            while let EDataType::List { id } = ty {
+                let Some(list) = self.get_list(&id) else {
+                    break;
+                };
+                items.insert(id, list.value_type);
+                ty = list.value_type;
+            }
+        }
+        items
+    }
+
     fn make_type_generic<'a>(
         id: ETypeId,
         arguments: UstrMap<EItemInfo>,
         generic_id: ETypeId,
+        list_item: impl Fn(&EListId) -> Option<EDataType>,
         mut get_object: impl FnMut(&ETypeId) -> miette::Result<WhateverRef<'a, EObjectType>>,
     ) -> miette::Result<EObjectType> {
         let check_generics = |args: &[Ustr]| {
@@ -477,11 +559,25 @@ impl ETypesRegistry {
         match obj {
             EObjectType::Struct(data) => {
                 check_generics(&data.generic_arguments)?;
+                check_generic_constraints(
+                    id,
+                    &data.generic_constraints,
+                    &arguments,
+                    &list_item,
+                    &mut get_object,
+                )?;
                 let obj = data.apply_generics(&arguments, generic_id)?;
                 Ok(EObjectType::Struct(obj))
             }
             EObjectType::Enum(data) => {
                 check_generics(&data.generic_arguments)?;
+                check_generic_constraints(
+                    id,
+                    &data.generic_constraints,
+                    &arguments,
+                    &list_item,
+                    &mut get_object,
+                )?;
                 let obj = data.apply_generics(&arguments, generic_id, &mut get_object)?;
                 Ok(EObjectType::Enum(obj))
             } // EObjectType::List(mut data) => {
@@ -698,6 +794,36 @@ impl ETypesRegistry {
         Ok(self)
     }
 
+    /// Checks that the values compared by the field conditions are valid
+    /// for the types of the compared fields
+    fn check_conditions(self) -> miette::Result<Self> {
+        for (id, item) in &self.types {
+            let EObjectType::Struct(data) = item.expect_ready().deref() else {
+                continue;
+            };
+            for field in &data.fields {
+                for (prop, condition) in [
+                    ("required_if", &field.required_if),
+                    ("visible_if", &field.visible_if),
+                ] {
+                    let Some(condition) = condition else {
+                        continue;
+                    };
+                    condition
+                        .check_values(&self, &data.fields)
+                        .with_context(|| {
+                            format!(
+                                "bad `{}` condition of field `{}` in `{}`",
+                                prop, field.name, id
+                            )
+                        })?;
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
     // MAYBE?: use https://github.com/compenguy/ngrammatic for hints
     pub(crate) fn assert_defined(&self, id: &ETypeId) -> miette::Result<()> {
         if !self.types.contains_key(id) {
@@ -7,7 +7,7 @@ use crate::etype::property::object_props;
 use crate::json_utils::repr::Repr;
 use crate::m_try;
 use crate::registry::{EObjectType, ETypesRegistry};
-use crate::serialization::item::ThingItem;
+use crate::serialization::item::{take_constraints, take_docs, ThingItem};
 use crate::validation::{validator_by_name, Validator};
 use crate::value::id::ETypeId;
 use itertools::Itertools;
@@ -82,33 +82,48 @@ struct ThingEnum {
 
 impl ThingStruct {
     fn into_estruct(
-        self,
+        mut self,
         registry: &mut ETypesRegistry,
         id: ETypeId,
     ) -> miette::Result<EStructData> {
+        take_docs(&mut self.fields, &mut self.extra_properties)?;
+        let constraints = take_constraints(&mut self.fields, &self.generic_arguments)?;
         let mut data = EStructData::new(
             id,
             self.generic_arguments,
             self.repr,
             object_props(self.extra_properties)?,
         );
+        data.generic_constraints = constraints;
         for e in self.fields {
             let field_name = e.name;
             m_try(|| {
                 let (name, item) = e.into_item(registry, &data.generic_arguments)?;
-                data.add_field(EStructField { name, ty: item })?;
+                data.add_field(EStructField {
+                    name,
+                    ty: item,
+                    required_if: None,
+                    visible_if: None,
+                })?;
 
                 Ok(())
             })
             .with_context(|| format!("failed to initialize field {}", field_name))?;
         }
+        data.parse_conditions()?;
 
         Ok(data)
     }
 }
 
 impl ThingEnum {
-    fn into_eenum(self, registry: &mut ETypesRegistry, id: ETypeId) -> miette::Result<EEnumData> {
+    fn into_eenum(
+        mut self,
+        registry: &mut ETypesRegistry,
+        id: ETypeId,
+    ) -> miette::Result<EEnumData> {
+        take_docs(&mut self.variants, &mut self.extra_properties)?;
+        let constraints = take_constraints(&mut self.variants, &self.generic_arguments)?;
         let repr = if let Some(tag) = self.tag {
             if tag.as_str() == "{}" {
                 if self.content.is_some() {
@@ -137,6 +152,7 @@ impl ThingEnum {
             repr,
             object_props(self.extra_properties)?,
         );
+        data.generic_constraints = constraints;
         for e in self.variants {
             let (name, item) = e.into_item(registry, &data.generic_arguments)?;
             data.add_variant(EEnumVariant::from_eitem(
@@ -151,6 +167,7 @@ impl ThingEnum {
                 name,
             )?);
         }
+        data.check_overlaps()?;
         Ok(data)
     }
 }
@@ -1,20 +1,25 @@
 use crate::etype::econst::ETypeConst;
 use crate::etype::eitem::{EItemInfo, EItemInfoGeneric, EItemInfoSpecific};
+use crate::etype::generic::GenericConstraint;
 use crate::etype::property::field_props;
 use crate::etype::EDataType;
 use crate::m_try;
 use crate::registry::ETypesRegistry;
 use crate::serialization::validators;
+use crate::validation::{validator_by_name, DataValidator, Validator};
 use crate::value::id::ETypeId;
 use itertools::Itertools;
 use miette::{bail, Context, Diagnostic};
 use std::fmt::Display;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use strum::EnumString;
 use thiserror::Error;
 use ustr::{Ustr, UstrMap};
 use utils::map::HashMap;
 
+static ASSET_VALIDATOR: LazyLock<Validator> =
+    LazyLock::new(|| validator_by_name("asset".into()).unwrap());
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, EnumString)]
 #[strum(serialize_all = "snake_case")]
 pub enum ThingItemKind {
@@ -25,7 +30,11 @@ pub enum ThingItemKind {
     Const,
     List,
     Map,
+    Tuple,
+    Asset,
     Generic,
+    Doc,
+    Constraint,
 }
 
 #[derive(Debug, knus::Decode)]
@@ -44,10 +53,12 @@ pub struct ThingItem {
 
 impl ThingItem {
     pub fn into_item(
-        self,
+        mut self,
         registry: &mut ETypesRegistry,
         generic_arguments: &[Ustr],
     ) -> miette::Result<(Ustr, EItemInfo)> {
+        take_docs(&mut self.generics, &mut self.extra_properties)?;
+
         let no_args = || {
             if !self.arguments.is_empty() {
                 bail!(
@@ -67,9 +78,23 @@ impl ThingItem {
             }
             Ok(())
         };
-        let generics = |registry: &mut ETypesRegistry| {
+        let tuple_items = |registry: &mut ETypesRegistry, items: Vec<ThingItem>| {
+            let mut types = vec![];
+            for (i, item) in items.into_iter().enumerate() {
+                let name = item.name;
+                let (_, v) = item
+                    .into_item(registry, generic_arguments)
+                    .with_context(|| {
+                        format!("failed to parse tuple item at position {i} with name {name}")
+                    })?;
+                types.push(v.ty());
+            }
+
+            miette::Result::<Vec<EDataType>>::Ok(types)
+        };
+        let generics = |registry: &mut ETypesRegistry, children: Vec<ThingItem>| {
             let mut items = UstrMap::default();
-            for (i, arg) in self.generics.into_iter().enumerate() {
+            for (i, arg) in children.into_iter().enumerate() {
                 let name = arg.name;
                 let (k, v) = m_try(|| {
                     // if arg.extra_properties.len() > 0 {
@@ -92,6 +117,7 @@ impl ThingItem {
             miette::Result::<UstrMap<EItemInfo>>::Ok(items)
         };
 
+        let mut extra_validators = vec![];
         let ty = match self.kind {
             ThingItemKind::Boolean => {
                 no_args()?;
@@ -112,7 +138,7 @@ impl ThingItem {
                 let [ty] = expect_args(self.arguments)?;
                 let mut ty = id(ty, 0)?;
                 registry.assert_defined(&ty)?;
-                let generics = generics(registry)?;
+                let generics = generics(registry, self.generics)?;
                 if !generics.is_empty() {
                     ty = registry.make_generic(ty, generics)?;
                 }
@@ -124,7 +150,7 @@ impl ThingItem {
             }
             ThingItemKind::List => {
                 no_args()?;
-                let generics = generics(registry)?;
+                let generics = generics(registry, self.generics)?;
                 let Some(ty) = generics.get(&Ustr::from("Item")) else {
                     bail!("generic argument `Item` is not provided");
                 };
@@ -133,7 +159,7 @@ impl ThingItem {
             }
             ThingItemKind::Map => {
                 no_args()?;
-                let generics = generics(registry)?;
+                let generics = generics(registry, self.generics)?;
                 let Some(key) = generics.get(&Ustr::from("Key")) else {
                     bail!("generic argument `Key` is not provided");
                 };
@@ -143,6 +169,30 @@ impl ThingItem {
 
                 registry.map_of(key.ty(), value.ty())
             }
+            ThingItemKind::Tuple => {
+                no_args()?;
+                if self.generics.is_empty() {
+                    bail!("tuple must have at least one item");
+                }
+                let items = tuple_items(registry, self.generics)?;
+
+                registry.tuple_of(items)
+            }
+            ThingItemKind::Asset => {
+                no_args()?;
+                no_generics()?;
+                extra_validators.push(ASSET_VALIDATOR.clone());
+                self.extra_properties
+                    .entry("editor".to_string())
+                    .or_insert_with(|| ETypeConst::String("asset".into()));
+                EDataType::String
+            }
+            ThingItemKind::Doc => {
+                bail!("`doc` node can only be used as a documentation child");
+            }
+            ThingItemKind::Constraint => {
+                bail!("`constraint` node can only be used at the top level of a generic type");
+            }
             ThingItemKind::Generic => {
                 let [arg] = expect_args(self.arguments)?;
                 let arg = generic_name(arg, 0, generic_arguments)?;
@@ -157,7 +207,12 @@ impl ThingItem {
             }
         };
 
-        let validators = validators(&self.extra_properties)?;
+        let mut validators = validators(&self.extra_properties)?;
+        for validator in extra_validators {
+            if !validators.iter().any(|v| v.name() == validator.name()) {
+                validators.push(validator);
+            }
+        }
 
         Ok((
             self.name,
@@ -170,6 +225,75 @@ impl ThingItem {
     }
 }
 
+/// Moves `doc` children into the `doc` property
+///
+/// Multiple `doc` children are joined with newlines
+pub fn take_docs(
+    children: &mut Vec<ThingItem>,
+    props: &mut HashMap<String, ETypeConst>,
+) -> miette::Result<()> {
+    let (docs, rest): (Vec<_>, Vec<_>) = std::mem::take(children)
+        .into_iter()
+        .partition(|child| child.kind == ThingItemKind::Doc);
+    *children = rest;
+
+    if docs.is_empty() {
+        return Ok(());
+    }
+
+    if docs.iter().any(|doc| {
+        !doc.arguments.is_empty() || !doc.extra_properties.is_empty() || !doc.generics.is_empty()
+    }) {
+        bail!("`doc` node only accepts a single string argument");
+    }
+
+    if props.contains_key("doc") {
+        bail!("documentation is provided both via the `doc` property and `doc` children");
+    }
+
+    let text = docs.iter().map(|doc| doc.name.as_str()).join("\n");
+    props.insert("doc".to_string(), ETypeConst::String(text.into()));
+
+    Ok(())
+}
+
+/// Takes `constraint` children describing constraints on the generic
+/// arguments of the type
+pub fn take_constraints(
+    children: &mut Vec<ThingItem>,
+    generic_arguments: &[Ustr],
+) -> miette::Result<Vec<(Ustr, GenericConstraint)>> {
+    let (nodes, rest): (Vec<_>, Vec<_>) = std::mem::take(children)
+        .into_iter()
+        .partition(|child| child.kind == ThingItemKind::Constraint);
+    *children = rest;
+
+    let mut constraints: Vec<(Ustr, GenericConstraint)> = vec![];
+    for node in nodes {
+        let name = node.name;
+        m_try(|| {
+            if !node.extra_properties.is_empty() || !node.generics.is_empty() {
+                bail!("`constraint` node only accepts arguments");
+            }
+            if !generic_arguments.contains(&name) {
+                return Err(BadGenericArg(name, 0, generic_arguments.to_vec()).into());
+            }
+            if constraints.iter().any(|(arg, _)| *arg == name) {
+                bail!("generic argument `{}` is constrained more than once", name);
+            }
+
+            let Some((ETypeConst::String(kind), types)) = node.arguments.split_first() else {
+                bail!("expected constraint kind as the second argument");
+            };
+            constraints.push((name, GenericConstraint::parse(kind, types)?));
+            Ok(())
+        })
+        .with_context(|| format!("failed to parse constraint of generic argument `{}`", name))?;
+    }
+
+    Ok(constraints)
+}
+
 fn expect_args<const N: usize, T>(args: Vec<T>) -> miette::Result<[T; N]> {
     if args.len() != N {
         bail!(
@@ -220,3 +344,74 @@ impl Diagnostic for BadGenericArg {
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{take_docs, ThingItem, ThingItemKind};
+    use crate::etype::econst::ETypeConst;
+    use rstest::rstest;
+    use utils::map::HashMap;
+
+    fn item(kind: ThingItemKind, name: &str) -> ThingItem {
+        ThingItem {
+            kind,
+            name: name.into(),
+            arguments: vec![],
+            extra_properties: Default::default(),
+            generics: vec![],
+        }
+    }
+
+    #[test]
+    fn should_join_doc_children() {
+        let mut children = vec![
+            item(ThingItemKind::Doc, "First line"),
+            item(ThingItemKind::Number, "field"),
+            item(ThingItemKind::Doc, "Second line"),
+        ];
+        let mut props = HashMap::default();
+
+        take_docs(&mut children, &mut props).unwrap();
+
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].kind, ThingItemKind::Number);
+        assert_eq!(
+            props.get("doc"),
+            Some(&ETypeConst::String("First line\nSecond line".into()))
+        );
+    }
+
+    #[test]
+    fn should_keep_props_without_docs() {
+        let mut children = vec![item(ThingItemKind::Number, "field")];
+        let mut props = HashMap::default();
+
+        take_docs(&mut children, &mut props).unwrap();
+
+        assert_eq!(children.len(), 1);
+        assert!(props.is_empty());
+    }
+
+    #[rstest]
+    #[case::argument(|doc: &mut ThingItem| doc.arguments.push(ETypeConst::Null))]
+    #[case::property(|doc: &mut ThingItem| {
+        doc.extra_properties.insert("title".to_string(), ETypeConst::Null);
+    })]
+    #[case::child(|doc: &mut ThingItem| doc.generics.push(item(ThingItemKind::Doc, "nested")))]
+    fn should_reject_malformed_doc(#[case] modify: fn(&mut ThingItem)) {
+        let mut doc = item(ThingItemKind::Doc, "text");
+        modify(&mut doc);
+        let mut children = vec![doc];
+
+        assert!(take_docs(&mut children, &mut HashMap::default()).is_err());
+    }
+
+    #[test]
+    fn should_reject_doc_property_and_children() {
+        let mut children = vec![item(ThingItemKind::Doc, "text")];
+        let mut props = HashMap::default();
+        props.insert("doc".to_string(), ETypeConst::String("other".into()));
+
+        assert!(take_docs(&mut children, &mut props).is_err());
+    }
+}
@@ -1,24 +1,46 @@
 use crate::etype::eitem::EItemInfo;
+use crate::etype::estruct::EStructField;
+use crate::etype::property::default_properties::PROP_FIELD_INTEGER;
 use crate::json_utils::repr::JsonRepr;
 use crate::m_try;
 use crate::registry::ETypesRegistry;
+use crate::validation::ids::numeric::NumericIds;
+use crate::validation::ids::string::StringIds;
 use crate::value::EValue;
 use atomic_refcell::AtomicRefCell;
 use diagnostic::context::DiagnosticContextMut;
 use miette::{miette, Context};
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::sync::{Arc, LazyLock};
 use ustr::{Ustr, UstrMap};
 
+pub mod asset;
+pub mod base64;
+pub mod bitflags;
+pub mod colors;
+pub mod fixes;
 pub mod ids;
+pub mod localized;
+pub mod vec2;
 
 static VALIDATORS: LazyLock<AtomicRefCell<UstrMap<Arc<dyn DataValidator>>>> =
     LazyLock::new(|| AtomicRefCell::new(default_validators().collect()));
 
 fn default_validators() -> impl Iterator<Item = (Ustr, Arc<dyn DataValidator>)> {
-    let v: Vec<Arc<dyn DataValidator>> =
-        vec![Arc::new(ids::numeric::Id), Arc::new(ids::numeric::Ref)];
+    let v: Vec<Arc<dyn DataValidator>> = vec![
+        Arc::new(ids::validators::Id::<NumericIds>::default()),
+        Arc::new(ids::validators::Ref::<NumericIds>::default()),
+        Arc::new(ids::validators::Id::<StringIds>::default()),
+        Arc::new(ids::validators::Ref::<StringIds>::default()),
+        Arc::new(bitflags::Bitflags),
+        Arc::new(vec2::Vec2),
+        Arc::new(colors::Color),
+        Arc::new(base64::Base64),
+        Arc::new(localized::Localized),
+        Arc::new(asset::Asset),
+    ];
     v.into_iter().map(|item| (Ustr::from(&item.name()), item))
 }
 
@@ -106,7 +128,11 @@ fn validate_inner(
         match data {
             EValue::Null => {}
             EValue::Boolean { .. } => {}
-            EValue::Number { .. } => {}
+            EValue::Number { value } => {
+                if item.is_some_and(|i| PROP_FIELD_INTEGER.get(i.extra_properties(), false)) {
+                    validate_integer(ctx.enter_inline(), value.0);
+                }
+            }
             EValue::String { .. } => {}
             EValue::Struct { ident, fields } => {
                 let obj = registry.get_struct(ident).ok_or_else(|| {
@@ -148,6 +174,14 @@ fn validate_inner(
                         Some(&field.ty),
                         data_field,
                     )?;
+
+                    validate_conditions(
+                        ctx.enter_field(field.name.as_str()),
+                        field,
+                        fields,
+                        data_field,
+                        default.get(&field.name),
+                    );
                 }
             }
             EValue::Enum { data, variant } => {
@@ -171,7 +205,7 @@ fn validate_inner(
                     data,
                 )?;
             }
-            EValue::List { values, .. } => {
+            EValue::List { values, .. } | EValue::Tuple { values, .. } => {
                 for (idx, value) in values.iter().enumerate() {
                     validate_inner(registry, ctx.enter_index(idx), None, value)?;
                 }
@@ -186,3 +220,66 @@ fn validate_inner(
     })
     .with_context(|| format!("in path `{}`", ctx.path()))
 }
+
+/// Checks the `required_if` and `visible_if` conditions of the struct field
+fn validate_conditions(
+    mut ctx: DiagnosticContextMut,
+    field: &EStructField,
+    fields: &BTreeMap<Ustr, EValue>,
+    value: &EValue,
+    default: Option<&EValue>,
+) {
+    let is_default = default == Some(value);
+
+    if let Some(condition) = &field.required_if {
+        match condition.eval(fields) {
+            Err(err) => ctx.emit_error(miette!(
+                code = "condition/invalid",
+                "bad `required_if` condition: {}",
+                err
+            )),
+            Ok(true) if is_default => ctx.emit_error(miette!(
+                code = "field/required",
+                "field is required when `{}`",
+                condition
+            )),
+            Ok(_) => {}
+        }
+    }
+
+    if let Some(condition) = &field.visible_if {
+        match condition.eval(fields) {
+            Err(err) => ctx.emit_error(miette!(
+                code = "condition/invalid",
+                "bad `visible_if` condition: {}",
+                err
+            )),
+            Ok(false) if !is_default => ctx.emit_warning(miette!(
+                code = "field/unused-value",
+                "field has a non-default value, but is only used when `{}`",
+                condition
+            )),
+            Ok(_) => {}
+        }
+    }
+}
+
+/// Largest integer magnitude that can be stored in [crate::value::ENumber]
+/// without colliding with its neighbours, same as `Number.MAX_SAFE_INTEGER`
+pub const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+fn validate_integer(mut ctx: DiagnosticContextMut, value: f64) {
+    if value.fract() != 0.0 {
+        ctx.emit_error(miette!(
+            code = "number/not-integer",
+            "expected an integer, but got {}",
+            value
+        ));
+    } else if value.abs() > MAX_SAFE_INTEGER {
+        ctx.emit_warning(miette!(
+            code = "number/unsafe-integer",
+            "integer {} is larger than 2^53 - 1 and can't be stored without precision loss",
+            value
+        ));
+    }
+}
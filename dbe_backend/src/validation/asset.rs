@@ -0,0 +1,60 @@
+use crate::etype::eitem::EItemInfo;
+use crate::project::assets::{AssetFilter, ProjectAssets};
+use crate::registry::ETypesRegistry;
+use crate::validation::DataValidator;
+use crate::value::EValue;
+use diagnostic::context::DiagnosticContextMut;
+use itertools::Itertools;
+use miette::miette;
+use std::borrow::Cow;
+
+/// Checks that the asset path points to an existing project file
+///
+/// Empty paths are treated as a missing asset and are not checked
+#[derive(Debug)]
+pub struct Asset;
+
+impl DataValidator for Asset {
+    fn name(&self) -> Cow<'static, str> {
+        "asset".into()
+    }
+
+    fn clear_cache(&self, _registry: &ETypesRegistry) {}
+
+    fn validate(
+        &self,
+        registry: &ETypesRegistry,
+        mut ctx: DiagnosticContextMut,
+        item: Option<&EItemInfo>,
+        data: &EValue,
+    ) -> miette::Result<()> {
+        let path = data.try_as_string()?;
+        if path.is_empty() {
+            return Ok(());
+        }
+
+        let filter = item
+            .map(|item| AssetFilter::from_props(item.extra_properties()))
+            .unwrap_or_default();
+
+        let full_path = filter.project_path(path);
+        if !filter.extension_allowed(&full_path) {
+            ctx.emit_error(miette!(
+                code = "asset/bad-extension",
+                "asset `{}` has unsupported extension, expected one of: {}",
+                path,
+                filter.extensions.iter().join(", ")
+            ));
+        }
+
+        if !ProjectAssets::of(registry).read().exists(&full_path) {
+            ctx.emit_error(miette!(
+                code = "asset/missing",
+                "asset file `{}` does not exist",
+                full_path
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::etype::eitem::EItemInfo;
+use crate::json_utils::repr::base64::{as_byte, DATA_FIELD};
+use crate::registry::ETypesRegistry;
+use crate::validation::DataValidator;
+use crate::value::EValue;
+use diagnostic::context::DiagnosticContextMut;
+use miette::{bail, miette};
+use std::borrow::Cow;
+use ustr::Ustr;
+
+#[derive(Debug)]
+pub struct Base64;
+
+impl DataValidator for Base64 {
+    fn name(&self) -> Cow<'static, str> {
+        "base64".into()
+    }
+
+    fn clear_cache(&self, _registry: &ETypesRegistry) {}
+
+    fn validate(
+        &self,
+        _registry: &ETypesRegistry,
+        mut ctx: DiagnosticContextMut,
+        _item: Option<&EItemInfo>,
+        data: &EValue,
+    ) -> miette::Result<()> {
+        let EValue::Struct { fields, .. } = data else {
+            bail!("expected a base64 struct value, got {:?}", data);
+        };
+
+        let Some(value) = fields.get(&Ustr::from(DATA_FIELD)) else {
+            return Ok(());
+        };
+
+        let EValue::List { values, .. } = value else {
+            bail!("field `{}` must be a list, got {:?}", DATA_FIELD, value);
+        };
+
+        let mut ctx = ctx.enter_field(DATA_FIELD);
+        for (idx, value) in values.iter().enumerate() {
+            let value = value.try_as_number()?.0;
+            if as_byte(value).is_none() {
+                ctx.enter_index(idx).emit_error(miette!(
+                    code = "base64/bad-byte",
+                    "expected a byte value from 0 to 255, got {}",
+                    value
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
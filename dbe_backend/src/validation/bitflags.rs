@@ -0,0 +1,54 @@
+use crate::etype::eitem::EItemInfo;
+use crate::json_utils::repr::bitflags::{flag_bits, UNKNOWN_BITS_FIELD};
+use crate::registry::ETypesRegistry;
+use crate::validation::DataValidator;
+use crate::value::EValue;
+use diagnostic::context::DiagnosticContextMut;
+use diagnostic::rules::with_code;
+use miette::{bail, miette};
+use std::borrow::Cow;
+use ustr::Ustr;
+
+#[derive(Debug)]
+pub struct Bitflags;
+
+impl DataValidator for Bitflags {
+    fn name(&self) -> Cow<'static, str> {
+        "bitflags".into()
+    }
+
+    fn clear_cache(&self, _registry: &ETypesRegistry) {}
+
+    fn validate(
+        &self,
+        registry: &ETypesRegistry,
+        mut ctx: DiagnosticContextMut,
+        _item: Option<&EItemInfo>,
+        data: &EValue,
+    ) -> miette::Result<()> {
+        let EValue::Struct { ident, fields } = data else {
+            bail!("expected a bitflags struct value, got {:?}", data);
+        };
+
+        if let Err(err) = flag_bits(registry, *ident) {
+            ctx.emit_error(with_code(err, "bitflags/invalid-type"));
+            return Ok(());
+        }
+
+        let unknown = match fields.get(&Ustr::from(UNKNOWN_BITS_FIELD)) {
+            None => 0,
+            Some(value) => value.try_as_number()?.0 as u64,
+        };
+
+        if unknown != 0 {
+            ctx.emit_error(miette!(
+                code = "bitflags/unknown-bits",
+                "bits {:#x} don't correspond to any flag of `{}`",
+                unknown,
+                ident
+            ));
+        }
+
+        Ok(())
+    }
+}
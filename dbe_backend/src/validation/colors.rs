@@ -0,0 +1,48 @@
+use crate::etype::eitem::EItemInfo;
+use crate::registry::ETypesRegistry;
+use crate::validation::DataValidator;
+use crate::value::EValue;
+use diagnostic::context::DiagnosticContextMut;
+use miette::{bail, miette};
+use std::borrow::Cow;
+use ustr::Ustr;
+
+#[derive(Debug)]
+pub struct Color;
+
+impl DataValidator for Color {
+    fn name(&self) -> Cow<'static, str> {
+        "color".into()
+    }
+
+    fn clear_cache(&self, _registry: &ETypesRegistry) {}
+
+    fn validate(
+        &self,
+        _registry: &ETypesRegistry,
+        mut ctx: DiagnosticContextMut,
+        _item: Option<&EItemInfo>,
+        data: &EValue,
+    ) -> miette::Result<()> {
+        let EValue::Struct { fields, .. } = data else {
+            bail!("expected a color struct value, got {:?}", data);
+        };
+
+        for name in ["r", "g", "b", "a"] {
+            let Some(value) = fields.get(&Ustr::from(name)) else {
+                continue;
+            };
+
+            let value = value.try_as_number()?.0;
+            if !(0.0..=1.0).contains(&value) {
+                ctx.enter_field(name).emit_error(miette!(
+                    code = "color/out-of-range",
+                    "color component must be in range from 0 to 1, got {}",
+                    value
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
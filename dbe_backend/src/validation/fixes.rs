@@ -0,0 +1,91 @@
+//! Machine-applicable fixes of the validation diagnostics
+//!
+//! Validators attach fixes to the diagnostics via
+//! [DiagnosticContextMut::emit_with_fixes](diagnostic::context::DiagnosticContextMut::emit_with_fixes),
+//! with a [FixEdit] as the edit. Fixes are applied via
+//! [Project::apply_fix](crate::project::Project::apply_fix), which records
+//! the change in the undo history.
+
+use crate::value::EValue;
+use diagnostic::diagnostic::DiagnosticFix;
+use diagnostic::path::{DiagnosticPath, DiagnosticPathSegment};
+use ustr::Ustr;
+
+#[derive(Debug, Clone)]
+pub enum FixEdit {
+    /// Replaces the value at the fix path
+    SetValue {
+        value: EValue,
+        /// Whether the value must not be assigned by other fixes applied in
+        /// the same batch, e.g. for newly allocated IDs
+        unique: bool,
+    },
+    /// Drops the unknown JSON fields of the file
+    DropUnknownFields,
+}
+
+/// Creates a fix replacing the value at the path
+pub fn set_value_fix(
+    title: impl Into<String>,
+    path: &DiagnosticPath,
+    value: EValue,
+) -> DiagnosticFix {
+    DiagnosticFix::new(
+        title,
+        path.clone(),
+        FixEdit::SetValue {
+            value,
+            unique: false,
+        },
+    )
+}
+
+/// Creates a fix replacing the value at the path with a value that must be
+/// unique, see [FixEdit::SetValue]
+pub fn unique_value_fix(
+    title: impl Into<String>,
+    path: &DiagnosticPath,
+    value: EValue,
+) -> DiagnosticFix {
+    DiagnosticFix::new(
+        title,
+        path.clone(),
+        FixEdit::SetValue {
+            value,
+            unique: true,
+        },
+    )
+}
+
+/// Returns the value at the diagnostic path
+///
+/// Variant segments only match the currently selected enum variant
+pub fn value_at_path_mut<'a>(
+    value: &'a mut EValue,
+    path: &DiagnosticPath,
+) -> Option<&'a mut EValue> {
+    let mut value = value;
+    for segment in path.iter() {
+        value = match (segment, value) {
+            (DiagnosticPathSegment::Field(name), EValue::Struct { fields, .. }) => {
+                fields.get_mut(&Ustr::from(name.as_ref()))?
+            }
+            (DiagnosticPathSegment::Variant(name), EValue::Enum { variant, data }) => {
+                if variant.variant_name().as_str() != name.as_ref() {
+                    return None;
+                }
+                &mut **data
+            }
+            (
+                DiagnosticPathSegment::Index(idx),
+                EValue::List { values, .. } | EValue::Tuple { values, .. },
+            ) => values.get_mut(*idx)?,
+            // Map entries are validated in the order of the keys
+            (DiagnosticPathSegment::Index(idx), EValue::Map { values, .. }) => {
+                values.values_mut().nth(*idx)?
+            }
+            _ => return None,
+        };
+    }
+    Some(value)
+}
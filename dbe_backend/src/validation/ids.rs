@@ -1 +1,235 @@
+use crate::etype::eenum::pattern::EnumPattern;
+use crate::etype::EDataType;
+use crate::json_utils::JsonValue;
+use crate::registry::config::merge::ConfigMerge;
+use crate::registry::ETypesRegistry;
+use crate::value::id::ETypeId;
+use crate::value::{ENumber, EValue};
+use camino::Utf8PathBuf;
+use itertools::Itertools;
+use miette::{bail, miette, Diagnostic, Report};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::sync::Arc;
+use thiserror::Error;
+use ustr::{Ustr, UstrMap};
+use utils::map::HashSet;
+
 pub mod numeric;
+pub mod registry;
+pub mod string;
+pub mod validators;
+
+/// Kind of the IDs stored in the `id` field of the ID structs
+///
+/// Every kind has its own ID and reference validators and reprs, and
+/// collects its IDs into a separate [registry::IdRegistry]
+pub trait IdKind: Debug + Send + Sync + 'static {
+    type Id: Copy + Eq + Ord + Hash + Debug + Display + DeserializeOwned + Send + Sync;
+
+    /// Name of the ID repr and validator, also used as the config name
+    const ID: &'static str;
+    /// Name of the reference repr and validator
+    const REF: &'static str;
+    /// Name of the JSON type of the ID, for error messages
+    const EXPECTED: &'static str;
+
+    /// Enum pattern of the ID values
+    fn enum_pattern() -> EnumPattern;
+
+    /// Checks whenever the type can hold the plain ID value
+    fn is_id_type(ty: &EDataType) -> bool;
+
+    /// Extracts the ID from its JSON representation
+    fn id_json(json: &JsonValue) -> Option<JsonValue>;
+
+    fn from_value(value: &EValue) -> Option<Self::Id>;
+
+    fn to_value(id: Self::Id) -> EValue;
+
+    /// Parses the ID typed in by the user
+    fn parse(text: &str) -> Option<Self::Id>;
+
+    /// Checks the ID value itself, before any conflicts are checked
+    fn check_id(_ty: Ustr, _id: Self::Id) -> Option<Report> {
+        None
+    }
+
+    /// Returns the first ID after the given one that satisfies the
+    /// predicate, or `None` if the kind has no ordering of the free IDs
+    fn next_free_id(
+        _id: Self::Id,
+        _is_free: impl FnMut(Self::Id) -> miette::Result<bool>,
+    ) -> miette::Result<Option<Self::Id>> {
+        Ok(None)
+    }
+
+    /// Returns the distance between the IDs, used to suggest the closest ID
+    /// for an undefined reference
+    fn distance(_a: Self::Id, _b: Self::Id) -> Option<ENumber> {
+        None
+    }
+}
+
+pub(crate) fn config<K: IdKind>(
+    registry: &ETypesRegistry,
+) -> miette::Result<Arc<ReservedIdConfig<K::Id>>> {
+    registry.config().get::<ReservedIdConfig<K::Id>>(K::ID)
+}
+
+/// Extracts the `Id` generic argument of an ID struct type
+pub(crate) fn extract_generic_arg(
+    registry: &ETypesRegistry,
+    ident: &ETypeId,
+) -> miette::Result<Ustr> {
+    let obj_data = registry
+        .get_struct(ident)
+        .ok_or_else(|| miette!("unknown object type or not a struct: `{:?}`", ident))?;
+
+    let arg = obj_data
+        .generic_arguments_values
+        .iter()
+        .exactly_one()
+        .map_err(|_| {
+            miette!(
+                "expected struct with exactly one generic argument called `Id`, got {:?} arguments",
+                obj_data.generic_arguments.len()
+            )
+        })?
+        .ty();
+
+    if obj_data.generic_arguments[0] != "Id" {
+        bail!(
+            "expected generic argument to be called `Id`, got `{}`",
+            obj_data.generic_arguments[0]
+        );
+    }
+
+    let EDataType::Const { value } = arg else {
+        bail!(
+            "generic argument `Id` is expected to be a constant string, got `{:?}`",
+            arg
+        );
+    };
+
+    let Some(arg) = value.as_string() else {
+        bail!(
+            "generic argument `Id` is expected to be a constant string, got constant `{:?}`",
+            value
+        );
+    };
+
+    Ok(arg)
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum IdValidationError<Id: Debug + Display + 'static> {
+    #[error("duplicate ID of type {}", .ty)]
+    DuplicateId { ty: Ustr, others: Vec<String> },
+    #[error("ID {} of type {} is reserved", .id, .ty)]
+    ReservedId { ty: Ustr, id: Id },
+    #[error("ID of type {} conflicts with ID of type {}", .ty, .conflicting)]
+    FromConflicting {
+        ty: Ustr,
+        conflicting: Ustr,
+        #[source]
+        error: Box<IdValidationError<Id>>,
+    },
+}
+
+impl<Id: Debug + Display + 'static> Diagnostic for IdValidationError<Id> {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(match self {
+            IdValidationError::DuplicateId { .. } => "ids/duplicate",
+            IdValidationError::ReservedId { .. } => "ids/reserved",
+            IdValidationError::FromConflicting { .. } => "ids/conflicting",
+        }))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            IdValidationError::DuplicateId { ty: _, others } => Some(Box::new(format!(
+                "duplicate IDs in:\n\t{}",
+                others.join("\n\t")
+            ))),
+            _ => None,
+        }
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        match self {
+            IdValidationError::FromConflicting { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(bound(deserialize = "Id: Deserialize<'de> + Eq + Hash"))]
+pub(crate) struct ReservedIdConfig<Id> {
+    pub types: UstrMap<ReservedIdTypeConfig<Id>>,
+}
+
+impl<Id> Default for ReservedIdConfig<Id> {
+    fn default() -> Self {
+        Self {
+            types: Default::default(),
+        }
+    }
+}
+
+impl<Id: Eq + Hash> ConfigMerge for ReservedIdConfig<Id> {
+    fn merge(
+        &mut self,
+        paths: &[&Utf8PathBuf],
+        other: Self,
+        other_path: &Utf8PathBuf,
+    ) -> miette::Result<()> {
+        ConfigMerge::merge(&mut self.types, paths, other.types, other_path)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(bound(deserialize = "Id: Deserialize<'de> + Eq + Hash"))]
+pub(crate) struct ReservedIdTypeConfig<Id> {
+    /// Reserved IDs
+    #[serde(default)]
+    pub reserved_ids: HashSet<Id>,
+    /// Other types that this type cannot share IDs with
+    #[serde(default)]
+    pub conflicting_types: Vec<Ustr>,
+    /// Other types that this ID can be satisfied by
+    #[serde(default)]
+    pub satisfied_by_types: Vec<Ustr>,
+}
+
+impl<Id: Eq + Hash> ConfigMerge for ReservedIdTypeConfig<Id> {
+    fn merge(
+        &mut self,
+        paths: &[&Utf8PathBuf],
+        other: Self,
+        other_path: &Utf8PathBuf,
+    ) -> miette::Result<()> {
+        ConfigMerge::merge(
+            &mut self.reserved_ids,
+            paths,
+            other.reserved_ids,
+            other_path,
+        )?;
+        ConfigMerge::merge(
+            &mut self.conflicting_types,
+            paths,
+            other.conflicting_types,
+            other_path,
+        )?;
+        ConfigMerge::merge(
+            &mut self.satisfied_by_types,
+            paths,
+            other.satisfied_by_types,
+            other_path,
+        )?;
+        Ok(())
+    }
+}
@@ -1,585 +1,190 @@
-use crate::etype::eitem::EItemInfo;
+use crate::etype::eenum::pattern::EnumPattern;
 use crate::etype::EDataType;
-use crate::registry::config::merge::ConfigMerge;
-use crate::registry::ETypesRegistry;
-use crate::validation::DataValidator;
-use crate::value::id::ETypeId;
+use crate::json_utils::JsonValue;
+use crate::validation::ids::registry::{
+    conflicting_claims, reserved_ids, used_ids, Data, IdRegistry,
+};
+use crate::validation::ids::{config, IdKind};
 use crate::value::{ENumber, EValue};
-use camino::Utf8PathBuf;
-use diagnostic::context::DiagnosticContextMut;
 use itertools::Itertools;
-use miette::{bail, miette, Context, Diagnostic};
-use parking_lot::RwLock;
-use parking_lot::RwLockWriteGuard;
-use serde::Deserialize;
-use smallvec::{smallvec, SmallVec};
-use std::borrow::Cow;
+use ordered_float::OrderedFloat;
 use std::collections::BTreeSet;
-use std::fmt::Display;
-use std::sync::Arc;
-use thiserror::Error;
-use ustr::{Ustr, UstrMap};
-use utils::map::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use ustr::Ustr;
 
-#[derive(Default)]
-pub struct NumericIDsRegistry {
-    ids: UstrMap<HashMap<ENumber, BTreeSet<String>>>,
-}
-
-type Data = RwLock<NumericIDsRegistry>;
-
-/// Extracts the struct type and ID from an ID struct value
-fn ty_and_id(registry: &ETypesRegistry, data: &EValue) -> miette::Result<(Ustr, ENumber)> {
-    let EValue::Struct { ident, fields } = data else {
-        bail!("expected an ID struct value, got {:?}", data);
-    };
-
-    let arg = extract_generic_arg(registry, ident)?;
+/// Numeric IDs, validated by `ids/numeric` and `ids/numeric_ref`
+#[derive(Debug, Default)]
+pub struct NumericIds;
 
-    let Some(id) = fields.get(&Ustr::from("id")) else {
-        bail!(
-            "expected field `id` in an ID struct, got {:?}",
-            fields.keys().map(|x| x.as_str()).join(", ")
-        );
-    };
+pub type NumericIDRegistry<'a> = IdRegistry<'a, NumericIds>;
 
-    let id = id.try_as_number().map_err(|_| {
-        miette!(
-            "expected numeric value for `id` field in an ID struct, got {:?}",
-            id
-        )
-    })?;
+impl IdKind for NumericIds {
+    type Id = ENumber;
 
-    Ok((arg, *id))
-}
-
-fn extract_generic_arg(registry: &ETypesRegistry, ident: &ETypeId) -> miette::Result<Ustr> {
-    let obj_data = registry
-        .get_struct(ident)
-        .ok_or_else(|| miette!("unknown object type or not a struct: `{:?}`", ident))?;
+    const ID: &'static str = "ids/numeric";
+    const REF: &'static str = "ids/numeric_ref";
+    const EXPECTED: &'static str = "number";
 
-    let arg = obj_data
-        .generic_arguments_values
-        .iter()
-        .exactly_one()
-        .map_err(|_| {
-            miette!(
-                "expected struct with exactly one generic argument called `Id`, got {:?} arguments",
-                obj_data.generic_arguments.len()
-            )
-        })?
-        .ty();
-
-    if obj_data.generic_arguments[0] != "Id" {
-        bail!(
-            "expected generic argument to be called `Id`, got `{}`",
-            obj_data.generic_arguments[0]
-        );
+    fn enum_pattern() -> EnumPattern {
+        EnumPattern::Number
     }
 
-    let EDataType::Const { value } = arg else {
-        bail!(
-            "generic argument `Id` is expected to be a constant string, got `{:?}`",
-            arg
-        );
-    };
-
-    let Some(arg) = value.as_string() else {
-        bail!(
-            "generic argument `Id` is expected to be a constant string, got constant `{:?}`",
-            value
-        );
-    };
-
-    Ok(arg)
-}
-
-#[derive(Debug, Error)]
-enum IdValidationError {
-    #[error("duplicate ID of type {}", .ty)]
-    DuplicateId { ty: Ustr, others: Vec<String> },
-    #[error("ID {} of type {} is reserved", .id, .ty)]
-    ReservedId { ty: Ustr, id: ENumber },
-    #[error("ID of type {} conflicts with ID of type {}", .ty, .conflicting)]
-    FromConflicting {
-        ty: Ustr,
-        conflicting: Ustr,
-        #[source]
-        error: Box<IdValidationError>,
-    },
-}
-
-impl Diagnostic for IdValidationError {
-    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
-        match self {
-            IdValidationError::DuplicateId { ty: _, others } => Some(Box::new(format!(
-                "duplicate IDs in:\n\t{}",
-                others.join("\n\t")
-            ))),
-            _ => None,
-        }
+    fn is_id_type(ty: &EDataType) -> bool {
+        ty.is_number()
     }
 
-    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
-        match self {
-            IdValidationError::FromConflicting { error, .. } => Some(error.as_ref()),
-            _ => None,
-        }
+    fn id_json(json: &JsonValue) -> Option<JsonValue> {
+        json.as_f64().map(JsonValue::from)
     }
-}
-
-#[derive(Debug, Default, Deserialize)]
-struct ReservedIdConfig {
-    types: UstrMap<ReservedIdTypeConfig>,
-}
 
-impl ConfigMerge for ReservedIdConfig {
-    fn merge(
-        &mut self,
-        paths: &[&Utf8PathBuf],
-        other: Self,
-        other_path: &Utf8PathBuf,
-    ) -> miette::Result<()> {
-        ConfigMerge::merge(&mut self.types, paths, other.types, other_path)
+    fn from_value(value: &EValue) -> Option<Self::Id> {
+        value.try_as_number().ok().copied()
     }
-}
 
-#[derive(Debug, Default, Deserialize)]
-struct ReservedIdTypeConfig {
-    /// Reserved IDs
-    #[serde(default)]
-    reserved_ids: HashSet<ENumber>,
-    /// Other types that this type cannot share IDs with
-    #[serde(default)]
-    conflicting_types: Vec<Ustr>,
-    /// Other types that this ID can be satisfied by
-    #[serde(default)]
-    satisfied_by_types: Vec<Ustr>,
-}
-
-impl ConfigMerge for ReservedIdTypeConfig {
-    fn merge(
-        &mut self,
-        paths: &[&Utf8PathBuf],
-        other: Self,
-        other_path: &Utf8PathBuf,
-    ) -> miette::Result<()> {
-        ConfigMerge::merge(
-            &mut self.reserved_ids,
-            paths,
-            other.reserved_ids,
-            other_path,
-        )?;
-        ConfigMerge::merge(
-            &mut self.conflicting_types,
-            paths,
-            other.conflicting_types,
-            other_path,
-        )?;
-        ConfigMerge::merge(
-            &mut self.satisfied_by_types,
-            paths,
-            other.satisfied_by_types,
-            other_path,
-        )?;
-        Ok(())
+    fn to_value(id: Self::Id) -> EValue {
+        EValue::Number { value: id }
     }
-}
 
-fn config(reg: &ETypesRegistry) -> miette::Result<Arc<ReservedIdConfig>> {
-    reg.config().get::<ReservedIdConfig>("ids/numeric")
-}
-
-#[derive(Debug, Copy, Clone)]
-pub struct NumericIDRegistry<'a> {
-    registry: &'a ETypesRegistry,
-}
-
-impl<'a> NumericIDRegistry<'a> {
-    pub fn of(registry: &'a ETypesRegistry) -> Self {
-        Self { registry }
+    fn parse(text: &str) -> Option<Self::Id> {
+        let id = text.trim().parse::<i32>().ok()?;
+        Some(OrderedFloat(id as f64))
     }
 
-    pub fn is_id_assignable(&self, from: Ustr, to: Ustr) -> miette::Result<bool> {
-        let config = config(self.registry)?;
-        fn is_assignable(
-            config: &ReservedIdConfig,
-            from: Ustr,
-            to: Ustr,
-            visited: &mut SmallVec<[Ustr; 2]>,
-        ) -> bool {
-            if visited.contains(&to) {
-                return false;
-            }
-            visited.push(to);
-
-            if from == to {
-                return true;
-            }
-
-            if let Some(cfg) = config.types.get(&to) {
-                for ty in &cfg.satisfied_by_types {
-                    if is_assignable(config, from, *ty, visited) {
-                        return true;
-                    }
-                }
-            }
-
-            false
+    fn next_free_id(
+        id: Self::Id,
+        mut is_free: impl FnMut(Self::Id) -> miette::Result<bool>,
+    ) -> miette::Result<Option<Self::Id>> {
+        let mut new_id = OrderedFloat(id.0.floor() + 1.0);
+        while !is_free(new_id)? {
+            new_id.0 += 1.0;
         }
-
-        Ok(is_assignable(&config, from, to, &mut smallvec![]))
+        Ok(Some(new_id))
     }
 
-    /// Checks if the given types are assignable, i.e. if an ID of type `from`
-    /// can be assigned to a reference of type `to`
-    pub fn is_id_assignable_ty(&self, from: EDataType, to: EDataType) -> miette::Result<bool> {
-        let (EDataType::Object { ident: from }, EDataType::Object { ident: to }) = (from, to)
-        else {
-            bail!("expected object types, got {:?} and {:?}", from, to);
-        };
-
-        let from_arg = extract_generic_arg(self.registry, &from)?;
-        let to_arg = extract_generic_arg(self.registry, &to)?;
-
-        self.is_id_assignable(from_arg, to_arg)
+    fn distance(a: Self::Id, b: Self::Id) -> Option<ENumber> {
+        Some(OrderedFloat((a.0 - b.0).abs()))
     }
+}
 
-    /// Returns the location of the given ID, if it exists
+impl NumericIDRegistry<'_> {
+    /// Reports the allocation of the IDs of every category, with up to
+    /// `next_free` free IDs after the highest claimed ID
     ///
-    /// Exact format of the location is not specified, but it should be
-    /// human-readable
-    pub fn location_for_id(
-        &self,
-        ref_ty: EDataType,
-        id: ENumber,
-    ) -> miette::Result<Option<String>> {
-        let EDataType::Object { ident } = ref_ty else {
-            bail!("expected object type, got {:?}", ref_ty);
-        };
+    /// Categories are sorted by name. IDs are recorded during validation, so
+    /// the report is only complete once all files are validated
+    pub fn allocation_report(&self, next_free: usize) -> miette::Result<Vec<IdAllocation>> {
+        let config = config::<NumericIds>(self.registry)?;
 
-        let category = extract_generic_arg(self.registry, &ident)?;
-
-        let config = config(self.registry)?;
-
-        let reg = self.registry.extra_data::<Data>();
+        let reg = self.registry.extra_data::<Data<NumericIds>>();
         let reg = reg.read();
 
-        fn location(
-            config: &ReservedIdConfig,
-            reg: &NumericIDsRegistry,
-            id: ENumber,
-            category: Ustr,
-            visited: &mut SmallVec<[Ustr; 2]>,
-        ) -> Option<String> {
-            if visited.contains(&category) {
-                return None;
-            }
-            visited.push(category);
-
-            if let Some(id) = reg
-                .ids
-                .get(&category)
-                .and_then(|m| m.get(&id))
-                .and_then(|s| s.iter().next())
-            {
-                return Some(id.clone());
-            };
-
-            if let Some(cfg) = config.types.get(&category) {
-                for ty in &cfg.satisfied_by_types {
-                    if let Some(id) = location(config, reg, id, *ty, visited) {
-                        return Some(id);
-                    }
-                }
-            }
-
-            None
-        }
-
-        Ok(location(&config, &reg, id, category, &mut smallvec![]))
-    }
-
-    /// Runs the provided closure with an iterator over available IDs for the
-    /// given type, as well as the reserved IDs, and all IDs for types that
-    /// this type is satisfied by
-    pub fn with_available_ids<T>(
-        &self,
-        ref_ty: EDataType,
-        cb: impl FnOnce(AvailableIdsIter) -> miette::Result<T>,
-    ) -> miette::Result<T> {
-        let EDataType::Object { ident } = ref_ty else {
-            bail!("expected object type, got {:?}", ref_ty);
-        };
-
-        let category = extract_generic_arg(self.registry, &ident)?;
-
-        let config = config(self.registry)?;
-
-        let reg = self.registry.extra_data::<Data>();
-        let reg = reg.read();
-
-        let iter = AvailableIdsIter::new(&reg, &config, smallvec![category]);
-
-        cb(iter)
-    }
-}
-
-/// Iterator over available IDs for a given type
-///
-/// This iterator will yield all available IDs for a given type, as well as the
-/// reserved IDs for that type, and the same for all `satisfied_by` types
-pub struct AvailableIdsIter<'a> {
-    reg: &'a NumericIDsRegistry,
-    cfg: &'a ReservedIdConfig,
-    categories: SmallVec<[Ustr; 1]>,
-    cur_iter: Option<std::collections::hash_map::Iter<'a, ENumber, BTreeSet<String>>>,
-    cur_reserved_iter: Option<std::collections::hash_set::Iter<'a, ENumber>>,
-}
-
-impl<'a> AvailableIdsIter<'a> {
-    fn new(
-        reg: &'a NumericIDsRegistry,
-        cfg: &'a ReservedIdConfig,
-        categories: SmallVec<[Ustr; 1]>,
-    ) -> Self {
-        Self {
-            reg,
-            cfg,
-            categories,
-            cur_iter: None,
-            cur_reserved_iter: None,
-        }
-    }
-}
-
-impl<'a> Iterator for AvailableIdsIter<'a> {
-    type Item = (&'a ENumber, Option<&'a BTreeSet<String>>);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(iter) = &mut self.cur_iter {
-            if let Some(next) = iter.next() {
-                return Some((next.0, Some(next.1)));
+        let categories: BTreeSet<Ustr> = reg
+            .ids
+            .iter()
+            .filter(|(_, ids)| ids.values().any(|paths| !paths.is_empty()))
+            .map(|(ty, _)| *ty)
+            .chain(config.types.keys().copied())
+            .collect();
+
+        let mut report = vec![];
+        for category in categories {
+            let used = used_ids(&reg, category);
+            let reserved = reserved_ids(&config, category);
+
+            // IDs claimed by this category and the categories it conflicts with
+            let mut blocked: BTreeSet<ENumber> = used.union(&reserved).copied().collect();
+            let mut conflicts = vec![];
+            for (conflicting, claimed) in conflicting_claims(&reg, &config, category) {
+                conflicts.extend(used.intersection(&claimed).map(|id| (*id, conflicting)));
+                blocked.extend(claimed);
             }
-            {
-                self.cur_iter = None;
-            }
-        } else if let Some(iter) = &mut self.cur_reserved_iter {
-            if let Some(next) = iter.next() {
-                return Some((next, None));
-            } else {
-                self.cur_reserved_iter = None;
-            }
-        }
-
-        // cur iter is exhausted, get the next one
-
-        while let Some(category) = self.categories.pop() {
-            let reserved_ids = if let Some(config) = self.cfg.types.get(&category) {
-                self.categories
-                    .extend(config.satisfied_by_types.iter().cloned());
-                (!config.reserved_ids.is_empty()).then(|| config.reserved_ids.iter())
-            } else {
-                None
+            conflicts.sort();
+
+            let own = used.union(&reserved).copied().collect::<BTreeSet<_>>();
+            let gaps = match (own.first(), own.last()) {
+                (Some(low), Some(high)) => blocked
+                    .range(low..=high)
+                    .tuple_windows()
+                    .filter(|(a, b)| b.0 > a.0 + 1.0)
+                    .map(|(a, b)| OrderedFloat(a.0 + 1.0)..=OrderedFloat(b.0 - 1.0))
+                    .collect(),
+                _ => vec![],
             };
 
-            let ids = self
-                .reg
-                .ids
-                .get(&category)
-                .filter(|ids| !ids.is_empty())
-                .map(|ids| ids.iter());
-
-            if reserved_ids.is_none() && ids.is_none() {
-                continue;
-            }
-
-            self.cur_iter = ids;
-            self.cur_reserved_iter = reserved_ids;
-            return self.next();
-        }
-
-        // all categories are exhausted, end of iteration
-        None
-    }
-}
-
-#[derive(Debug)]
-pub struct Id;
-
-impl DataValidator for Id {
-    fn name(&self) -> Cow<'static, str> {
-        "ids/numeric".into()
-    }
-
-    fn clear_cache(&self, registry: &ETypesRegistry) {
-        let ids = registry.extra_data::<Data>();
-        let mut ids = ids.write();
-        ids.ids.clear();
-    }
-
-    fn validate(
-        &self,
-        registry: &ETypesRegistry,
-        mut ctx: DiagnosticContextMut,
-        _item: Option<&EItemInfo>,
-        data: &EValue,
-    ) -> miette::Result<()> {
-        let reg = registry.extra_data::<Data>();
-        let mut reg = reg.write();
-
-        fn check_id_conflicts(
-            registry: &ETypesRegistry,
-            reg: &mut RwLockWriteGuard<NumericIDsRegistry>,
-            mut ctx: DiagnosticContextMut,
-            ty: Ustr,
-            id: ENumber,
-            visited: &mut SmallVec<[Ustr; 2]>,
-            top: bool,
-        ) -> miette::Result<Vec<IdValidationError>> {
-            if visited.contains(&ty) {
-                return Ok(vec![]);
-            }
-            visited.push(ty);
-
-            let mut errors = vec![];
-
-            let config = config(registry)?;
-            if let Some(config) = config.types.get(&ty) {
-                if config.reserved_ids.contains(&id) {
-                    errors.push(IdValidationError::ReservedId { ty, id });
-                }
-                for conflicting in &config.conflicting_types {
-                    let conflicts = check_id_conflicts(
-                        registry,
-                        reg,
-                        ctx.enter_inline(),
-                        *conflicting,
-                        id,
-                        visited,
-                        false,
-                    )
-                    .with_context(|| format!("in conflicting type `{}`", conflicting))?;
-                    errors.extend(conflicts.into_iter().map(|error| {
-                        IdValidationError::FromConflicting {
-                            ty,
-                            conflicting: *conflicting,
-                            error: Box::new(error),
-                        }
-                    }));
+            let mut free = vec![];
+            let mut candidate = own
+                .last()
+                .map(|id| OrderedFloat(id.0.floor() + 1.0))
+                .unwrap_or_default();
+            while free.len() < next_free {
+                if !blocked.contains(&candidate) {
+                    free.push(candidate);
                 }
+                candidate.0 += 1.0;
             }
 
-            let ids = reg.ids.entry(ty).or_default().entry(id).or_default();
-
-            // trace!("validating id: `{}` for type `{:?}`", id, ty);
-
-            let mut filter_out_path = None;
-            if top {
-                let path = ctx.full_path();
-                ids.insert(path.to_string());
-                filter_out_path = Some(path);
-            }
-
-            if ids.len() > if top { 1 } else { 0 } {
-                errors.push(IdValidationError::DuplicateId {
-                    ty,
-                    others: if let Some(path) = filter_out_path {
-                        ids.iter()
-                            .filter(|other| *other != &path)
-                            .cloned()
-                            .collect()
-                    } else {
-                        ids.iter().cloned().collect()
-                    },
-                });
-            }
-            Ok(errors)
+            report.push(IdAllocation {
+                category,
+                highest: used.last().copied(),
+                used: id_ranges(&used),
+                reserved: id_ranges(&reserved),
+                gaps,
+                next_free: free,
+                conflicts,
+            });
         }
 
-        let (ty, id) = ty_and_id(registry, data)?;
-
-        let errors = check_id_conflicts(
-            registry,
-            &mut reg,
-            ctx.enter_inline(),
-            ty,
-            id,
-            &mut smallvec![],
-            true,
-        )?;
-
-        for error in errors {
-            ctx.emit_error(error.into());
+        Ok(report)
+    }
+}
+
+/// Allocation of the numeric IDs of a single category
+#[derive(Debug, Clone)]
+pub struct IdAllocation {
+    pub category: Ustr,
+    /// Ranges of the IDs used by the category
+    pub used: Vec<RangeInclusive<ENumber>>,
+    /// Ranges of the IDs reserved for the category
+    pub reserved: Vec<RangeInclusive<ENumber>>,
+    /// Free ranges between the lowest and highest used or reserved IDs,
+    /// excluding the IDs claimed by the conflicting categories
+    pub gaps: Vec<RangeInclusive<ENumber>>,
+    /// Highest used ID
+    pub highest: Option<ENumber>,
+    /// Free IDs after the highest used or reserved ID
+    pub next_free: Vec<ENumber>,
+    /// Used IDs that are also used or reserved by a conflicting category
+    pub conflicts: Vec<(ENumber, Ustr)>,
+}
+
+/// Groups the sorted IDs into ranges of consecutive IDs
+fn id_ranges(ids: &BTreeSet<ENumber>) -> Vec<RangeInclusive<ENumber>> {
+    let mut ranges: Vec<RangeInclusive<ENumber>> = vec![];
+    for id in ids {
+        match ranges.last_mut() {
+            Some(range) if range.end().0 + 1.0 == id.0 => *range = *range.start()..=*id,
+            _ => ranges.push(*id..=*id),
         }
-
-        Ok(())
     }
-}
-
-#[derive(Debug)]
-pub struct Ref;
-
-impl DataValidator for Ref {
-    fn name(&self) -> Cow<'static, str> {
-        "ids/numeric_ref".into()
-    }
-
-    fn clear_cache(&self, _registry: &ETypesRegistry) {
-        // cache is cleared by the `Id` validator
-    }
-
-    fn validate(
-        &self,
-        registry: &ETypesRegistry,
-        mut ctx: DiagnosticContextMut,
-        _item: Option<&EItemInfo>,
-        data: &EValue,
-    ) -> miette::Result<()> {
-        let reg = registry.extra_data::<Data>();
-        let mut reg = reg.write();
-
-        let (ty, id) = ty_and_id(registry, data)?;
-
-        fn check_id_exists(
-            registry: &ETypesRegistry,
-            reg: &mut RwLockWriteGuard<NumericIDsRegistry>,
-            ty: Ustr,
-            id: ENumber,
-            visited: &mut SmallVec<[Ustr; 2]>,
-        ) -> miette::Result<bool> {
-            if visited.contains(&ty) {
-                return Ok(false);
-            }
-            visited.push(ty);
-
-            let config = config(registry)?;
-            if let Some(config) = config.types.get(&ty) {
-                if config.reserved_ids.contains(&id) {
-                    return Ok(true);
-                }
-                for satisfied in &config.satisfied_by_types {
-                    if check_id_exists(registry, reg, *satisfied, id, visited)
-                        .with_context(|| format!("in satisfied_by type `{}`", satisfied))?
-                    {
-                        return Ok(true);
-                    }
-                }
-            }
-
-            Ok(!reg
-                .ids
-                .entry(ty)
-                .or_default()
-                .entry(id)
-                .or_default()
-                .is_empty())
-        }
-
-        if !check_id_exists(registry, &mut reg, ty, id, &mut smallvec![])? {
-            ctx.emit_error(miette!("ID {} of type `{}` is not defined", id, ty));
-        }
-
-        Ok(())
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::id_ranges;
+    use ordered_float::OrderedFloat;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[], &[])]
+    #[case(&[5.0], &[(5.0, 5.0)])]
+    #[case(&[1.0, 2.0, 3.0, 7.0, 9.0, 10.0], &[(1.0, 3.0), (7.0, 7.0), (9.0, 10.0)])]
+    fn should_group_id_ranges(#[case] ids: &[f64], #[case] expected: &[(f64, f64)]) {
+        let ids = ids.iter().copied().map(OrderedFloat).collect();
+        let ranges = id_ranges(&ids)
+            .into_iter()
+            .map(|range| (range.start().0, range.end().0))
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, expected);
     }
 }
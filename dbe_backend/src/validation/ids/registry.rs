@@ -0,0 +1,431 @@
+use crate::etype::EDataType;
+use crate::registry::ETypesRegistry;
+use crate::validation::ids::{config, extract_generic_arg, IdKind, ReservedIdConfig};
+use miette::bail;
+use parking_lot::RwLock;
+use smallvec::{smallvec, SmallVec};
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use ustr::{Ustr, UstrMap};
+use utils::map::HashMap;
+
+/// IDs and references of a single [IdKind], collected during validation
+pub struct IdsRegistry<K: IdKind> {
+    pub(super) ids: UstrMap<HashMap<K::Id, BTreeSet<String>>>,
+    /// Locations of the reference values pointing at the IDs
+    pub(super) refs: UstrMap<HashMap<K::Id, BTreeSet<String>>>,
+    /// Changed every time the IDs are cleared or validated, unique across
+    /// all registries
+    pub(super) generation: u64,
+}
+
+impl<K: IdKind> IdsRegistry<K> {
+    /// Marks the IDs as changed
+    pub(super) fn bump_generation(&mut self) {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        self.generation = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<K: IdKind> Default for IdsRegistry<K> {
+    fn default() -> Self {
+        Self {
+            ids: Default::default(),
+            refs: Default::default(),
+            generation: 0,
+        }
+    }
+}
+
+pub(super) type Data<K> = RwLock<IdsRegistry<K>>;
+
+#[derive(Debug)]
+pub struct IdRegistry<'a, K: IdKind> {
+    pub(super) registry: &'a ETypesRegistry,
+    _kind: PhantomData<K>,
+}
+
+impl<K: IdKind> Clone for IdRegistry<'_, K> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K: IdKind> Copy for IdRegistry<'_, K> {}
+
+impl<'a, K: IdKind> IdRegistry<'a, K> {
+    pub fn of(registry: &'a ETypesRegistry) -> Self {
+        Self {
+            registry,
+            _kind: PhantomData,
+        }
+    }
+
+    /// Returns the current generation of the IDs, so the reports built from
+    /// them can be cached until the next change
+    pub fn generation(&self) -> u64 {
+        self.registry.extra_data::<Data<K>>().read().generation
+    }
+
+    pub fn is_id_assignable(&self, from: Ustr, to: Ustr) -> miette::Result<bool> {
+        let config = config::<K>(self.registry)?;
+        fn is_assignable<Id>(
+            config: &ReservedIdConfig<Id>,
+            from: Ustr,
+            to: Ustr,
+            visited: &mut SmallVec<[Ustr; 2]>,
+        ) -> bool {
+            if visited.contains(&to) {
+                return false;
+            }
+            visited.push(to);
+
+            if from == to {
+                return true;
+            }
+
+            if let Some(cfg) = config.types.get(&to) {
+                for ty in &cfg.satisfied_by_types {
+                    if is_assignable(config, from, *ty, visited) {
+                        return true;
+                    }
+                }
+            }
+
+            false
+        }
+
+        Ok(is_assignable(&config, from, to, &mut smallvec![]))
+    }
+
+    /// Checks if the given types are assignable, i.e. if an ID of type `from`
+    /// can be assigned to a reference of type `to`
+    pub fn is_id_assignable_ty(&self, from: EDataType, to: EDataType) -> miette::Result<bool> {
+        let (EDataType::Object { ident: from }, EDataType::Object { ident: to }) = (from, to)
+        else {
+            bail!("expected object types, got {:?} and {:?}", from, to);
+        };
+
+        let from_arg = extract_generic_arg(self.registry, &from)?;
+        let to_arg = extract_generic_arg(self.registry, &to)?;
+
+        self.is_id_assignable(from_arg, to_arg)
+    }
+
+    /// Returns the location of the given ID, if it exists
+    ///
+    /// Exact format of the location is not specified, but it should be
+    /// human-readable
+    pub fn location_for_id(&self, ref_ty: EDataType, id: K::Id) -> miette::Result<Option<String>> {
+        let EDataType::Object { ident } = ref_ty else {
+            bail!("expected object type, got {:?}", ref_ty);
+        };
+
+        let category = extract_generic_arg(self.registry, &ident)?;
+
+        let config = config::<K>(self.registry)?;
+
+        let reg = self.registry.extra_data::<Data<K>>();
+        let reg = reg.read();
+
+        fn location<K: IdKind>(
+            config: &ReservedIdConfig<K::Id>,
+            reg: &IdsRegistry<K>,
+            id: K::Id,
+            category: Ustr,
+            visited: &mut SmallVec<[Ustr; 2]>,
+        ) -> Option<String> {
+            if visited.contains(&category) {
+                return None;
+            }
+            visited.push(category);
+
+            if let Some(id) = reg
+                .ids
+                .get(&category)
+                .and_then(|m| m.get(&id))
+                .and_then(|s| s.iter().next())
+            {
+                return Some(id.clone());
+            };
+
+            if let Some(cfg) = config.types.get(&category) {
+                for ty in &cfg.satisfied_by_types {
+                    if let Some(id) = location(config, reg, id, *ty, visited) {
+                        return Some(id);
+                    }
+                }
+            }
+
+            None
+        }
+
+        Ok(location(&config, &reg, id, category, &mut smallvec![]))
+    }
+
+    /// Returns the IDs that are not referenced by any reference value, as
+    /// the type, the ID, and the location of the ID
+    ///
+    /// References are recorded during validation, so the result is only
+    /// complete once all files are validated
+    pub fn unreferenced_ids(&self) -> Vec<(Ustr, K::Id, String)> {
+        let reg = self.registry.extra_data::<Data<K>>();
+        let reg = reg.read();
+
+        let mut unreferenced = vec![];
+        for (ty, ids) in &reg.ids {
+            let refs = reg.refs.get(ty);
+            for (id, locations) in ids {
+                if refs.is_some_and(|refs| refs.contains_key(id)) {
+                    continue;
+                }
+                for location in locations {
+                    unreferenced.push((*ty, *id, location.clone()));
+                }
+            }
+        }
+        unreferenced.sort();
+        unreferenced
+    }
+
+    /// Returns the references from other files to the IDs defined in the
+    /// file, sorted by the ID
+    ///
+    /// These references become undefined when the file is deleted
+    pub fn references_to_file(&self, file: &str) -> Vec<IdReference<K::Id>> {
+        self.references(|definition, location| {
+            location_file(definition) == file && location_file(location) != file
+        })
+    }
+
+    /// Returns the references from the file to the IDs defined in other
+    /// files, sorted by the ID
+    pub fn references_from_file(&self, file: &str) -> Vec<IdReference<K::Id>> {
+        self.references(|definition, location| {
+            location_file(location) == file && location_file(definition) != file
+        })
+    }
+
+    fn references(&self, filter: impl Fn(&str, &str) -> bool) -> Vec<IdReference<K::Id>> {
+        let reg = self.registry.extra_data::<Data<K>>();
+        let reg = reg.read();
+
+        let mut references = vec![];
+        for (category, refs) in &reg.refs {
+            for (id, locations) in refs {
+                let Some(definitions) = reg.ids.get(category).and_then(|ids| ids.get(id)) else {
+                    continue;
+                };
+                for definition in definitions {
+                    for location in locations {
+                        if filter(definition, location) {
+                            references.push(IdReference {
+                                category: *category,
+                                id: *id,
+                                definition: definition.clone(),
+                                location: location.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        references.sort();
+        references
+    }
+
+    /// Returns the IDs defined for the category
+    pub fn used_ids(&self, category: Ustr) -> BTreeSet<K::Id> {
+        let reg = self.registry.extra_data::<Data<K>>();
+        let reg = reg.read();
+        used_ids(&reg, category)
+    }
+
+    /// Returns the IDs that the category can't use besides its own IDs: its
+    /// reserved IDs, and the IDs used or reserved by the categories it
+    /// conflicts with
+    pub fn blocked_ids(&self, category: Ustr) -> miette::Result<BTreeSet<K::Id>> {
+        let config = config::<K>(self.registry)?;
+
+        let reg = self.registry.extra_data::<Data<K>>();
+        let reg = reg.read();
+
+        let mut blocked = reserved_ids(&config, category);
+        for (_, claimed) in conflicting_claims(&reg, &config, category) {
+            blocked.extend(claimed);
+        }
+        Ok(blocked)
+    }
+
+    /// Runs the provided closure with an iterator over available IDs for the
+    /// given type, as well as the reserved IDs, and all IDs for types that
+    /// this type is satisfied by
+    pub fn with_available_ids<T>(
+        &self,
+        ref_ty: EDataType,
+        cb: impl FnOnce(AvailableIdsIter<K>) -> miette::Result<T>,
+    ) -> miette::Result<T> {
+        let EDataType::Object { ident } = ref_ty else {
+            bail!("expected object type, got {:?}", ref_ty);
+        };
+
+        let category = extract_generic_arg(self.registry, &ident)?;
+
+        let config = config::<K>(self.registry)?;
+
+        let reg = self.registry.extra_data::<Data<K>>();
+        let reg = reg.read();
+
+        let iter = AvailableIdsIter::new(&reg, &config, smallvec![category]);
+
+        cb(iter)
+    }
+}
+
+/// Reference of an ID
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct IdReference<Id> {
+    pub category: Ustr,
+    pub id: Id,
+    /// Location of the ID definition
+    pub definition: String,
+    /// Location of the referencing value
+    pub location: String,
+}
+
+/// Returns the file of the `file@path` location
+pub fn location_file(location: &str) -> &str {
+    location.split_once('@').map_or(location, |(file, _)| file)
+}
+
+pub(super) fn used_ids<K: IdKind>(reg: &IdsRegistry<K>, category: Ustr) -> BTreeSet<K::Id> {
+    reg.ids
+        .get(&category)
+        .into_iter()
+        .flatten()
+        .filter(|(_, paths)| !paths.is_empty())
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+pub(super) fn reserved_ids<Id: Copy + Ord>(
+    config: &ReservedIdConfig<Id>,
+    category: Ustr,
+) -> BTreeSet<Id> {
+    config
+        .types
+        .get(&category)
+        .into_iter()
+        .flat_map(|cfg| cfg.reserved_ids.iter().copied())
+        .collect()
+}
+
+/// Returns the IDs used or reserved by each of the categories that the given
+/// category conflicts with, directly or transitively
+pub(super) fn conflicting_claims<K: IdKind>(
+    reg: &IdsRegistry<K>,
+    config: &ReservedIdConfig<K::Id>,
+    category: Ustr,
+) -> Vec<(Ustr, BTreeSet<K::Id>)> {
+    let mut claims = vec![];
+    let mut visited: SmallVec<[Ustr; 2]> = smallvec![category];
+    let mut stack = config
+        .types
+        .get(&category)
+        .map(|cfg| cfg.conflicting_types.clone())
+        .unwrap_or_default();
+    while let Some(conflicting) = stack.pop() {
+        if visited.contains(&conflicting) {
+            continue;
+        }
+        visited.push(conflicting);
+        if let Some(cfg) = config.types.get(&conflicting) {
+            stack.extend(cfg.conflicting_types.iter().copied());
+        }
+
+        let mut claimed = used_ids(reg, conflicting);
+        claimed.extend(reserved_ids(config, conflicting));
+        claims.push((conflicting, claimed));
+    }
+    claims
+}
+
+/// Iterator over available IDs for a given type
+///
+/// This iterator will yield all available IDs for a given type, as well as the
+/// reserved IDs for that type, and the same for all `satisfied_by` types
+pub struct AvailableIdsIter<'a, K: IdKind> {
+    reg: &'a IdsRegistry<K>,
+    cfg: &'a ReservedIdConfig<K::Id>,
+    categories: SmallVec<[Ustr; 1]>,
+    cur_iter: Option<std::collections::hash_map::Iter<'a, K::Id, BTreeSet<String>>>,
+    cur_reserved_iter: Option<std::collections::hash_set::Iter<'a, K::Id>>,
+}
+
+impl<'a, K: IdKind> AvailableIdsIter<'a, K> {
+    fn new(
+        reg: &'a IdsRegistry<K>,
+        cfg: &'a ReservedIdConfig<K::Id>,
+        categories: SmallVec<[Ustr; 1]>,
+    ) -> Self {
+        Self {
+            reg,
+            cfg,
+            categories,
+            cur_iter: None,
+            cur_reserved_iter: None,
+        }
+    }
+}
+
+impl<'a, K: IdKind> Iterator for AvailableIdsIter<'a, K> {
+    type Item = (&'a K::Id, Option<&'a BTreeSet<String>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(iter) = &mut self.cur_iter {
+            if let Some(next) = iter.next() {
+                return Some((next.0, Some(next.1)));
+            }
+            {
+                self.cur_iter = None;
+            }
+        } else if let Some(iter) = &mut self.cur_reserved_iter {
+            if let Some(next) = iter.next() {
+                return Some((next, None));
+            } else {
+                self.cur_reserved_iter = None;
+            }
+        }
+
+        // cur iter is exhausted, get the next one
+
+        while let Some(category) = self.categories.pop() {
+            let reserved_ids = if let Some(config) = self.cfg.types.get(&category) {
+                self.categories
+                    .extend(config.satisfied_by_types.iter().cloned());
+                (!config.reserved_ids.is_empty()).then(|| config.reserved_ids.iter())
+            } else {
+                None
+            };
+
+            let ids = self
+                .reg
+                .ids
+                .get(&category)
+                .filter(|ids| !ids.is_empty())
+                .map(|ids| ids.iter());
+
+            if reserved_ids.is_none() && ids.is_none() {
+                continue;
+            }
+
+            self.cur_iter = ids;
+            self.cur_reserved_iter = reserved_ids;
+            return self.next();
+        }
+
+        // all categories are exhausted, end of iteration
+        None
+    }
+}
@@ -0,0 +1,53 @@
+use crate::etype::eenum::pattern::EnumPattern;
+use crate::etype::EDataType;
+use crate::json_utils::JsonValue;
+use crate::validation::ids::registry::IdRegistry;
+use crate::validation::ids::IdKind;
+use crate::value::EValue;
+use miette::{miette, Report};
+use ustr::Ustr;
+
+/// String IDs, validated by `ids/string` and `ids/string_ref`
+#[derive(Debug, Default)]
+pub struct StringIds;
+
+pub type StringIDRegistry<'a> = IdRegistry<'a, StringIds>;
+
+impl IdKind for StringIds {
+    type Id = Ustr;
+
+    const ID: &'static str = "ids/string";
+    const REF: &'static str = "ids/string_ref";
+    const EXPECTED: &'static str = "string";
+
+    fn enum_pattern() -> EnumPattern {
+        EnumPattern::String
+    }
+
+    fn is_id_type(ty: &EDataType) -> bool {
+        ty.is_string()
+    }
+
+    fn id_json(json: &JsonValue) -> Option<JsonValue> {
+        json.as_str().map(JsonValue::from)
+    }
+
+    fn from_value(value: &EValue) -> Option<Self::Id> {
+        value.try_as_string().ok().map(|id| Ustr::from(id.as_str()))
+    }
+
+    fn to_value(id: Self::Id) -> EValue {
+        EValue::String {
+            value: id.to_string(),
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self::Id> {
+        Some(Ustr::from(text))
+    }
+
+    fn check_id(ty: Ustr, id: Self::Id) -> Option<Report> {
+        id.is_empty()
+            .then(|| miette!(code = "ids/empty", "ID of type `{}` must not be empty", ty))
+    }
+}
@@ -0,0 +1,344 @@
+use crate::etype::eitem::EItemInfo;
+use crate::registry::ETypesRegistry;
+use crate::validation::fixes::{set_value_fix, unique_value_fix};
+use crate::validation::ids::registry::{Data, IdRegistry, IdsRegistry};
+use crate::validation::ids::{config, extract_generic_arg, IdKind, IdValidationError};
+use crate::validation::DataValidator;
+use crate::value::EValue;
+use diagnostic::context::DiagnosticContextMut;
+use diagnostic::diagnostic::DiagnosticLevel;
+use itertools::Itertools;
+use miette::{bail, miette, Context};
+use parking_lot::RwLockWriteGuard;
+use smallvec::{smallvec, SmallVec};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use ustr::Ustr;
+
+/// Extracts the struct type and ID from an ID struct value
+fn ty_and_id<K: IdKind>(registry: &ETypesRegistry, data: &EValue) -> miette::Result<(Ustr, K::Id)> {
+    let EValue::Struct { ident, fields } = data else {
+        bail!("expected an ID struct value, got {:?}", data);
+    };
+
+    let arg = extract_generic_arg(registry, ident)?;
+
+    let Some(id) = fields.get(&Ustr::from("id")) else {
+        bail!(
+            "expected field `id` in an ID struct, got {:?}",
+            fields.keys().map(|x| x.as_str()).join(", ")
+        );
+    };
+
+    let id = K::from_value(id).ok_or_else(|| {
+        miette!(
+            "expected {} value for `id` field in an ID struct, got {:?}",
+            K::EXPECTED,
+            id
+        )
+    })?;
+
+    Ok((arg, id))
+}
+
+/// Returns a copy of the ID struct value with the ID replaced
+fn with_id<K: IdKind>(data: &EValue, id: K::Id) -> EValue {
+    let mut data = data.clone();
+    if let EValue::Struct { fields, .. } = &mut data {
+        fields.insert(Ustr::from("id"), K::to_value(id));
+    }
+    data
+}
+
+/// Validator registering the ID definitions of the [IdKind]
+#[derive(Debug, Default)]
+pub struct Id<K>(PhantomData<K>);
+
+impl<K: IdKind> DataValidator for Id<K> {
+    fn name(&self) -> Cow<'static, str> {
+        K::ID.into()
+    }
+
+    fn clear_cache(&self, registry: &ETypesRegistry) {
+        let ids = registry.extra_data::<Data<K>>();
+        let mut ids = ids.write();
+        ids.ids.clear();
+        ids.refs.clear();
+        ids.bump_generation();
+    }
+
+    fn validate(
+        &self,
+        registry: &ETypesRegistry,
+        mut ctx: DiagnosticContextMut,
+        _item: Option<&EItemInfo>,
+        data: &EValue,
+    ) -> miette::Result<()> {
+        let reg = registry.extra_data::<Data<K>>();
+        let mut reg = reg.write();
+        reg.bump_generation();
+
+        fn check_id_conflicts<K: IdKind>(
+            registry: &ETypesRegistry,
+            reg: &mut RwLockWriteGuard<IdsRegistry<K>>,
+            mut ctx: DiagnosticContextMut,
+            ty: Ustr,
+            id: K::Id,
+            visited: &mut SmallVec<[Ustr; 2]>,
+            top: bool,
+        ) -> miette::Result<Vec<IdValidationError<K::Id>>> {
+            if visited.contains(&ty) {
+                return Ok(vec![]);
+            }
+            visited.push(ty);
+
+            let mut errors = vec![];
+
+            let config = config::<K>(registry)?;
+            if let Some(config) = config.types.get(&ty) {
+                if config.reserved_ids.contains(&id) {
+                    errors.push(IdValidationError::ReservedId { ty, id });
+                }
+                for conflicting in &config.conflicting_types {
+                    let conflicts = check_id_conflicts(
+                        registry,
+                        reg,
+                        ctx.enter_inline(),
+                        *conflicting,
+                        id,
+                        visited,
+                        false,
+                    )
+                    .with_context(|| format!("in conflicting type `{}`", conflicting))?;
+                    errors.extend(conflicts.into_iter().map(|error| {
+                        IdValidationError::FromConflicting {
+                            ty,
+                            conflicting: *conflicting,
+                            error: Box::new(error),
+                        }
+                    }));
+                }
+            }
+
+            let ids = reg.ids.entry(ty).or_default().entry(id).or_default();
+
+            let mut filter_out_path = None;
+            if top {
+                let path = ctx.full_path();
+                ids.insert(path.to_string());
+                filter_out_path = Some(path);
+            }
+
+            if ids.len() > if top { 1 } else { 0 } {
+                errors.push(IdValidationError::DuplicateId {
+                    ty,
+                    others: if let Some(path) = filter_out_path {
+                        ids.iter()
+                            .filter(|other| *other != &path)
+                            .cloned()
+                            .collect()
+                    } else {
+                        ids.iter().cloned().collect()
+                    },
+                });
+            }
+            Ok(errors)
+        }
+
+        /// Checks that the ID is neither used nor reserved by the type and
+        /// the types it conflicts with
+        fn is_id_free<K: IdKind>(
+            registry: &ETypesRegistry,
+            reg: &IdsRegistry<K>,
+            ty: Ustr,
+            id: K::Id,
+            visited: &mut SmallVec<[Ustr; 2]>,
+        ) -> miette::Result<bool> {
+            if visited.contains(&ty) {
+                return Ok(true);
+            }
+            visited.push(ty);
+
+            if reg
+                .ids
+                .get(&ty)
+                .and_then(|ids| ids.get(&id))
+                .is_some_and(|paths| !paths.is_empty())
+            {
+                return Ok(false);
+            }
+
+            let config = config::<K>(registry)?;
+            if let Some(config) = config.types.get(&ty) {
+                if config.reserved_ids.contains(&id) {
+                    return Ok(false);
+                }
+                for conflicting in &config.conflicting_types {
+                    if !is_id_free(registry, reg, *conflicting, id, visited)? {
+                        return Ok(false);
+                    }
+                }
+            }
+
+            Ok(true)
+        }
+
+        let (ty, id) = ty_and_id::<K>(registry, data)?;
+
+        if let Some(error) = K::check_id(ty, id) {
+            ctx.emit_error(error);
+            return Ok(());
+        }
+
+        let errors = check_id_conflicts(
+            registry,
+            &mut reg,
+            ctx.enter_inline(),
+            ty,
+            id,
+            &mut smallvec![],
+            true,
+        )?;
+
+        let path = ctx.full_path();
+        for error in errors {
+            let mut fixes = vec![];
+            // The first occurrence keeps the ID, others are reassigned
+            if let IdValidationError::DuplicateId { others, .. } = &error {
+                if others.iter().any(|other| *other < path) {
+                    let new_id = K::next_free_id(id, |candidate| {
+                        is_id_free(registry, &reg, ty, candidate, &mut smallvec![])
+                    })?;
+                    if let Some(new_id) = new_id {
+                        fixes.push(unique_value_fix(
+                            format!("Reassign to ID {}", new_id),
+                            ctx.path(),
+                            with_id::<K>(data, new_id),
+                        ));
+                    }
+                }
+            }
+            ctx.emit_with_fixes(error.into(), DiagnosticLevel::Error, fixes);
+        }
+
+        Ok(())
+    }
+}
+
+/// Validator checking the references to the IDs of the [IdKind]
+#[derive(Debug, Default)]
+pub struct Ref<K>(PhantomData<K>);
+
+impl<K: IdKind> DataValidator for Ref<K> {
+    fn name(&self) -> Cow<'static, str> {
+        K::REF.into()
+    }
+
+    fn clear_cache(&self, _registry: &ETypesRegistry) {
+        // cache is cleared by the `Id` validator
+    }
+
+    fn validate(
+        &self,
+        registry: &ETypesRegistry,
+        mut ctx: DiagnosticContextMut,
+        _item: Option<&EItemInfo>,
+        data: &EValue,
+    ) -> miette::Result<()> {
+        let reg = registry.extra_data::<Data<K>>();
+        let mut reg = reg.write();
+
+        let (ty, id) = ty_and_id::<K>(registry, data)?;
+
+        fn check_id_exists<K: IdKind>(
+            registry: &ETypesRegistry,
+            reg: &mut RwLockWriteGuard<IdsRegistry<K>>,
+            ty: Ustr,
+            id: K::Id,
+            location: &str,
+            visited: &mut SmallVec<[Ustr; 2]>,
+        ) -> miette::Result<bool> {
+            if visited.contains(&ty) {
+                return Ok(false);
+            }
+            visited.push(ty);
+
+            let config = config::<K>(registry)?;
+            if let Some(config) = config.types.get(&ty) {
+                if config.reserved_ids.contains(&id) {
+                    return Ok(true);
+                }
+                for satisfied in &config.satisfied_by_types {
+                    if check_id_exists(registry, reg, *satisfied, id, location, visited)
+                        .with_context(|| format!("in satisfied_by type `{}`", satisfied))?
+                    {
+                        return Ok(true);
+                    }
+                }
+            }
+
+            let exists = !reg
+                .ids
+                .entry(ty)
+                .or_default()
+                .entry(id)
+                .or_default()
+                .is_empty();
+            if exists {
+                reg.refs
+                    .entry(ty)
+                    .or_default()
+                    .entry(id)
+                    .or_default()
+                    .insert(location.to_string());
+            }
+            Ok(exists)
+        }
+
+        let location = ctx.full_path();
+        if !check_id_exists(registry, &mut reg, ty, id, &location, &mut smallvec![])? {
+            // Available IDs are looked up via the registry lock
+            drop(reg);
+
+            let mut fixes = vec![];
+            let closest = IdRegistry::<K>::of(registry).with_available_ids(data.ty(), |ids| {
+                Ok(ids
+                    .filter_map(|(other, paths)| Some((K::distance(id, *other)?, other, paths)))
+                    .min_by_key(|(distance, _, _)| *distance)
+                    .map(|(_, other, paths)| (*other, paths.and_then(|p| p.first().cloned()))))
+            })?;
+            if let Some((closest, location)) = closest {
+                let title = match location {
+                    Some(location) => format!("Point to ID {} at `{}`", closest, location),
+                    None => format!("Point to reserved ID {}", closest),
+                };
+                fixes.push(set_value_fix(
+                    title,
+                    ctx.path(),
+                    with_id::<K>(data, closest),
+                ));
+            }
+            let default = data.ty().default_value(registry);
+            if *default != *data {
+                fixes.push(set_value_fix(
+                    "Clear reference",
+                    ctx.path(),
+                    default.into_owned(),
+                ));
+            }
+
+            ctx.emit_with_fixes(
+                miette!(
+                    code = "ids/undefined-ref",
+                    "ID {} of type `{}` is not defined",
+                    id,
+                    ty
+                ),
+                DiagnosticLevel::Error,
+                fixes,
+            );
+        }
+
+        Ok(())
+    }
+}
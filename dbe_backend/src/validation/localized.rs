@@ -0,0 +1,71 @@
+use crate::etype::eitem::EItemInfo;
+use crate::json_utils::repr::localized::KEY_FIELD;
+use crate::project::localization::Localization;
+use crate::registry::ETypesRegistry;
+use crate::validation::DataValidator;
+use crate::value::EValue;
+use diagnostic::context::DiagnosticContextMut;
+use itertools::Itertools;
+use miette::{bail, miette};
+use std::borrow::Cow;
+
+#[derive(Debug)]
+pub struct Localized;
+
+impl DataValidator for Localized {
+    fn name(&self) -> Cow<'static, str> {
+        "localized".into()
+    }
+
+    fn clear_cache(&self, registry: &ETypesRegistry) {
+        Localization::of(registry).read().clear_used();
+    }
+
+    fn validate(
+        &self,
+        registry: &ETypesRegistry,
+        mut ctx: DiagnosticContextMut,
+        _item: Option<&EItemInfo>,
+        data: &EValue,
+    ) -> miette::Result<()> {
+        let EValue::Struct { .. } = data else {
+            bail!("expected a localized string struct value, got {:?}", data);
+        };
+
+        let key = data.try_get_field(KEY_FIELD)?.try_as_string()?;
+
+        if key.is_empty() {
+            ctx.emit_error(miette!(
+                code = "localization/empty-key",
+                "translation key must not be empty"
+            ));
+            return Ok(());
+        }
+
+        let localization = Localization::of(registry);
+        let localization = localization.read();
+        localization.mark_used(key);
+
+        let missing = localization.missing_locales(key);
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        if localization.locales().count() == missing.len() {
+            ctx.emit_error(miette!(
+                code = "localization/undefined-key",
+                "translation key `{}` is not defined in any locale",
+                key
+            ));
+        } else {
+            ctx.emit_warning(miette!(
+                code = "localization/missing-translation",
+                "translation key `{}` is missing in locales: {}",
+                key,
+                missing.iter().join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+}
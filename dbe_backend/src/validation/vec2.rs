@@ -0,0 +1,54 @@
+use crate::etype::eitem::EItemInfo;
+use crate::registry::ETypesRegistry;
+use crate::validation::DataValidator;
+use crate::value::EValue;
+use diagnostic::context::DiagnosticContextMut;
+use miette::{bail, miette};
+use std::borrow::Cow;
+use ustr::Ustr;
+
+#[derive(Debug)]
+pub struct Vec2;
+
+impl DataValidator for Vec2 {
+    fn name(&self) -> Cow<'static, str> {
+        "vec2".into()
+    }
+
+    fn clear_cache(&self, _registry: &ETypesRegistry) {}
+
+    fn validate(
+        &self,
+        _registry: &ETypesRegistry,
+        mut ctx: DiagnosticContextMut,
+        _item: Option<&EItemInfo>,
+        data: &EValue,
+    ) -> miette::Result<()> {
+        let EValue::Struct { ident, fields } = data else {
+            bail!("expected a vector struct value, got {:?}", data);
+        };
+
+        for name in ["x", "y"] {
+            let Some(value) = fields.get(&Ustr::from(name)) else {
+                ctx.emit_error(miette!(
+                    code = "vec2/missing-field",
+                    "vector struct `{}` has no `{}` field",
+                    ident,
+                    name
+                ));
+                continue;
+            };
+
+            let value = value.try_as_number()?.0;
+            if !value.is_finite() {
+                ctx.enter_field(name).emit_error(miette!(
+                    code = "vec2/non-finite",
+                    "vector component must be finite, got {}",
+                    value
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
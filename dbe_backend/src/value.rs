@@ -1,7 +1,7 @@
 use crate::etype::econst::ETypeConst;
 use crate::etype::eenum::variant::EEnumVariantId;
 use crate::etype::EDataType;
-use crate::value::id::{EListId, EMapId, ETypeId};
+use crate::value::id::{EListId, EMapId, ETupleId, ETypeId};
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
@@ -62,6 +62,10 @@ pub enum EValue {
         id: EMapId,
         values: BTreeMap<EValue, EValue>,
     },
+    Tuple {
+        id: ETupleId,
+        values: Vec<EValue>,
+    },
 }
 
 impl EValue {
@@ -79,6 +83,7 @@ impl EValue {
             },
             EValue::List { id: ty, .. } => EDataType::List { id: *ty },
             EValue::Map { id, .. } => EDataType::Map { id: *id },
+            EValue::Tuple { id, .. } => EDataType::Tuple { id: *id },
         }
     }
 }
@@ -297,6 +302,13 @@ impl Display for EValue {
                     values.iter().map(|(k, v)| format!("{k}: {v}")).join(", ")
                 )
             }
+            EValue::Tuple { id, values } => {
+                write!(
+                    f,
+                    "{id}({})",
+                    values.iter().map(|e| e.to_string()).join(", ")
+                )
+            }
         }
     }
 }
@@ -324,7 +336,7 @@ impl EValue {
                 enum_data.write_json(registry, data, variant)
             })
             .with_context(|| format!("in enum variant `{}`", variant.variant_name()))?,
-            EValue::List { id: _, values } => JsonValue::Array(
+            EValue::List { values, .. } | EValue::Tuple { values, .. } => JsonValue::Array(
                 values
                     .iter()
                     .enumerate()
@@ -82,6 +82,8 @@ id_type!(EListId);
 
 id_type!(EMapId);
 
+id_type!(ETupleId);
+
 // #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 // #[serde(untagged)]
 // pub enum EValueId {
@@ -130,6 +130,7 @@ struct AppWrapper(DbeApp);
 impl AppWrapper {
     pub fn new(cx: &CreationContext, collector: EventCollector) -> Self {
         DbeApp::register_fonts(&cx.egui_ctx);
+        DbeApp::install_image_loaders(&cx.egui_ctx);
 
         let info = AppInfo {
             name: "Squidhammer".to_string(),
@@ -120,6 +120,11 @@ impl DbeApp {
         ctx.set_fonts(fonts);
     }
 
+    /// Installs loaders used for asset previews
+    pub fn install_image_loaders(ctx: &Context) {
+        egui_extras::install_image_loaders(ctx);
+    }
+
     pub fn new(info: AppInfo, collector: EventCollector) -> Self {
         ui_props::register_extra_properties();
 
@@ -301,6 +306,13 @@ impl DbeApp {
                     if ui.button("Run Graphs").clicked() {
                         project.clean_validate().unwrap_or_else(report_error);
                     }
+                    if ui
+                        .button("Reload Types")
+                        .on_hover_text("Reloads all type files without reopening the project")
+                        .clicked()
+                    {
+                        project.reload_types().unwrap_or_else(report_error);
+                    }
                 }
             });
         });
@@ -14,6 +14,7 @@ mod diagnostics_list;
 pub mod docs;
 mod file_tree;
 pub mod history;
+mod ids;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ToolPanel {
@@ -23,6 +24,7 @@ pub enum ToolPanel {
     Theme,
     Docs,
     History,
+    Ids,
 }
 
 pub struct ToolPanelViewer<'a>(pub &'a mut DbeApp);
@@ -38,6 +40,7 @@ impl ToolbarViewer for ToolPanelViewer<'_> {
             ToolPanel::Theme => "Theme".into(),
             ToolPanel::Docs => "Docs".into(),
             ToolPanel::History => "Undo History".into(),
+            ToolPanel::Ids => "ID Allocation".into(),
         }
     }
 
@@ -61,6 +64,9 @@ impl ToolbarViewer for ToolPanelViewer<'_> {
             ToolPanel::History => {
                 history::history_tab(ui, self.0);
             }
+            ToolPanel::Ids => {
+                ids::ids_tab(ui, self.0);
+            }
         }
     }
 
@@ -79,6 +85,7 @@ impl ToolbarViewer for ToolPanelViewer<'_> {
                 ToolPanel::Theme,
                 ToolPanel::Docs,
                 ToolPanel::History,
+                ToolPanel::Ids,
             ],
         )
     }
@@ -5,6 +5,7 @@ use camino::Utf8PathBuf;
 use dbe_backend::diagnostic::prelude::DiagnosticLevel;
 use egui::{Label, RichText, Ui, Widget};
 use inline_tweak::tweak;
+use tracing::info;
 
 pub fn diagnostics_tab(ui: &mut Ui, app: &mut DbeApp) {
     let Some(project) = &mut app.project else {
@@ -15,6 +16,7 @@ pub fn diagnostics_tab(ui: &mut Ui, app: &mut DbeApp) {
     };
 
     let mut open_file = None;
+    let mut apply_fix = None;
 
     ui.vertical(|ui| {
         ui.label("Diagnostics");
@@ -23,6 +25,26 @@ pub fn diagnostics_tab(ui: &mut Ui, app: &mut DbeApp) {
             project.clean_validate().unwrap_or_else(report_error);
         }
         btn_res.on_hover_text("Reruns all graphs and checks all files for errors");
+        let btn_res = ui.button("Update Baseline");
+        if btn_res.clicked() {
+            project.update_baseline().unwrap_or_else(report_error);
+        }
+        btn_res.on_hover_text(
+            "Records all current diagnostics into the baseline file, so only new issues are reported",
+        );
+        let btn_res = ui.button("Export SARIF");
+        if btn_res.clicked() {
+            project.export_sarif().unwrap_or_else(report_error);
+        }
+        btn_res.on_hover_text("Writes all current diagnostics into a SARIF file for external tools");
+        let btn_res = ui.button("Fix All");
+        if btn_res.clicked() {
+            match project.apply_all_fixes(None) {
+                Ok(count) => info!(count, "Applied diagnostic fixes"),
+                Err(err) => report_error(err),
+            }
+        }
+        btn_res.on_hover_text("Applies the first suggested fix of every diagnostic");
         egui::ScrollArea::both()
             .auto_shrink(tweak!(false))
             .show(ui, |ui| {
@@ -67,12 +89,21 @@ pub fn diagnostics_tab(ui: &mut Ui, app: &mut DbeApp) {
                                     ui.label(path.to_string());
                                 }
                                 diagnostics_column(ui, diagnostic);
+                                for fix in diagnostic.iter().flat_map(|d| &d.fixes) {
+                                    if ui.button(format!("🔧 {}", fix.title)).clicked() {
+                                        apply_fix = Some((Utf8PathBuf::from(file), fix.clone()));
+                                    }
+                                }
                             }
                         });
                 }
             });
     });
 
+    if let Some((file, fix)) = apply_fix {
+        project.apply_fix(&file, &fix).unwrap_or_else(report_error);
+    }
+
     if let Some(path) = open_file {
         app.open_tab_for(ui.ctx(), path);
     }
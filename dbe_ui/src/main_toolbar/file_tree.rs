@@ -2,6 +2,9 @@ use crate::DbeApp;
 use camino::{Utf8Path, Utf8PathBuf};
 use dbe_backend::project::io::ProjectIO;
 use dbe_backend::project::Project;
+use dbe_backend::validation::ids::numeric::NumericIDRegistry;
+use dbe_backend::validation::ids::registry::{location_file, IdReference};
+use dbe_backend::value::ENumber;
 use egui::{CollapsingHeader, Context, Label, RichText, Sense, Ui};
 use inline_tweak::tweak;
 use itertools::Itertools;
@@ -12,6 +15,7 @@ enum Command {
     OpenFile { path: Utf8PathBuf },
     NewFile { folder: Utf8PathBuf },
     NewGraph { folder: Utf8PathBuf },
+    DeleteFile { path: Utf8PathBuf },
 }
 
 pub fn file_tab(ui: &mut Ui, app: &mut DbeApp) {
@@ -40,12 +44,15 @@ fn file_tree<Io: ProjectIO>(ui: &mut Ui, project: &mut Project<Io>) -> Vec<Comma
     let mut commands = vec![];
 
     let io = &project.io;
+    let ids = NumericIDRegistry::of(&project.registry);
     show_folder(
         ui,
         "".as_ref(),
         &mut project.files.keys().peekable(),
         &|_| false,
         &|path| io.is_file_writable(path).unwrap_or(false),
+        &|path| ids.references_to_file(path.as_str()),
+        &|path| ids.references_from_file(path.as_str()),
         &mut commands,
     );
 
@@ -58,6 +65,7 @@ fn consume_commands(commands: Vec<Command>, app: &mut DbeApp, ctx: &Context) {
             Command::OpenFile { path } => app.open_tab_for(ctx, path),
             Command::NewFile { folder } => app.new_file(ctx, folder),
             Command::NewGraph { folder } => app.new_graph(ctx, folder),
+            Command::DeleteFile { path } => app.delete_file(ctx, path),
         }
     }
 }
@@ -68,6 +76,8 @@ fn show_folder(
     fs: &mut Peekable<impl Iterator<Item = impl AsRef<Utf8Path>>>,
     disabled: &impl Fn(&Utf8Path) -> bool,
     editable: &impl Fn(&Utf8Path) -> bool,
+    usages: &impl Fn(&Utf8Path) -> Vec<IdReference<ENumber>>,
+    dependencies: &impl Fn(&Utf8Path) -> Vec<IdReference<ENumber>>,
     commands: &mut Vec<Command>,
 ) {
     let is_enabled = !disabled(path);
@@ -139,22 +149,34 @@ fn show_folder(
                         &mut folder.into_iter().peekable(),
                         disabled,
                         editable,
+                        usages,
+                        dependencies,
                         commands,
                     );
                 }
                 for (file, file_name) in files {
                     let text = RichText::new(file_name);
-                    if ui
-                        .add_enabled(
-                            is_enabled,
-                            Label::new(text).sense(Sense::click()).selectable(false),
-                        )
-                        .double_clicked()
-                    {
+                    let response = ui.add_enabled(
+                        is_enabled,
+                        Label::new(text).sense(Sense::click()).selectable(false),
+                    );
+                    if response.double_clicked() {
                         commands.push(Command::OpenFile {
                             path: file.to_path_buf(),
                         });
                     }
+                    if is_enabled {
+                        response.context_menu(|ui| {
+                            file_context_menu(
+                                ui,
+                                &file,
+                                is_editable,
+                                usages,
+                                dependencies,
+                                commands,
+                            )
+                        });
+                    }
                 }
             });
 
@@ -182,3 +204,52 @@ fn folder_context_menu(ui: &mut Ui, path: &Utf8Path, commands: &mut Vec<Command>
         }
     });
 }
+
+fn file_context_menu(
+    ui: &mut Ui,
+    path: &Utf8Path,
+    is_editable: bool,
+    usages: &impl Fn(&Utf8Path) -> Vec<IdReference<ENumber>>,
+    dependencies: &impl Fn(&Utf8Path) -> Vec<IdReference<ENumber>>,
+    commands: &mut Vec<Command>,
+) {
+    ui.menu_button("Find Usages", |ui| {
+        let usages = usages(path);
+        if usages.is_empty() {
+            ui.label("IDs of this file are not referenced by other files");
+            return;
+        }
+        references_list(ui, usages, |usage| &usage.location, commands);
+    });
+    ui.menu_button("Dependencies", |ui| {
+        let dependencies = dependencies(path);
+        if dependencies.is_empty() {
+            ui.label("This file doesn't reference IDs of other files");
+            return;
+        }
+        references_list(ui, dependencies, |usage| &usage.definition, commands);
+    });
+    if is_editable && ui.button("Delete").clicked() {
+        commands.push(Command::DeleteFile {
+            path: path.to_path_buf(),
+        });
+        ui.close_menu()
+    }
+}
+
+fn references_list(
+    ui: &mut Ui,
+    references: Vec<IdReference<ENumber>>,
+    target: impl Fn(&IdReference<ENumber>) -> &String,
+    commands: &mut Vec<Command>,
+) {
+    for reference in &references {
+        let text = format!("ID {}: {}", reference.id, target(reference));
+        if ui.button(text).clicked() {
+            commands.push(Command::OpenFile {
+                path: location_file(target(reference)).into(),
+            });
+            ui.close_menu()
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use crate::error::report_error;
+use crate::DbeApp;
+use camino::Utf8PathBuf;
+use dbe_backend::project::renumber::{IdRenumbering, RenumberReport};
+use dbe_backend::registry::ETypesRegistry;
+use dbe_backend::validation::ids::numeric::{IdAllocation, NumericIDRegistry};
+use dbe_backend::value::ENumber;
+use egui::{DragValue, RichText, Ui};
+use inline_tweak::tweak;
+use itertools::Itertools;
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use tracing::info;
+use ustr::Ustr;
+
+/// Number of free IDs listed for every category
+const NEXT_FREE_COUNT: usize = 5;
+
+pub fn ids_tab(ui: &mut Ui, app: &mut DbeApp) {
+    let Some(project) = &mut app.project else {
+        ui.vertical_centered_justified(|ui| {
+            ui.label("No project is open");
+        });
+        return;
+    };
+
+    ui.label("ID Allocation");
+
+    let Some(report) = allocation_report(ui, &project.registry) else {
+        return;
+    };
+
+    if report.is_empty() {
+        ui.label("No numeric IDs are defined");
+        return;
+    }
+
+    let mapping_files = project.mapping_files().cloned().collect_vec();
+    let mut renumber = None;
+
+    egui::ScrollArea::both()
+        .auto_shrink(tweak!(false))
+        .show(ui, |ui| {
+            ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
+            for category in report.iter() {
+                let mut title = RichText::new(category.category.as_str()).monospace();
+                if !category.conflicts.is_empty() {
+                    title = title.color(ui.style().visuals.warn_fg_color);
+                }
+                egui::CollapsingHeader::new(title)
+                    .id_salt(category.category)
+                    .show(ui, |ui| {
+                        let highest = category
+                            .highest
+                            .map(|id| id.to_string())
+                            .unwrap_or_else(|| "-".to_string());
+                        ui.label(format!("Highest: {}", highest));
+                        ui.label(format!("Used: {}", format_ranges(&category.used)));
+                        ui.label(format!("Reserved: {}", format_ranges(&category.reserved)));
+                        ui.label(format!("Gaps: {}", format_ranges(&category.gaps)));
+                        ui.label(format!(
+                            "Next free: {}",
+                            category.next_free.iter().join(", ")
+                        ));
+                        for (id, other) in &category.conflicts {
+                            ui.colored_label(
+                                ui.style().visuals.warn_fg_color,
+                                format!("ID {} is also claimed by `{}`", id, other),
+                            );
+                        }
+                        ui.separator();
+                        if let Some(action) = renumber_ui(ui, category.category, &mapping_files) {
+                            renumber = Some((category.category, action));
+                        }
+                    });
+            }
+        });
+
+    if let Some((category, action)) = renumber {
+        let id = renumber_id(category);
+        match action {
+            RenumberAction::Preview(renumbering, mappings) => {
+                match project.preview_renumber_ids(category, &renumbering, &mappings) {
+                    Ok(report) => ui.data_mut(|data| {
+                        data.get_temp_mut_or_default::<RenumberInputs>(id).preview = Some(report)
+                    }),
+                    Err(err) => report_error(err),
+                }
+            }
+            RenumberAction::Apply(renumbering, mappings) => {
+                ui.data_mut(|data| {
+                    data.get_temp_mut_or_default::<RenumberInputs>(id).preview = None
+                });
+                match project.renumber_ids(category, &renumbering, &mappings) {
+                    Ok(report) => info!("{}", report),
+                    Err(err) => report_error(err),
+                }
+            }
+        }
+    }
+}
+
+/// Returns the allocation report, rebuilding it only after the IDs were
+/// changed by validation
+fn allocation_report(ui: &mut Ui, registry: &ETypesRegistry) -> Option<Arc<Vec<IdAllocation>>> {
+    let ids = NumericIDRegistry::of(registry);
+    let generation = ids.generation();
+    let id = egui::Id::new("ids_allocation_report");
+
+    let cached = ui.data(|data| data.get_temp::<(u64, Arc<Vec<IdAllocation>>)>(id));
+    if let Some((cached_generation, report)) = cached {
+        if cached_generation == generation {
+            return Some(report);
+        }
+    }
+
+    match ids.allocation_report(NEXT_FREE_COUNT) {
+        Ok(report) => {
+            let report = Arc::new(report);
+            ui.data_mut(|data| data.insert_temp(id, (generation, report.clone())));
+            Some(report)
+        }
+        Err(err) => {
+            report_error(err);
+            None
+        }
+    }
+}
+
+/// Inputs of the renumbering form of a category
+#[derive(Debug, Clone, Default)]
+struct RenumberInputs {
+    start: f64,
+    end: f64,
+    offset: f64,
+    /// Mappings files to rewrite along with the IDs
+    mappings: BTreeSet<Utf8PathBuf>,
+    /// Changes of the current inputs, shown for confirmation
+    preview: Option<RenumberReport>,
+}
+
+/// Renumbering requested by the form
+enum RenumberAction {
+    Preview(IdRenumbering, BTreeSet<Utf8PathBuf>),
+    Apply(IdRenumbering, BTreeSet<Utf8PathBuf>),
+}
+
+fn renumber_id(category: Ustr) -> egui::Id {
+    egui::Id::new((category, "renumber"))
+}
+
+/// Shows the renumbering form
+///
+/// The renumbering is previewed first, and is only applied once the
+/// previewed changes are confirmed. Editing the inputs drops the preview
+fn renumber_ui(
+    ui: &mut Ui,
+    category: Ustr,
+    mapping_files: &[Utf8PathBuf],
+) -> Option<RenumberAction> {
+    let id = renumber_id(category);
+    let mut inputs = ui.data_mut(|data| data.get_temp_mut_or_default::<RenumberInputs>(id).clone());
+
+    let mut changed = false;
+    let mut preview = false;
+    ui.horizontal(|ui| {
+        ui.label("Move IDs");
+        changed |= ui.add(DragValue::new(&mut inputs.start)).changed();
+        ui.label("..=");
+        changed |= ui.add(DragValue::new(&mut inputs.end)).changed();
+        ui.label("by");
+        changed |= ui.add(DragValue::new(&mut inputs.offset)).changed();
+        preview = ui
+            .button("Preview")
+            .on_hover_text("Lists the files that renumbering would change")
+            .clicked();
+    });
+
+    if !mapping_files.is_empty() {
+        ui.label("Also rewrite mappings:");
+        for path in mapping_files {
+            let mut selected = inputs.mappings.contains(path);
+            if ui.checkbox(&mut selected, path.as_str()).changed() {
+                changed = true;
+                if selected {
+                    inputs.mappings.insert(path.clone());
+                } else {
+                    inputs.mappings.remove(path);
+                }
+            }
+        }
+    }
+
+    if changed {
+        inputs.preview = None;
+    }
+
+    let mut apply = false;
+    if let Some(report) = &inputs.preview {
+        for line in report.to_string().lines() {
+            ui.label(line);
+        }
+        ui.horizontal(|ui| {
+            apply = ui
+                .button("Apply")
+                .on_hover_text("Moves the IDs and rewrites the listed files")
+                .clicked();
+            if ui.button("Cancel").clicked() {
+                inputs.preview = None;
+            }
+        });
+    }
+
+    let renumbering = (inputs.offset != 0.0).then(|| IdRenumbering::Offset {
+        range: ENumber::from(inputs.start)..=ENumber::from(inputs.end),
+        offset: ENumber::from(inputs.offset),
+    });
+    let mappings = inputs.mappings.clone();
+    ui.data_mut(|data| data.insert_temp(id, inputs));
+
+    let renumbering = renumbering?;
+    if apply {
+        Some(RenumberAction::Apply(renumbering, mappings))
+    } else if preview {
+        Some(RenumberAction::Preview(renumbering, mappings))
+    } else {
+        None
+    }
+}
+
+fn format_ranges(ranges: &[RangeInclusive<ENumber>]) -> String {
+    if ranges.is_empty() {
+        return "-".to_string();
+    }
+    ranges
+        .iter()
+        .map(|range| {
+            if range.start() == range.end() {
+                range.start().to_string()
+            } else {
+                format!("{}..={}", range.start(), range.end())
+            }
+        })
+        .join(", ")
+}
@@ -8,6 +8,7 @@ use crate::workspace::graph::toolbar::{GraphTab, GraphToolbarViewer};
 use crate::DbeApp;
 use camino::{Utf8Path, Utf8PathBuf};
 use dbe_backend::diagnostic::diagnostic::{Diagnostic, DiagnosticLevel};
+use dbe_backend::etype::computed::{check_computed, update_computed};
 use dbe_backend::graph::editing::PartialGraphEditingContext;
 use dbe_backend::graph::node::SnarlNode;
 use dbe_backend::project::docs::DocsRef;
@@ -52,6 +53,32 @@ impl DbeApp {
         }
     }
 
+    /// Deletes the file and closes its tab, unless IDs defined in the file
+    /// are still referenced by other files
+    pub fn delete_file(&mut self, _ctx: &Context, path: Utf8PathBuf) {
+        let Some(project) = self.project.as_mut() else {
+            report_error(miette!("No project is open"));
+            return;
+        };
+
+        if !project.io.is_file_writable(&path).unwrap_or(false) {
+            report_error(miette!("Path is not writable"));
+            return;
+        }
+
+        if let Err(err) = project
+            .check_deletion(&path)
+            .and_then(|_| project.delete_file(&path))
+        {
+            report_error(err);
+            return;
+        }
+
+        if let Some(tab) = self.tabs.find_tab(&path) {
+            self.tabs.remove_tab(tab);
+        }
+    }
+
     pub fn new_file(&mut self, ctx: &Context, folder: Utf8PathBuf) {
         self.show_new_file_modal(ctx, folder, |app, ctx, folder, mut filename| {
             let segments: Vec<&str> = filename.split('.').collect();
@@ -228,6 +255,9 @@ impl<Io: ProjectIO> TabViewer for WorkspaceTabViewer<'_, Io> {
         //     .ensure_file_state(&self.0.files, &self.0.graphs, &tab)
         //     .unwrap_or_else(report_error);
 
+        // Base is cloned, since it's stored alongside the edited file
+        let prefab_base = self.0.prefab_base(tab).cloned();
+
         let Some(data) = self.0.files.get_mut(tab) else {
             ui.centered_and_justified(|ui| {
                 ui.label(format!("!!INTERNAL ERROR!! the file {} is missing", tab));
@@ -250,9 +280,14 @@ impl<Io: ProjectIO> TabViewer for WorkspaceTabViewer<'_, Io> {
                     // figure out when it's actually changed based on hash
                     changed = true;
 
+                    if let Some(prefab) = self.0.prefabs.get(tab.as_path()) {
+                        ui.weak(format!("extends {}", prefab.base));
+                    }
+
                     let res = editor.show(
                         ui,
-                        EditorContext::new(&self.0.registry, &self.0.docs, DocsRef::None),
+                        EditorContext::new(&self.0.registry, &self.0.docs, DocsRef::None)
+                            .with_prefab_base(prefab_base.as_ref()),
                         diagnostics.as_readonly(),
                         "",
                         value,
@@ -260,11 +295,23 @@ impl<Io: ProjectIO> TabViewer for WorkspaceTabViewer<'_, Io> {
 
                     if res.changed {
                         trace!(%tab, "tab value changed, revalidating");
+                        // Computed fields are updated before the file is
+                        // checked by the undo history below, so they are
+                        // recorded in the same snapshot as the edit
+                        if update_computed(&self.0.registry, Some(&self.0.graphs), value) {
+                            trace!(%tab, "computed fields updated");
+                        }
                         if let Err(err) =
                             validate(&self.0.registry, diagnostics.enter_inline(), None, value)
                         {
                             report_error(err);
                         }
+                        check_computed(
+                            &self.0.registry,
+                            Some(&self.0.graphs),
+                            diagnostics.enter_inline(),
+                            value,
+                        );
                     }
 
                     ui.add_space(ui.ctx().screen_rect().height() * 0.5);
@@ -370,6 +417,7 @@ impl<Io: ProjectIO> TabViewer for WorkspaceTabViewer<'_, Io> {
                                         &Diagnostic {
                                             info: err.into(),
                                             level: DiagnosticLevel::Error,
+                                            fixes: vec![],
                                         },
                                     );
                                 };
@@ -1,16 +1,19 @@
 use crate::m_try;
 use crate::ui_props::{PROP_FIELD_EDITOR, PROP_OBJECT_EDITOR};
+use crate::workspace::editors::asset::AssetEditor;
 use crate::workspace::editors::boolean::BooleanEditor;
 use crate::workspace::editors::consts::ConstEditor;
-use crate::workspace::editors::enum_flags::EnumFlagsEditor;
+use crate::workspace::editors::enum_flags::{BitflagsEditor, EnumFlagsEditor};
 use crate::workspace::editors::enums::EnumEditor;
 use crate::workspace::editors::errors::{ErrorEditor, ErrorProps};
 use crate::workspace::editors::id_ref::IdRefEditor;
+use crate::workspace::editors::localized::LocalizedEditor;
 use crate::workspace::editors::map::MapEditor;
 use crate::workspace::editors::number::NumberEditor;
 use crate::workspace::editors::rgb::RgbEditor;
 use crate::workspace::editors::string::StringEditor;
 use crate::workspace::editors::structs::StructEditor;
+use crate::workspace::editors::tuple::TupleEditor;
 use crate::workspace::editors::utils::EditorSize;
 use crate::workspace::editors::wrapped::WrappedEditor;
 use ::utils::map::HashMap;
@@ -23,6 +26,8 @@ use dbe_backend::etype::EDataType;
 use dbe_backend::project::docs::Docs;
 use dbe_backend::project::docs::DocsRef;
 use dbe_backend::registry::{EObjectType, ETypesRegistry};
+use dbe_backend::validation::ids::numeric::NumericIds;
+use dbe_backend::validation::ids::string::StringIds;
 use dbe_backend::value::EValue;
 use downcast_rs::{impl_downcast, Downcast};
 use dyn_clone::DynClone;
@@ -37,6 +42,7 @@ use ustr::{Ustr, UstrMap};
 pub mod quick;
 mod utils;
 
+mod asset;
 mod boolean;
 mod consts;
 mod enum_flags;
@@ -44,11 +50,13 @@ mod enums;
 mod errors;
 mod id_ref;
 mod list;
+mod localized;
 mod map;
 mod number;
 mod rgb;
 mod string;
 mod structs;
+mod tuple;
 mod wrapped;
 
 static EDITORS: LazyLock<UstrMap<Box<dyn Editor>>> = LazyLock::new(|| default_editors().collect());
@@ -66,12 +74,27 @@ fn default_editors() -> impl Iterator<Item = (Ustr, Box<dyn Editor>)> {
         ("enum".into(), Box::new(EnumEditor)),
         ("list".into(), Box::new(ListEditor)),
         ("map".into(), Box::new(MapEditor)),
+        ("tuple".into(), Box::new(TupleEditor)),
         ("enum_flags".into(), Box::new(EnumFlagsEditor)),
+        ("bitflags".into(), Box::new(BitflagsEditor)),
         (
             "ids/numeric".into(),
             Box::new(WrappedEditor::new(NumberEditor::new(false), "id".into())),
         ),
-        ("ids/numeric_ref".into(), Box::new(IdRefEditor)),
+        (
+            "ids/numeric_ref".into(),
+            Box::new(IdRefEditor::<NumericIds>::default()),
+        ),
+        (
+            "ids/string".into(),
+            Box::new(WrappedEditor::new(StringEditor, "id".into())),
+        ),
+        (
+            "ids/string_ref".into(),
+            Box::new(IdRefEditor::<StringIds>::default()),
+        ),
+        ("localized".into(), Box::new(LocalizedEditor)),
+        ("asset".into(), Box::new(AssetEditor)),
         // TODO: proper combobox editors
         ("eh:image".into(), Box::new(StringEditor)),
         ("eh:layout".into(), Box::new(StringEditor)),
@@ -158,6 +181,11 @@ pub struct EditorContext<'a> {
     pub registry: &'a ETypesRegistry,
     pub docs: &'a Docs,
     pub docs_ref: DocsRef,
+    /// Value of the prefab base that the edited value inherits from
+    ///
+    /// Only set for the top-level value of the file, and is not passed to
+    /// the nested editors
+    pub prefab_base: Option<&'a EValue>,
 }
 
 impl<'a> EditorContext<'a> {
@@ -166,14 +194,21 @@ impl<'a> EditorContext<'a> {
             registry,
             docs,
             docs_ref,
+            prefab_base: None,
         }
     }
 
+    pub fn with_prefab_base(mut self, prefab_base: Option<&'a EValue>) -> Self {
+        self.prefab_base = prefab_base;
+        self
+    }
+
     pub fn copy_with_docs(&self, docs_ref: DocsRef) -> Self {
         Self {
             registry: self.registry,
             docs: self.docs,
             docs_ref,
+            prefab_base: None,
         }
     }
 
@@ -184,6 +219,7 @@ impl<'a> EditorContext<'a> {
             registry: self.registry,
             docs: self.docs,
             docs_ref: std::mem::replace(&mut self.docs_ref, docs_ref),
+            prefab_base: self.prefab_base,
         }
     }
 }
@@ -286,6 +322,7 @@ fn editor_for_raw(
             }
             EDataType::List { .. } => "list".into(),
             EDataType::Map { .. } => "map".into(),
+            EDataType::Tuple { .. } => "tuple".into(),
             EDataType::Unknown => "unknown".into(),
         },
         Some(name) => name,
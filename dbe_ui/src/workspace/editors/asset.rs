@@ -0,0 +1,109 @@
+use crate::workspace::editors::utils::{labeled_field, unsupported, EditorSize};
+use crate::workspace::editors::{
+    cast_props, DynProps, Editor, EditorContext, EditorProps, EditorResponse,
+};
+use dbe_backend::diagnostic::context::DiagnosticContextRef;
+use dbe_backend::etype::eitem::EItemInfo;
+use dbe_backend::project::assets::{AssetFilter, ProjectAssets};
+use dbe_backend::registry::ETypesRegistry;
+use dbe_backend::value::EValue;
+use egui::{Image, ScrollArea, TextEdit, Ui, Vec2};
+use inline_tweak::tweak;
+
+/// Editor for `asset` fields, with a picker of matching project files and a
+/// thumbnail preview for PNG images
+#[derive(Debug)]
+pub struct AssetEditor;
+
+impl Editor for AssetEditor {
+    fn props(
+        &self,
+        _reg: &ETypesRegistry,
+        item: Option<&EItemInfo>,
+        _object_props: DynProps,
+    ) -> miette::Result<DynProps> {
+        let filter = item
+            .map(|item| AssetFilter::from_props(item.extra_properties()))
+            .unwrap_or_default();
+
+        Ok(AssetProps { filter }.pack())
+    }
+
+    fn size(&self, _props: &DynProps) -> EditorSize {
+        EditorSize::Inline
+    }
+
+    fn edit(
+        &self,
+        ui: &mut Ui,
+        ctx: EditorContext,
+        _diagnostics: DiagnosticContextRef,
+        field_name: &str,
+        value: &mut EValue,
+        props: &DynProps,
+    ) -> EditorResponse {
+        let Ok(value) = value.try_as_string_mut() else {
+            unsupported!(ui, field_name, value, self);
+        };
+
+        let props = cast_props::<AssetProps>(props);
+        let assets = ProjectAssets::of(ctx.registry);
+        let mut changed = false;
+
+        labeled_field(ui, field_name, ctx, |ui| {
+            let assets = assets.read();
+
+            let project_path = props.filter.project_path(value);
+            let is_png = project_path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+            if is_png && assets.exists(&project_path) {
+                let uri = format!("file://{}", assets.root.join(&project_path));
+                let size = tweak!(24.0);
+                ui.add(Image::new(uri.clone()).fit_to_exact_size(Vec2::splat(size)))
+                    .on_hover_ui(|ui| {
+                        ui.add(Image::new(uri).max_size(Vec2::splat(tweak!(256.0))));
+                    });
+            }
+
+            changed |= ui
+                .add(
+                    TextEdit::singleline(value)
+                        .desired_width(0.0)
+                        .clip_text(false),
+                )
+                .changed();
+
+            ui.menu_button("…", |ui| {
+                ScrollArea::vertical()
+                    .max_height(tweak!(300.0))
+                    .show(ui, |ui| {
+                        let mut any = false;
+                        for path in assets.matching(&props.filter) {
+                            any = true;
+                            if ui
+                                .selectable_label(path.as_str() == value.as_str(), path.as_str())
+                                .clicked()
+                            {
+                                *value = path.to_string();
+                                changed = true;
+                                ui.close_menu();
+                            }
+                        }
+                        if !any {
+                            ui.weak("no matching files");
+                        }
+                    });
+            });
+        });
+
+        EditorResponse::new(changed)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AssetProps {
+    filter: AssetFilter,
+}
+
+impl EditorProps for AssetProps {}
@@ -6,10 +6,12 @@ use dbe_backend::diagnostic::context::DiagnosticContextRef;
 use dbe_backend::etype::econst::ETypeConst;
 use dbe_backend::etype::eenum::variant::EEnumVariantId;
 use dbe_backend::etype::EDataType;
+use dbe_backend::json_utils::repr::bitflags::{FLAGS_FIELD, UNKNOWN_BITS_FIELD};
 use dbe_backend::project::docs::DocsRef;
 use dbe_backend::value::EValue;
 use egui::Ui;
 use miette::{bail, miette};
+use ustr::Ustr;
 
 #[derive(Debug)]
 pub struct EnumFlagsEditor;
@@ -105,6 +107,53 @@ impl Editor for EnumFlagsEditor {
     }
 }
 
+/// Editor for `bitflags` structs, editing the flags list with [EnumFlagsEditor]
+#[derive(Debug)]
+pub struct BitflagsEditor;
+
+impl Editor for BitflagsEditor {
+    fn size(&self, _props: &DynProps) -> EditorSize {
+        EditorSize::Block
+    }
+
+    fn edit(
+        &self,
+        ui: &mut Ui,
+        ctx: EditorContext,
+        diagnostics: DiagnosticContextRef,
+        field_name: &str,
+        value: &mut EValue,
+        props: &DynProps,
+    ) -> EditorResponse {
+        let EValue::Struct { fields, ident: _ } = value else {
+            unsupported!(ui, field_name, value, self);
+        };
+
+        let unknown_bits = fields
+            .get(&Ustr::from(UNKNOWN_BITS_FIELD))
+            .and_then(|v| v.try_as_number().ok())
+            .map_or(0, |v| v.0 as u64);
+
+        let Some(flags) = fields.get_mut(&Ustr::from(FLAGS_FIELD)) else {
+            unsupported!(ui, field_name, value, self);
+        };
+
+        let mut response = EnumFlagsEditor.edit(ui, ctx, diagnostics, field_name, flags, props);
+
+        if unknown_bits != 0 {
+            ui.horizontal(|ui| {
+                ui.label(format!("Unknown bits: {:#x}", unknown_bits));
+                if ui.button("Clear").clicked() {
+                    fields.insert(Ustr::from(UNKNOWN_BITS_FIELD), EValue::from(0.0));
+                    response.changed = true;
+                }
+            });
+        }
+
+        response
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 struct Flag {
     id: EEnumVariantId,
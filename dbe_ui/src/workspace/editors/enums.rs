@@ -347,6 +347,13 @@ impl<'a> EnumEditorData<'a> {
 
     fn body(mut self, ui: &mut Ui) -> EditorResponse {
         if !self.skip_draw_body {
+            // Prefab base only applies to the variant data if the variant is
+            // inherited as well
+            self.ctx.prefab_base = match self.ctx.prefab_base {
+                Some(EValue::Enum { variant, data }) if *variant == *self.variant => Some(&**data),
+                _ => None,
+            };
+
             let mut d = self
                 .diagnostics
                 .enter_variant(self.selected_variant.name.as_str());
@@ -9,19 +9,23 @@ use dbe_backend::diagnostic::context::DiagnosticContextRef;
 use dbe_backend::etype::eitem::EItemInfo;
 use dbe_backend::etype::EDataType;
 use dbe_backend::registry::ETypesRegistry;
-use dbe_backend::validation::ids::numeric::NumericIDRegistry;
-use dbe_backend::value::{ENumber, EValue};
+use dbe_backend::validation::ids::registry::IdRegistry;
+use dbe_backend::validation::ids::IdKind;
+use dbe_backend::value::EValue;
 use egui::{Frame, Ui, Widget};
 use egui_hooks::UseHookExt;
 use itertools::Itertools;
 use miette::bail;
 use std::collections::BTreeSet;
-use std::str::FromStr;
+use std::fmt::Display;
+use std::marker::PhantomData;
 
-#[derive(Debug)]
-pub struct IdRefEditor;
+/// Editor for the references of the [IdKind], offering a dropdown of the
+/// available IDs
+#[derive(Debug, Default)]
+pub struct IdRefEditor<K>(PhantomData<K>);
 
-impl Editor for IdRefEditor {
+impl<K: IdKind> Editor for IdRefEditor<K> {
     fn props(
         &self,
         _reg: &ETypesRegistry,
@@ -73,38 +77,38 @@ impl Editor for IdRefEditor {
                 bail!("expected field `id`");
             };
 
-            let EValue::Number { value } = value else {
-                bail!("expected number, found {:?}", value);
+            let Some(id) = K::from_value(value) else {
+                bail!("expected {}, found {:?}", K::EXPECTED, value);
             };
 
-            let id_name = NumericIDRegistry::of(ctx.registry)
-                .location_for_id(data_ty, *value)?
+            let id_name = IdRegistry::<K>::of(ctx.registry)
+                .location_for_id(data_ty, id)?
                 .map(|name| format_name(&name, props.show_file_path, props.show_field_path));
 
-            Ok((value, id_name))
+            Ok((value, id, id_name))
         })
-        .then_draw(ui, |ui, (value, name)| {
+        .then_draw(ui, |ui, (value, id, name)| {
             let registry = ctx.registry;
             labeled_field(ui, field_name, ctx, |ui| {
                 let mut edited_text = ui.use_state(|| None::<String>, ()).into_var();
                 if let Some(edited) = edited_text.as_mut() {
-                    if let Some(done) = NumericIDRegistry::of(registry)
+                    if let Some(done) = IdRegistry::<K>::of(registry)
                         .with_available_ids(data_ty, |iter| {
                             let res = DropDownBox::from_iter(
                                 iter.map(|(id, locations)| {
                                     DropDownItem::new(
-                                        id,
+                                        *id,
                                         name_of(locations, true, props.show_field_path),
                                     )
                                 })
-                                .sorted_unstable_by_key(|x| *x.num),
+                                .sorted_unstable_by_key(|x| x.id),
                                 field_name,
                                 edited,
-                                |ui, id, name| {
+                                |ui, item, name| {
                                     ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
-                                    let res = ui.selectable_label(id.num == value, name);
+                                    let res = ui.selectable_label(item.id == id, name);
                                     if res.clicked() {
-                                        *value = *id.num;
+                                        *value = K::to_value(item.id);
                                         changed = true;
                                     }
                                     res
@@ -113,16 +117,14 @@ impl Editor for IdRefEditor {
                             .select_on_focus(true)
                             .ui(ui);
 
-                            // res.request_focus();
-
                             Ok(res.clicked_elsewhere())
                         })
                         .or_draw_error(ui)
                     {
                         if done {
                             if !changed {
-                                if let Ok(num) = <i32 as FromStr>::from_str(edited) {
-                                    *value = (num as f64).into();
+                                if let Some(new_id) = K::parse(edited).filter(|new| *new != id) {
+                                    *value = K::to_value(new_id);
                                     changed = true;
                                 }
                             }
@@ -136,10 +138,10 @@ impl Editor for IdRefEditor {
                     }
                     .show(ui, |ui| {
                         if ui
-                            .selectable_label(false, name.unwrap_or_else(|| value.to_string()))
+                            .selectable_label(false, name.unwrap_or_else(|| id.to_string()))
                             .clicked()
                         {
-                            *edited_text = Some(value.to_string());
+                            *edited_text = Some(id.to_string());
                         };
                     });
                 }
@@ -150,25 +152,25 @@ impl Editor for IdRefEditor {
     }
 }
 
-struct DropDownItem<'a> {
-    num: &'a ENumber,
+struct DropDownItem<Id> {
+    id: Id,
     ref_name: String,
 }
 
-impl<'a> DropDownItem<'a> {
-    pub fn new(num: &'a ENumber, ref_name: Option<String>) -> Self {
+impl<Id: Display> DropDownItem<Id> {
+    pub fn new(id: Id, ref_name: Option<String>) -> Self {
         Self {
-            num,
             ref_name: if let Some(name) = ref_name {
-                format!("{} ({})", num, name)
+                format!("{} ({})", id, name)
             } else {
-                num.to_string()
+                id.to_string()
             },
+            id,
         }
     }
 }
 
-impl AsRef<str> for DropDownItem<'_> {
+impl<Id> AsRef<str> for DropDownItem<Id> {
     fn as_ref(&self) -> &str {
         &self.ref_name
     }
@@ -176,8 +178,8 @@ impl AsRef<str> for DropDownItem<'_> {
 
 #[derive(Debug, Clone)]
 struct IdRefProps {
-    show_file_path: bool,
-    show_field_path: bool,
+    pub show_file_path: bool,
+    pub show_field_path: bool,
 }
 
 impl EditorProps for IdRefProps {}
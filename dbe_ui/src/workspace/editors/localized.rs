@@ -0,0 +1,108 @@
+use crate::m_try;
+use crate::workspace::editors::utils::{labeled_field, unsupported, EditorResultExt, EditorSize};
+use crate::workspace::editors::{DynProps, Editor, EditorContext, EditorResponse};
+use dbe_backend::diagnostic::context::DiagnosticContextRef;
+use dbe_backend::json_utils::repr::localized::KEY_FIELD;
+use dbe_backend::project::localization::Localization;
+use dbe_backend::value::EValue;
+use egui::{ComboBox, TextEdit, Ui};
+use miette::bail;
+use ustr::Ustr;
+
+/// Editor for `sys:localized` strings, showing the translation key along with
+/// the text for the active locale
+#[derive(Debug)]
+pub struct LocalizedEditor;
+
+impl Editor for LocalizedEditor {
+    fn size(&self, _props: &DynProps) -> EditorSize {
+        EditorSize::Inline
+    }
+
+    fn edit(
+        &self,
+        ui: &mut Ui,
+        ctx: EditorContext,
+        _diagnostics: DiagnosticContextRef,
+        field_name: &str,
+        value: &mut EValue,
+        _props: &DynProps,
+    ) -> EditorResponse {
+        let EValue::Struct { fields, .. } = value else {
+            unsupported!(ui, field_name, value, self);
+        };
+
+        let localization = Localization::of(ctx.registry);
+        let mut changed = false;
+
+        m_try(|| {
+            let Some(key) = fields.get_mut(&Ustr::from(KEY_FIELD)) else {
+                bail!("expected field `{}`", KEY_FIELD);
+            };
+
+            let EValue::String { value: key } = key else {
+                bail!("expected string, found {:?}", key);
+            };
+
+            Ok(key)
+        })
+        .then_draw(ui, |ui, key| {
+            labeled_field(ui, field_name, ctx, |ui| {
+                changed |= ui
+                    .add(
+                        TextEdit::singleline(key)
+                            .hint_text("key")
+                            .desired_width(0.0),
+                    )
+                    .changed();
+
+                let mut localization = localization.write();
+                let Some(active) = localization.active_locale() else {
+                    ui.weak("no locales");
+                    return;
+                };
+
+                let mut selected = active;
+                ComboBox::from_id_salt((field_name, "locale"))
+                    .selected_text(active.as_str())
+                    .show_ui(ui, |ui| {
+                        for locale in localization.locales() {
+                            ui.selectable_value(&mut selected, locale, locale.as_str());
+                        }
+                    });
+
+                if selected != active {
+                    localization
+                        .set_active_locale(selected)
+                        .expect("locale should exist");
+                }
+
+                if key.is_empty() {
+                    return;
+                }
+
+                let mut text = localization
+                    .get(selected, key)
+                    .map(|s| s.to_string())
+                    .unwrap_or_default();
+
+                if ui
+                    .add(
+                        TextEdit::singleline(&mut text)
+                            .hint_text("missing translation")
+                            .desired_width(0.0)
+                            .clip_text(false),
+                    )
+                    .changed()
+                {
+                    changed |= localization
+                        .set(selected, key, text)
+                        .or_draw_error(ui)
+                        .is_some();
+                }
+            });
+        });
+
+        EditorResponse::new(changed)
+    }
+}
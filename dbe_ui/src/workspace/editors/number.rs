@@ -5,6 +5,7 @@ use crate::workspace::editors::{
 };
 use dbe_backend::diagnostic::context::DiagnosticContextRef;
 use dbe_backend::etype::eitem::EItemInfo;
+use dbe_backend::etype::property::default_properties::PROP_FIELD_INTEGER;
 use dbe_backend::registry::ETypesRegistry;
 use dbe_backend::value::{ENumber, EValue};
 use egui::{DragValue, Slider, Ui};
@@ -33,6 +34,7 @@ impl Editor for NumberEditor {
         let min = props.and_then(|p| PROP_FIELD_MIN.try_get(p));
         let max = props.and_then(|p| PROP_FIELD_MAX.try_get(p));
         let logarithmic = props.and_then(|p| PROP_FIELD_LOGARITHMIC.try_get(p));
+        let integer = props.is_some_and(|p| PROP_FIELD_INTEGER.get(p, false));
 
         let min = min.unwrap_or(ENumber::min_value()).0;
         let max = max.unwrap_or(ENumber::max_value()).0;
@@ -40,6 +42,7 @@ impl Editor for NumberEditor {
         Ok(NumericProps {
             range: min..=max,
             logarithmic: logarithmic.unwrap_or(max - min >= 1e6),
+            integer,
         }
         .pack())
     }
@@ -64,15 +67,27 @@ impl Editor for NumberEditor {
         let props = cast_props::<NumericProps>(props);
 
         let changed = labeled_field(ui, field_name, ctx, |ui| {
-            if self.slider {
-                ui.add(
-                    Slider::new(&mut value.0, props.range.clone()).logarithmic(props.logarithmic),
-                )
-                .changed()
+            let changed = if self.slider {
+                let mut slider =
+                    Slider::new(&mut value.0, props.range.clone()).logarithmic(props.logarithmic);
+                if props.integer {
+                    slider = slider.integer();
+                }
+                ui.add(slider).changed()
             } else {
-                ui.add(DragValue::new(&mut value.0).range(props.range.clone()))
-                    .changed()
+                let mut drag = DragValue::new(&mut value.0).range(props.range.clone());
+                if props.integer {
+                    drag = drag.speed(1.0).fixed_decimals(0);
+                }
+                ui.add(drag).changed()
+            };
+
+            // Typed in values can still have a fractional part
+            if changed && props.integer {
+                value.0 = value.0.round();
             }
+
+            changed
         });
 
         EditorResponse::new(changed.inner)
@@ -83,6 +98,7 @@ impl Editor for NumberEditor {
 struct NumericProps {
     range: RangeInclusive<f64>,
     logarithmic: bool,
+    integer: bool,
 }
 
 impl EditorProps for NumericProps {}
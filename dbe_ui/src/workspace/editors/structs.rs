@@ -1,12 +1,15 @@
 use crate::main_toolbar::docs::docs_label;
-use crate::ui_props::PROP_FIELD_HIDE_FIELDS;
+use crate::ui_props::{PROP_FIELD_HIDE_FIELDS, PROP_FIELD_READONLY};
 use crate::widgets::report::diagnostics_column;
 use crate::workspace::editors::utils::{labeled_field, unsupported, EditorResultExt, EditorSize};
 use crate::workspace::editors::{
     cast_props, editor_for_item, DynProps, Editor, EditorContext, EditorProps, EditorResponse,
 };
 use dbe_backend::diagnostic::context::DiagnosticContextRef;
+use dbe_backend::etype::computed::is_computed;
+use dbe_backend::etype::condition::is_field_visible;
 use dbe_backend::etype::eitem::EItemInfo;
+use dbe_backend::etype::estruct::EStructField;
 use dbe_backend::etype::property::default_properties::PROP_FIELD_INLINE;
 use dbe_backend::project::docs::DocsRef;
 use dbe_backend::registry::ETypesRegistry;
@@ -14,6 +17,8 @@ use dbe_backend::value::EValue;
 use egui::{Label, RichText, Ui};
 use itertools::Itertools;
 use miette::miette;
+use std::collections::BTreeMap;
+use ustr::Ustr;
 
 #[derive(Debug)]
 pub struct StructEditor;
@@ -56,6 +61,10 @@ impl Editor for StructEditor {
 
         let props = cast_props::<StructProps>(props);
         let docs_ctx = ctx.replace_docs_ref(DocsRef::None);
+        let prefab_base = match ctx.prefab_base {
+            Some(EValue::Struct { fields, .. }) => Some(fields),
+            _ => None,
+        };
 
         let mut changed = false;
         ctx.registry
@@ -66,7 +75,10 @@ impl Editor for StructEditor {
                     .fields
                     .iter()
                     .filter(|f| !props.hide_fields.contains(&f.name.as_str()))
-                    .map(|f| (f, editor_for_item(ctx.registry, &f.ty)))
+                    .filter_map(|f| {
+                        let enabled = field_enabled(ctx.registry, f, fields)?;
+                        Some((f, editor_for_item(ctx.registry, &f.ty), enabled))
+                    })
                     .collect_vec();
 
                 let hidden = data.fields.len() - items.len();
@@ -82,7 +94,7 @@ impl Editor for StructEditor {
                             Label::new(RichText::new(format!("{} fields hidden", hidden)).small()),
                         );
                     }
-                    for (field, editor) in items {
+                    for (field, editor, enabled) in items {
                         ui.push_id(field.name, |ui| {
                             fields
                                 .get_mut(&field.name)
@@ -91,12 +103,23 @@ impl Editor for StructEditor {
                                     let mut d = diagnostics.enter_field(field.name.as_str());
                                     let ctx =
                                         ctx.copy_with_docs(DocsRef::TypeField(*ident, field.name));
-                                    if editor
-                                        .show(ui, ctx, d.enter_inline(), field.name.as_ref(), value)
-                                        .changed
-                                    {
+                                    let res = ui.add_enabled_ui(enabled, |ui| {
+                                        editor.show(
+                                            ui,
+                                            ctx,
+                                            d.enter_inline(),
+                                            field.name.as_ref(),
+                                            value,
+                                        )
+                                    });
+                                    if res.inner.changed {
                                         changed = true;
                                     };
+                                    if let Some(base) =
+                                        prefab_base.and_then(|base| base.get(&field.name))
+                                    {
+                                        changed |= prefab_marker(ui, base, value);
+                                    }
                                     diagnostics_column(ui, d.get_reports_shallow())
                                 });
                         });
@@ -142,6 +165,55 @@ impl Editor for StructEditor {
     }
 }
 
+/// Returns whenever the field editor should be enabled, or `None` if the
+/// field should be hidden
+///
+/// Computed and `readonly` fields are read-only, computed fields are updated
+/// by the workspace after every edit. Fields hidden by the `visible_if` condition are still shown as
+/// disabled while they hold a non-default value, so the stray value is not
+/// lost from sight
+fn field_enabled(
+    registry: &ETypesRegistry,
+    field: &EStructField,
+    fields: &BTreeMap<Ustr, EValue>,
+) -> Option<bool> {
+    match is_field_visible(field, fields) {
+        Ok(false) => {
+            let is_default = fields
+                .get(&field.name)
+                .is_none_or(|value| *value == *field.ty.default_value(registry));
+            (!is_default).then_some(false)
+        }
+        _ => Some(
+            !is_computed(&field.ty) && !PROP_FIELD_READONLY.get(field.ty.extra_properties(), false),
+        ),
+    }
+}
+
+/// Shows whenever the field value is inherited from the prefab base, along
+/// with the button to revert an overridden value
+///
+/// Returns `true` if the value was reverted
+fn prefab_marker(ui: &mut Ui, base: &EValue, value: &mut EValue) -> bool {
+    if base == value {
+        ui.add_enabled(false, Label::new(RichText::new("inherited").small()))
+            .on_disabled_hover_text("value is inherited from the prefab base");
+        return false;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("overridden").small().strong())
+            .on_hover_text("value overrides the prefab base");
+        if ui.small_button("revert").clicked() {
+            *value = base.clone();
+            true
+        } else {
+            false
+        }
+    })
+    .inner
+}
+
 #[derive(Debug, Clone)]
 struct StructProps {
     inline: bool,
@@ -0,0 +1,93 @@
+use crate::widgets::report::diagnostics_column;
+use crate::workspace::editors::utils::{
+    labeled_collapsing_header, labeled_field, unsupported, EditorResultExt, EditorSize,
+};
+use crate::workspace::editors::{editor_for_type, DynProps, Editor, EditorContext, EditorResponse};
+use dbe_backend::diagnostic::context::DiagnosticContextRef;
+use dbe_backend::project::docs::DocsRef;
+use dbe_backend::value::EValue;
+use egui::Ui;
+use itertools::Itertools;
+use miette::miette;
+
+#[derive(Debug)]
+pub struct TupleEditor;
+
+impl Editor for TupleEditor {
+    fn size(&self, _props: &DynProps) -> EditorSize {
+        EditorSize::Block
+    }
+
+    fn edit(
+        &self,
+        ui: &mut Ui,
+        mut ctx: EditorContext,
+        mut diagnostics: DiagnosticContextRef,
+        field_name: &str,
+        value: &mut EValue,
+        _props: &DynProps,
+    ) -> EditorResponse {
+        let EValue::Tuple { values, id } = value else {
+            unsupported!(ui, field_name, value, self);
+        };
+
+        let mut changed = false;
+        let docs_ctx = ctx.replace_docs_ref(DocsRef::None);
+
+        ctx.registry
+            .get_tuple(id)
+            .ok_or_else(|| miette!("!!INTERNAL ERROR!! unknown tuple `{}`", id))
+            .and_then(|tuple_data| {
+                if tuple_data.item_types.len() != values.len() {
+                    return Err(miette!(
+                        "tuple `{}` has {} items, but value has {}",
+                        id,
+                        tuple_data.item_types.len(),
+                        values.len()
+                    ));
+                }
+                Ok(tuple_data)
+            })
+            .then_draw(ui, |ui, tuple_data| {
+                let editors = tuple_data
+                    .item_types
+                    .iter()
+                    .map(|ty| editor_for_type(ctx.registry, ty))
+                    .collect_vec();
+
+                let inline = editors.iter().all(|e| e.size() <= EditorSize::Inline);
+
+                let draw_items = |ui: &mut Ui| {
+                    for (i, (editor, val)) in editors.iter().zip(values.iter_mut()).enumerate() {
+                        ui.push_id(i, |ui| {
+                            let mut d = diagnostics.enter_index(i);
+                            if editor
+                                .show(
+                                    ui,
+                                    ctx.copy_with_docs(DocsRef::None),
+                                    d.enter_inline(),
+                                    "",
+                                    val,
+                                )
+                                .changed
+                            {
+                                changed = true;
+                            }
+
+                            diagnostics_column(ui, d.get_reports_shallow());
+                        });
+                    }
+                };
+
+                if inline {
+                    labeled_field(ui, field_name, docs_ctx, draw_items);
+                } else {
+                    labeled_collapsing_header(ui, field_name, docs_ctx, true, true, |ui| {
+                        ui.vertical(draw_items);
+                    });
+                }
+            });
+
+        EditorResponse::new(changed)
+    }
+}
@@ -16,10 +16,11 @@ use dbe_backend::graph::node::ports::NodePortType;
 use dbe_backend::graph::node::{
     all_node_factories, node_factories_by_category, NodeFactory, SnarlNode,
 };
+use dbe_backend::graph::validation::{node_key, EVALUATION_FIELD};
 use dbe_backend::registry::ETypesRegistry;
 use dbe_backend::value::id::ETypeId;
 use egui::epaint::PathShape;
-use egui::{Color32, Frame, Painter, Pos2, Rect, Stroke, Style, Ui};
+use egui::{Color32, Frame, Painter, Pos2, Rect, RichText, Stroke, Style, Ui};
 use egui_hooks::UseHookExt;
 use egui_snarl::ui::{
     AnyPins, BackgroundPattern, NodeLayout, PinInfo, SnarlStyle, SnarlViewer, Viewport,
@@ -61,6 +62,37 @@ impl<'a> GraphViewer<'a> {
             node_rects,
         }
     }
+
+    /// Checks whether the node failed during the last graph evaluation
+    fn has_evaluation_errors(&mut self, node: NodeId) -> bool {
+        let mut diagnostics = self.diagnostics.enter_field(EVALUATION_FIELD);
+        let diagnostics = diagnostics.enter_map_key(node_key(node));
+        let has_errors = diagnostics.get_reports_deep().next().is_some();
+        has_errors
+    }
+
+    /// Shows an error marker with the errors of the last graph evaluation of
+    /// the node on hover
+    fn show_evaluation_errors(&mut self, node: NodeId, ui: &mut Ui) {
+        let mut diagnostics = self.diagnostics.enter_field(EVALUATION_FIELD);
+        let diagnostics = diagnostics.enter_map_key(node_key(node));
+        let mut reports = diagnostics
+            .get_reports_deep()
+            .flat_map(|(_, reports)| reports)
+            .peekable();
+
+        if reports.peek().is_none() {
+            return;
+        }
+
+        ui.label(RichText::new("⚠").color(ui.visuals().error_fg_color))
+            .on_hover_ui(|ui| {
+                ui.set_max_width(512.0);
+                for report in reports {
+                    diagnostic_widget(ui, report);
+                }
+            });
+    }
 }
 
 impl SnarlViewer<SnarlNode> for GraphViewer<'_> {
@@ -103,6 +135,13 @@ impl SnarlViewer<SnarlNode> for GraphViewer<'_> {
             default = default.fill(scheme.theme.tokens.subtle_background())
         }
 
+        if self.has_evaluation_errors(node) {
+            default = default.stroke(Stroke::new(
+                default.stroke.width.max(2.0),
+                Color32::from_rgb(255, 60, 60),
+            ));
+        }
+
         default
     }
 
@@ -163,6 +202,7 @@ impl SnarlViewer<SnarlNode> for GraphViewer<'_> {
             }
 
             viewer.show_header(self, node_id, inputs, outputs, ui, scale, snarl)?;
+            self.show_evaluation_errors(node_id, ui);
 
             Ok(())
         })
@@ -173,6 +213,7 @@ impl SnarlViewer<SnarlNode> for GraphViewer<'_> {
                 &Diagnostic {
                     info: err,
                     level: DiagnosticLevel::Error,
+                    fixes: vec![],
                 },
             );
         })
@@ -197,6 +238,7 @@ impl SnarlViewer<SnarlNode> for GraphViewer<'_> {
                     &Diagnostic {
                         info: err,
                         level: DiagnosticLevel::Error,
+                        fixes: vec![],
                     },
                 );
                 PinInfo::circle().with_fill(Color32::BLACK)
@@ -222,6 +264,7 @@ impl SnarlViewer<SnarlNode> for GraphViewer<'_> {
                     &Diagnostic {
                         info: err,
                         level: DiagnosticLevel::Error,
+                        fixes: vec![],
                     },
                 );
                 PinInfo::circle().with_fill(Color32::BLACK)
@@ -264,6 +307,7 @@ impl SnarlViewer<SnarlNode> for GraphViewer<'_> {
                     &Diagnostic {
                         info: err,
                         level: DiagnosticLevel::Error,
+                        fixes: vec![],
                     },
                 );
             })
@@ -718,6 +762,10 @@ fn pin_color(ty: EDataType, registry: &ETypesRegistry) -> Color32 {
             .get_map(&id)
             .map(|e| pin_color(e.value_type, registry))
             .unwrap_or(NULL_COLOR),
+        EDataType::Tuple { id } => registry
+            .get_tuple(&id)
+            .and_then(|e| e.item_types.first().map(|ty| pin_color(*ty, registry)))
+            .unwrap_or(NULL_COLOR),
         EDataType::Unknown => UNKNOWN_COLOR,
     }
 }
@@ -746,6 +794,7 @@ fn pin_info(ty: &NodePortType, registry: &ETypesRegistry) -> PinInfo {
                 EDataType::Object { .. } => PinInfo::circle(),
                 EDataType::List { .. } => PinInfo::square(),
                 EDataType::Map { .. } => PinInfo::square(),
+                EDataType::Tuple { .. } => PinInfo::square(),
                 EDataType::Unknown => PinInfo::star(),
             };
 
@@ -1,5 +1,7 @@
 use crate::workspace::graph::viewer::default_view::DefaultNodeView;
-use crate::workspace::graph::viewer::destructuring::DestructuringNodeViewer;
+use crate::workspace::graph::viewer::destructuring::{
+    DestructuringNodeViewer, TupleDestructuringNodeViewer,
+};
 use crate::workspace::graph::viewer::reroute::RerouteNodeViewer;
 use crate::workspace::graph::viewer::subgraph::SubgraphNodeViewer;
 use crate::workspace::graph::GraphViewer;
@@ -27,6 +29,7 @@ fn default_viewers() -> impl Iterator<Item = (Ustr, Arc<dyn NodeView>)> {
         Arc::new(RerouteNodeViewer),
         Arc::new(SubgraphNodeViewer),
         Arc::new(DestructuringNodeViewer),
+        Arc::new(TupleDestructuringNodeViewer),
     ];
     v.into_iter().map(|item| (Ustr::from(&item.id()), item))
 }
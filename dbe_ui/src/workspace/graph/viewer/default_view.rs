@@ -36,6 +36,7 @@ pub fn format_value(value: &EValue) -> String {
         EValue::Enum { .. } => "".to_string(),
         EValue::List { .. } => "".to_string(),
         EValue::Map { .. } => "".to_string(),
+        EValue::Tuple { .. } => "".to_string(),
     }
 }
 
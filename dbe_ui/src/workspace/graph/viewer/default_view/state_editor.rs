@@ -24,6 +24,7 @@ pub fn show_state_editor(
                         registry: viewer.ctx.registry,
                         docs: viewer.ctx.docs,
                         docs_ref: DocsRef::NodeState(node_id, *field_name),
+                        prefab_base: None,
                     };
                     res |= quick_edit_evalue(
                         ui,
@@ -1,6 +1,8 @@
 use crate::workspace::graph::viewer::NodeView;
 use crate::workspace::graph::GraphViewer;
-use dbe_backend::graph::node::generic::destructuring::DestructuringNodeFactory;
+use dbe_backend::graph::node::generic::destructuring::{
+    DestructuringNodeFactory, TupleDestructuringNodeFactory,
+};
 use dbe_backend::graph::node::{NodeFactory, SnarlNode};
 use egui_snarl::ui::NodeLayout;
 use ustr::Ustr;
@@ -17,3 +19,16 @@ impl NodeView for DestructuringNodeViewer {
         NodeLayout::Sandwich
     }
 }
+
+#[derive(Debug)]
+pub struct TupleDestructuringNodeViewer;
+
+impl NodeView for TupleDestructuringNodeViewer {
+    fn id(&self) -> Ustr {
+        TupleDestructuringNodeFactory.id()
+    }
+
+    fn node_layout(&self, _viewer: &mut GraphViewer, _node: &SnarlNode) -> NodeLayout {
+        NodeLayout::Sandwich
+    }
+}
@@ -1,13 +1,18 @@
-use crate::diagnostic::{Diagnostic, DiagnosticLevel};
+use crate::diagnostic::{Diagnostic, DiagnosticFix, DiagnosticLevel};
 use crate::path::{DiagnosticPath, DiagnosticPathSegment};
+use crate::rules::{report_code, DiagnosticFilter};
+use crate::source::{with_source, DiagnosticSources};
 use smallvec::SmallVec;
 use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct DiagnosticContext {
     pub diagnostics: BTreeMap<String, BTreeMap<DiagnosticPath, SmallVec<[Diagnostic; 1]>>>,
     path: DiagnosticPath,
+    filter: Option<Arc<dyn DiagnosticFilter>>,
+    sources: Option<Arc<dyn DiagnosticSources>>,
 }
 
 impl Default for DiagnosticContext {
@@ -15,11 +20,23 @@ impl Default for DiagnosticContext {
         DiagnosticContext {
             diagnostics: Default::default(),
             path: DiagnosticPath::empty(),
+            filter: None,
+            sources: None,
         }
     }
 }
 
 impl DiagnosticContext {
+    /// Sets the filter applied to all diagnostics emitted from now on
+    pub fn set_filter(&mut self, filter: Option<Arc<dyn DiagnosticFilter>>) {
+        self.filter = filter;
+    }
+
+    /// Sets the sources used to locate all diagnostics emitted from now on
+    pub fn set_sources(&mut self, sources: Option<Arc<dyn DiagnosticSources>>) {
+        self.sources = sources;
+    }
+
     pub fn merge(&mut self, other: DiagnosticContext) {
         for (ident, diagnostics) in other.diagnostics {
             let entry = self.diagnostics.entry(ident).or_default();
@@ -36,6 +53,8 @@ impl DiagnosticContext {
             path: &mut self.path,
             ident,
             pop_on_exit: false,
+            filter: self.filter.as_deref(),
+            sources: self.sources.as_deref(),
         }
     }
 
@@ -46,6 +65,8 @@ impl DiagnosticContext {
             path: &mut self.path,
             ident,
             pop_on_exit: false,
+            filter: self.filter.as_deref(),
+            sources: self.sources.as_deref(),
         }
     }
 
@@ -69,10 +90,39 @@ impl DiagnosticContext {
 
 impl DiagnosticContextMut<'_> {
     pub fn emit(&mut self, info: miette::Report, level: DiagnosticLevel) {
+        self.emit_with_fixes(info, level, vec![]);
+    }
+
+    /// Emits the diagnostic together with the fixes that resolve it
+    pub fn emit_with_fixes(
+        &mut self,
+        info: miette::Report,
+        level: DiagnosticLevel,
+        fixes: Vec<DiagnosticFix>,
+    ) {
+        let level = match self.filter {
+            None => level,
+            Some(filter) => {
+                let code = report_code(&info);
+                match filter.filter(self.ident, self.path, code.as_deref(), level) {
+                    None => return,
+                    Some(level) => level,
+                }
+            }
+        };
+
+        let info = match self
+            .sources
+            .and_then(|sources| sources.locate(self.ident, self.path))
+        {
+            None => info,
+            Some((source, span)) => with_source(info, source, span),
+        };
+
         self.diagnostics
             .entry(self.path.clone())
             .or_default()
-            .push(Diagnostic { info, level });
+            .push(Diagnostic { info, level, fixes });
     }
 
     pub fn emit_error(&mut self, info: miette::Report) {
@@ -89,6 +139,15 @@ impl DiagnosticContextMut<'_> {
             .retain(|path, _| !path.starts_with(self.path));
     }
 
+    /// Clears all warnings originating from the current context or its
+    /// children, except for the ones under the given child segment.
+    pub fn clear_downstream_except(&mut self, segment: impl Into<DiagnosticPathSegment>) {
+        let mut keep = self.path.clone();
+        keep.push(segment);
+        self.diagnostics
+            .retain(|path, _| !path.starts_with(self.path) || path.starts_with(&keep));
+    }
+
     /// Returns a read-only view of this context
     pub fn as_readonly(&mut self) -> DiagnosticContextRef<'_> {
         DiagnosticContextRef {
@@ -96,6 +155,8 @@ impl DiagnosticContextMut<'_> {
             path: &mut self.path,
             ident: self.ident,
             pop_on_exit: false,
+            filter: self.filter,
+            sources: self.sources,
         }
     }
 }
@@ -111,6 +172,8 @@ pub struct DiagnosticContextRefHolder<'a, T: 'a + ContextLike> {
     path: &'a mut DiagnosticPath,
     ident: &'a str,
     pop_on_exit: bool,
+    filter: Option<&'a dyn DiagnosticFilter>,
+    sources: Option<&'a dyn DiagnosticSources>,
 }
 
 impl<'a, T: 'a + ContextLike> DiagnosticContextRefHolder<'a, T>
@@ -127,6 +190,8 @@ where
             path: self.path,
             ident: self.ident,
             pop_on_exit: true,
+            filter: self.filter,
+            sources: self.sources,
         }
     }
 
@@ -161,6 +226,8 @@ where
             path: self.path,
             ident: self.ident,
             pop_on_exit: false,
+            filter: self.filter,
+            sources: self.sources,
         }
     }
 
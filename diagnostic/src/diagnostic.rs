@@ -1,3 +1,7 @@
+use crate::path::DiagnosticPath;
+use std::any::Any;
+use std::sync::Arc;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub enum DiagnosticLevel {
     Trace,
@@ -11,4 +15,36 @@ pub enum DiagnosticLevel {
 pub struct Diagnostic {
     pub info: miette::Report,
     pub level: DiagnosticLevel,
+    /// Machine-applicable fixes, in the order of preference
+    pub fixes: Vec<DiagnosticFix>,
+}
+
+/// Machine-applicable fix of a diagnostic
+#[derive(Debug, Clone)]
+pub struct DiagnosticFix {
+    /// Short description of the fix, shown to the user
+    pub title: String,
+    /// Path of the edited value, relative to the file of the diagnostic
+    pub path: DiagnosticPath,
+    /// Edit to apply, its type is defined by the code applying the fixes
+    edit: Arc<dyn Any + Send + Sync>,
+}
+
+impl DiagnosticFix {
+    pub fn new(
+        title: impl Into<String>,
+        path: DiagnosticPath,
+        edit: impl Any + Send + Sync,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            path,
+            edit: Arc::new(edit),
+        }
+    }
+
+    /// Returns the edit of the fix, if it is of the given type
+    pub fn edit<T: Any>(&self) -> Option<&T> {
+        self.edit.downcast_ref()
+    }
 }
@@ -1,8 +1,10 @@
 pub mod context;
 pub mod diagnostic;
 pub mod path;
+pub mod rules;
+pub mod source;
 
 pub mod prelude {
     pub use crate::context::{DiagnosticContext, DiagnosticContextMut};
-    pub use crate::diagnostic::{Diagnostic, DiagnosticLevel};
+    pub use crate::diagnostic::{Diagnostic, DiagnosticFix, DiagnosticLevel};
 }
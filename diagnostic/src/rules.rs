@@ -0,0 +1,90 @@
+use crate::diagnostic::DiagnosticLevel;
+use crate::path::DiagnosticPath;
+use miette::{Diagnostic, LabeledSpan, Report, Severity, SourceCode};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Adjusts the level of the emitted diagnostics based on their rule code
+///
+/// Rule code of a diagnostic is the [code](Diagnostic::code) of its report
+pub trait DiagnosticFilter: Send + Sync + Debug {
+    /// Returns the level the diagnostic should be emitted with, or `None` if
+    /// the diagnostic should be dropped
+    fn filter(
+        &self,
+        ident: &str,
+        path: &DiagnosticPath,
+        code: Option<&str>,
+        level: DiagnosticLevel,
+    ) -> Option<DiagnosticLevel>;
+}
+
+/// Returns the rule code of the report
+pub fn report_code(report: &Report) -> Option<String> {
+    report.code().map(|code| code.to_string())
+}
+
+/// Attaches the rule code to the report, keeping its message and sources
+///
+/// Useful for reports that are produced by fallible operations, where the
+/// code can't be specified via the `miette!` macro
+pub fn with_code(report: Report, code: &'static str) -> Report {
+    Report::new(CodedReport { code, report })
+}
+
+struct CodedReport {
+    code: &'static str,
+    report: Report,
+}
+
+impl Debug for CodedReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.report, f)
+    }
+}
+
+impl Display for CodedReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.report, f)
+    }
+}
+
+impl Error for CodedReport {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.report.source()
+    }
+}
+
+impl Diagnostic for CodedReport {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(self.code))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.report.severity()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.report.help()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.report.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.report.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.report.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.report.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.report.diagnostic_source()
+    }
+}
@@ -0,0 +1,87 @@
+use crate::path::DiagnosticPath;
+use miette::{Diagnostic, LabeledSpan, NamedSource, Report, Severity, SourceCode, SourceSpan};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+/// Source file of the diagnostics
+pub type DiagnosticSource = Arc<NamedSource<String>>;
+
+/// Locates the emitted diagnostics in the source files
+pub trait DiagnosticSources: Send + Sync + Debug {
+    /// Returns the source of the file `ident` and the span of the value at
+    /// the path, or `None` if the file has no known source
+    fn locate(&self, ident: &str, path: &DiagnosticPath) -> Option<(DiagnosticSource, SourceSpan)>;
+}
+
+/// Attaches the source snippet to the report, keeping its message and code
+///
+/// Reports that already carry their own source code are returned unchanged
+pub fn with_source(report: Report, source: DiagnosticSource, span: SourceSpan) -> Report {
+    if report.source_code().is_some() {
+        return report;
+    }
+    Report::new(LocatedReport {
+        source,
+        span,
+        report,
+    })
+}
+
+struct LocatedReport {
+    source: DiagnosticSource,
+    span: SourceSpan,
+    report: Report,
+}
+
+impl Debug for LocatedReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.report, f)
+    }
+}
+
+impl Display for LocatedReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.report, f)
+    }
+}
+
+impl Error for LocatedReport {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.report.source()
+    }
+}
+
+impl Diagnostic for LocatedReport {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.report.code()
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.report.severity()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.report.help()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.report.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&*self.source)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::underline(self.span))))
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.report.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.report.diagnostic_source()
+    }
+}
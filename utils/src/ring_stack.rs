@@ -51,6 +51,14 @@ impl<T> RingStack<T> {
     pub fn iter_mut(&mut self) -> std::collections::vec_deque::IterMut<T> {
         self.stack.iter_mut()
     }
+    pub fn retain(&mut self, f: impl FnMut(&T) -> bool) {
+        self.stack.retain(f)
+    }
+
+    pub fn retain_mut(&mut self, f: impl FnMut(&mut T) -> bool) {
+        self.stack.retain_mut(f)
+    }
+
     pub fn drain(&mut self) -> std::collections::vec_deque::Drain<T> {
         self.stack.drain(..)
     }